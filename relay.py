@@ -1,13 +1,178 @@
 import argparse
 import asyncio
+import itertools
+import json
+import os
+import shutil
+import sys
+import time
+from dataclasses import dataclass
+from pathlib import Path
 
 import telnetlib3
 from telnetlib3 import TelnetReaderUnicode, TelnetWriterUnicode
 from hypy_utils.logging_utils import setup_logger
 
+try:
+    import zstandard
+except ImportError:
+    zstandard = None
+
 
 log = setup_logger()
 
+# Version of the handshake tngame-rs expects, see tngame-rs/src/handshake.rs
+PROTOCOL_VERSION = 1
+
+# Frame compression methods we can decompress, most preferred first
+COMPRESSION = ['zstd', 'rle'] if zstandard else ['rle']
+RLE_MARK = 0xff
+
+
+def unrle(data: bytes) -> bytes:
+    # Expand runs encoded as the mark byte, the run length and the repeated byte
+    out = bytearray()
+    i = 0
+    while i < len(data):
+        if data[i] == RLE_MARK:
+            out += bytes([data[i + 2]]) * data[i + 1]
+            i += 3
+        else:
+            out.append(data[i])
+            i += 1
+    return bytes(out)
+
+
+async def read_frame(stdout: asyncio.StreamReader) -> bytes:
+    # Compressed frames start with a tag and their length, other output ends with 3 null bytes
+    tag = await stdout.readexactly(1)
+    if tag in (b'R', b'Z'):
+        length = int.from_bytes(await stdout.readexactly(4), 'big')
+        data = await stdout.readexactly(length)
+        if tag == b'R':
+            return unrle(data)
+        if zstandard is None:
+            raise RuntimeError('Got a zstd frame, but the zstandard module is not installed (pip install zstandard)')
+        return zstandard.ZstdDecompressor().decompress(data)
+    return tag + await stdout.readuntil(b'\00\00\00')
+
+
+@dataclass
+class Session:
+    player: str
+    peer: str
+    started: float
+    writer: TelnetWriterUnicode
+    proc: asyncio.subprocess.Process
+
+
+# Connected visitors by session id, for the admin console
+sessions: dict[int, Session] = {}
+session_ids = itertools.count(1)
+
+
+def data_path(var: str, file: str) -> Path:
+    # Same locations as save::data_path in tngame-rs, so both find the shared files
+    if var in os.environ:
+        return Path(os.environ[var])
+    data = os.environ.get('XDG_DATA_HOME') or os.path.join(os.environ.get('HOME', '.'), '.local/share')
+    return Path(data) / 'tngame' / file
+
+
+def give_order(key: str, value) -> None:
+    # Orders are read by every game session from the admin file, stamped so each is only followed once
+    path = data_path('TN_ADMIN', 'admin.json')
+    path.parent.mkdir(parents=True, exist_ok=True)
+    try:
+        orders = json.loads(path.read_text())
+    except (OSError, ValueError):
+        orders = {}
+    orders[key] = [value, int(time.time() * 1000)]
+    tmp = path.with_suffix('.tmp')
+    tmp.write_text(json.dumps(orders))
+    tmp.replace(path)
+
+
+ADMIN_HELP = """Commands:
+  list                           List the connected sessions
+  kick <id>                      Disconnect a session
+  say <message>                  Show a message to everyone
+  weather <snow|rain|clear> [n]  Change the weather, with an intensity from 0 to 1
+  snapshot [file]                Copy the shared world to a file"""
+
+
+def admin_command(line: str) -> str:
+    cmd, _, arg = line.strip().partition(' ')
+    arg = arg.strip()
+    if cmd in ('', 'help', '?'):
+        return ADMIN_HELP
+    if cmd == 'list':
+        if not sessions:
+            return 'No one is connected.'
+        now = time.time()
+        return '\n'.join(f'{sid:>4}  {s.player:<16}  {s.peer:<21}  {int(now - s.started) // 60}m'
+                         for sid, s in sessions.items())
+    if cmd == 'kick':
+        s = sessions.get(int(arg)) if arg.isdigit() else None
+        if not s:
+            return f'No session {arg!r}.'
+        s.writer.write('\r\nYou were disconnected by the host.\r\n')
+        if s.proc.returncode is None:
+            s.proc.kill()
+        return f'Kicked {s.player}.'
+    if cmd == 'say':
+        if not arg:
+            return 'Usage: say <message>'
+        give_order('announcement', arg)
+        return 'Announced.'
+    if cmd == 'weather':
+        kind, _, intensity = arg.partition(' ')
+        if kind not in ('snow', 'rain', 'clear'):
+            return 'Usage: weather <snow|rain|clear> [intensity]'
+        try:
+            intensity = 0.0 if kind == 'clear' else min(max(float(intensity or 1), 0.0), 1.0)
+        except ValueError:
+            return f'Not an intensity: {intensity!r}'
+        give_order('weather', {'kind': kind, 'intensity': intensity})
+        return f'Weather set to {kind}.'
+    if cmd == 'snapshot':
+        world = data_path('TN_WORLD', 'world.json')
+        if not world.exists():
+            return 'The shared world has not been saved yet.'
+        target = Path(arg) if arg else world.with_name(f'world-{time.strftime("%Y%m%d-%H%M%S")}.json')
+        shutil.copy(world, target)
+        return f'Saved the world to {target}.'
+    return f'Unknown command {cmd!r}, try help.'
+
+
+async def admin_console(reader: asyncio.StreamReader, write) -> None:
+    # Answer commands line by line until the console is closed
+    while line := await reader.readline():
+        try:
+            answer = admin_command(line.decode(errors='replace'))
+        except Exception as e:
+            answer = f'Error: {e}'
+        await write(answer + '\n')
+
+
+async def stdin_console() -> None:
+    reader = asyncio.StreamReader()
+    await asyncio.get_running_loop().connect_read_pipe(lambda: asyncio.StreamReaderProtocol(reader), sys.stdin)
+
+    async def write(text: str):
+        print(text, end='', flush=True)
+
+    await admin_console(reader, write)
+
+
+async def socket_console(reader: asyncio.StreamReader, writer: asyncio.StreamWriter) -> None:
+    async def write(text: str):
+        writer.write(text.encode())
+        await writer.drain()
+
+    await admin_console(reader, write)
+    writer.close()
+
 
 async def shell(reader: TelnetReaderUnicode, writer: TelnetWriterUnicode):
     # Get the size of the terminal
@@ -22,14 +187,36 @@ async def shell(reader: TelnetReaderUnicode, writer: TelnetWriterUnicode):
 
         return height, width
 
-    # Run tngame-rs
+    # Colors the visitor's terminal can show, going by the terminal type their client sent
+    def get_colors() -> str:
+        term = (writer.get_extra_info('TERM') or '').lower()
+        if term.startswith(('screen', 'linux', 'vt')) or term == 'xterm':
+            return '256'
+        return 'truecolor'
+
+    # Run tngame-rs in server mode, describing the visitor's terminal in the handshake
     h, w = await get_size()
+    handshake = {
+        'version': PROTOCOL_VERSION,
+        'width': w,
+        'height': h,
+        'colors': get_colors(),
+        'player': writer.get_extra_info('USER') or None,
+        'compression': COMPRESSION,
+    }
+    env = {**os.environ, 'TN_SERVER': '1', 'TN_HANDSHAKE': json.dumps(handshake)}
+    # Spectators watch a broadcasting session instead of playing their own
+    extra = ['--watch', args.watch] if args.watch else []
     proc = await asyncio.create_subprocess_exec(
-        args.bin,
+        args.bin, *extra,
         stdin=asyncio.subprocess.PIPE,
         stdout=asyncio.subprocess.PIPE,
-        env={'TN_TERM_SIZE': f'{w}x{h}'}
+        env=env
     )
+    sid = next(session_ids)
+    peer = writer.get_extra_info('peername')
+    sessions[sid] = Session(handshake['player'] or 'Visitor', f'{peer[0]}:{peer[1]}' if peer else '?',
+                            time.time(), writer, proc)
 
     async def relay_stdout():
         # Listen input
@@ -40,7 +227,7 @@ async def shell(reader: TelnetReaderUnicode, writer: TelnetWriterUnicode):
 
             # Read output
             try:
-                out = await proc.stdout.readuntil(b'\00\00\00')
+                out = await read_frame(proc.stdout)
                 if out:
                     # print("Sending output from tngame-rs to telnet:", repr(out))
                     writer.write(out.decode())
@@ -73,6 +260,7 @@ async def shell(reader: TelnetReaderUnicode, writer: TelnetWriterUnicode):
         for task in tasks:
             task.cancel()
 
+    sessions.pop(sid, None)
     if proc.returncode is None:
         print("Killing process.")
         proc.kill()
@@ -80,8 +268,9 @@ async def shell(reader: TelnetReaderUnicode, writer: TelnetWriterUnicode):
     # Print exit message
     writer.write("\r\nThanks for visiting <3\r\n")
 
-    # Make cursor visible again
+    # Make cursor visible again, and restore the window title
     writer.write("\x1b[?25h")
+    writer.write("\x1b[23;2t")
 
     await writer.drain()
     writer.close()
@@ -94,6 +283,10 @@ if __name__ == '__main__':
     parser = argparse.ArgumentParser()
     parser.add_argument('--port', type=int, default=2323)
     parser.add_argument('--bin', type=str, default='./tngame-rs/target/release/tngame-rs')
+    parser.add_argument('--watch', type=str, default=None,
+                        help='Socket of a session started with --broadcast, to show to visitors read-only')
+    parser.add_argument('--admin-socket', type=str, default=None,
+                        help='Unix socket to serve the admin console on, besides stdin when it is a terminal')
     args = parser.parse_args()
 
     # Check if bin file exists and is executable
@@ -107,4 +300,10 @@ if __name__ == '__main__':
     asyncio.set_event_loop(loop)
     coro = telnetlib3.create_server(port=args.port, shell=shell)
     server = loop.run_until_complete(coro)
+
+    # Admin console, see "help" in it for the commands
+    if sys.stdin.isatty():
+        loop.create_task(stdin_console())
+    if args.admin_socket:
+        loop.run_until_complete(asyncio.start_unix_server(socket_console, args.admin_socket))
     loop.run_until_complete(server.wait_closed())
@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::save;
+use crate::weather::Weather;
+
+/// Seconds between checking for new orders
+const POLL_INTERVAL: f32 = 1.0;
+
+/// What the host sent from the relay's admin console to every session, with the unix time in
+/// milliseconds each order was given at
+#[derive(Deserialize, Default, Clone)]
+pub struct Orders {
+    #[serde(default)]
+    pub announcement: Option<(String, u64)>,
+    #[serde(default)]
+    pub weather: Option<(Weather, u64)>,
+}
+
+/// Follows the orders of the admin console in server mode
+pub struct Admin {
+    path: PathBuf,
+    last_poll: f32,
+    pub orders: Orders,
+    /// When the last weather order that was applied was given
+    applied_weather: u64,
+}
+
+impl Admin {
+    /// Location of the orders file, which can be overridden with TN_ADMIN
+    pub fn open() -> Option<Self> {
+        let path = save::data_path("TN_ADMIN", "admin.json")?;
+        Some(Self { path, last_poll: f32::MIN, orders: Orders::default(), applied_weather: 0 })
+    }
+
+    /// Read the orders every second, returning the weather to switch to when it was changed
+    pub fn poll(&mut self, time: f32) -> Option<Weather> {
+        if time - self.last_poll < POLL_INTERVAL { return None; }
+        self.last_poll = time;
+        self.orders = fs::read_to_string(&self.path).ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let (weather, at) = self.orders.weather?;
+        if at == self.applied_weather { return None; }
+        self.applied_weather = at;
+        Some(weather)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::Precipitation;
+
+    fn admin(name: &str) -> Admin {
+        let path = std::env::temp_dir().join(format!("tngame-admin-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        Admin { path, last_poll: f32::MIN, orders: Orders::default(), applied_weather: 0 }
+    }
+
+    // Orders as the relay's admin console writes them
+    fn order(a: &Admin, kind: &str, at: u64) {
+        let json = format!(r#"{{"announcement": ["Hello!", 5], "weather": [{{"kind": "{}", "intensity": 0.5}}, {}]}}"#, kind, at);
+        fs::write(&a.path, json).unwrap();
+    }
+
+    #[test]
+    fn follows_each_weather_order_once() {
+        let mut a = admin("once");
+        order(&a, "rain", 10);
+        assert_eq!(a.poll(0.0), Some(Weather { kind: Precipitation::Rain, intensity: 0.5 }));
+        assert_eq!(a.orders.announcement, Some(("Hello!".to_string(), 5)));
+        assert_eq!(a.poll(2.0), None);
+        order(&a, "clear", 20);
+        assert_eq!(a.poll(4.0).map(|w| w.kind), Some(Precipitation::Clear));
+        fs::remove_file(&a.path).unwrap();
+    }
+
+    #[test]
+    fn reads_the_orders_once_a_second() {
+        let mut a = admin("interval");
+        assert_eq!(a.poll(0.0), None);
+        order(&a, "rain", 10);
+        assert_eq!(a.poll(POLL_INTERVAL / 2.0), None);
+        assert!(a.poll(POLL_INTERVAL).is_some());
+        fs::remove_file(&a.path).unwrap();
+    }
+
+    #[test]
+    fn bad_orders_are_no_orders() {
+        let mut a = admin("bad");
+        fs::write(&a.path, "not json").unwrap();
+        assert_eq!(a.poll(0.0), None);
+        assert!(a.orders.announcement.is_none());
+        fs::remove_file(&a.path).unwrap();
+    }
+}
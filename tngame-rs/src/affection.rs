@@ -0,0 +1,126 @@
+/// Affection constants
+const MAX: f32 = 100.0;
+const START: f32 = 40.0;
+const DECAY: f32 = 0.5; // Affection lost every second
+const PET_GAIN: f32 = 6.0;
+const FEED_GAIN: f32 = 25.0;
+const PET_COOLDOWN: f32 = 0.8; // Seconds before petting raises the meter again
+const HEARTS: usize = 5; // Hearts shown in the meter
+
+/// How the cat feels, based on the affection meter
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mood {
+    Lonely,
+    Content,
+    Happy,
+}
+
+/// Warmth and affection of the cat, raised by petting and feeding and slowly cooling down
+pub struct Affection {
+    value: f32,
+    last_pet: f32,
+}
+
+impl Affection {
+    pub fn new() -> Self {
+        Self { value: START, last_pet: f32::NEG_INFINITY }
+    }
+
+    /// Affection picked up from where it was, like from an autosave
+    pub fn with_value(value: f32) -> Self {
+        Self { value: value.clamp(0.0, MAX), ..Self::new() }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.value = (self.value - DECAY * dt).max(0.0);
+    }
+
+    pub fn mood(&self) -> Mood {
+        match self.value {
+            v if v < 25.0 => Mood::Lonely,
+            v if v < 70.0 => Mood::Content,
+            _ => Mood::Happy,
+        }
+    }
+
+    /// Pet the cat at the given game time, returning what the cat purrs
+    pub fn pet(&mut self, time: f32) -> &'static str {
+        if time - self.last_pet >= PET_COOLDOWN {
+            self.value = (self.value + PET_GAIN).min(MAX);
+        }
+        self.last_pet = time;
+        match self.mood() {
+            Mood::Lonely => "...purr?",
+            Mood::Content => "Purr~",
+            Mood::Happy => "Purrrrrr <3",
+        }
+    }
+
+    /// Feed the cat a fish, returning what the cat says
+    pub fn feed(&mut self) -> &'static str {
+        self.value = (self.value + FEED_GAIN).min(MAX);
+        match self.mood() {
+            Mood::Happy => "Nom nom!\nBest fish ever <3",
+            _ => "Nom nom!\nThank you!",
+        }
+    }
+
+    /// The meter as a row of full and empty hearts
+    pub fn hearts(&self) -> String {
+        let full = ((self.value / MAX) * HEARTS as f32).ceil() as usize;
+        "♥".repeat(full) + &"♡".repeat(HEARTS - full)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn petting_raises_the_meter_once_per_cooldown() {
+        let mut a = Affection::new();
+        a.pet(0.0);
+        assert_eq!(a.value(), START + PET_GAIN);
+        // Petting again right away doesn't count, and starts the cooldown over
+        a.pet(PET_COOLDOWN / 2.0);
+        assert_eq!(a.value(), START + PET_GAIN);
+        a.pet(PET_COOLDOWN);
+        assert_eq!(a.value(), START + PET_GAIN);
+        a.pet(PET_COOLDOWN * 3.0);
+        assert_eq!(a.value(), START + PET_GAIN * 2.0);
+    }
+
+    #[test]
+    fn feeding_makes_the_cat_happy_up_to_the_max() {
+        let mut a = Affection::new();
+        assert_eq!(a.feed(), "Nom nom!\nThank you!");
+        assert_eq!(a.feed(), "Nom nom!\nBest fish ever <3");
+        assert_eq!(a.mood(), Mood::Happy);
+        for _ in 0..10 {
+            a.feed();
+        }
+        assert_eq!(a.value(), MAX);
+    }
+
+    #[test]
+    fn cools_down_to_lonely_over_time() {
+        let mut a = Affection::new();
+        assert_eq!(a.mood(), Mood::Content);
+        a.update(START / DECAY - 1.0);
+        assert_eq!(a.mood(), Mood::Lonely);
+        assert_eq!(a.pet(100.0), "...purr?");
+        a.update(1000.0);
+        assert_eq!(a.value(), 0.0);
+    }
+
+    #[test]
+    fn hearts_fill_with_the_meter() {
+        assert_eq!(Affection::with_value(0.0).hearts(), "♡♡♡♡♡");
+        assert_eq!(Affection::with_value(40.0).hearts(), "♥♥♡♡♡");
+        assert_eq!(Affection::with_value(500.0).hearts(), "♥♥♥♥♥");
+    }
+}
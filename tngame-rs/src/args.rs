@@ -0,0 +1,88 @@
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::terrain::Params;
+use crate::theme::Theme;
+
+/// Command line arguments
+#[derive(Default)]
+pub struct Args {
+    /// File to record the input to, so that the session can be replayed
+    pub record_inputs: Option<PathBuf>,
+    /// File of recorded input to replay
+    pub play_inputs: Option<PathBuf>,
+    /// Unix socket to mirror the session to for spectators
+    pub broadcast: Option<PathBuf>,
+    /// Unix socket of a broadcasting session to watch instead of playing
+    pub watch: Option<PathBuf>,
+    /// Show the animation as a login greeter instead of playing, or stream it, for a number of seconds
+    pub greet: bool,
+    pub duration: Option<f32>,
+    /// Print a single frame as plain text instead of playing
+    pub frame: bool,
+    /// File or pipe to write frames to without a terminal, "-" for stdout
+    pub stream: Option<PathBuf>,
+    /// Leave out background colors, so the background of a terminal or tmux pane shows through
+    pub transparent_bg: bool,
+    /// Map file to load on top of the built-in scene
+    pub map: Option<PathBuf>,
+    /// Map file to edit, created when it doesn't exist yet
+    pub edit: Option<PathBuf>,
+    /// Colors to draw with, overriding TN_THEME
+    pub theme: Option<Theme>,
+    /// Slow the snow down and leave out shaking and flashing, for visitors sensitive to motion
+    pub reduce_motion: bool,
+    /// Let the cat get cold at night and in blizzards, and warm up by the fire
+    pub cozy: bool,
+    /// Tell what happens in plain sentences for screen readers instead of drawing the screen
+    pub narrate: bool,
+    /// World to generate from a world code, or a new random one for "new"
+    pub world: Option<Params>,
+    /// File to export the world to as JSON instead of playing, and a world file to import and play in
+    pub export_world: Option<PathBuf>,
+    pub import_world: Option<PathBuf>,
+    /// File to report frame times to, after playing under the load of a crowd and ten times the snow
+    pub stress: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("Missing a value for {}", arg));
+            match arg.as_str() {
+                "--record-inputs" => parsed.record_inputs = Some(value()?.into()),
+                "--play-inputs" => parsed.play_inputs = Some(value()?.into()),
+                "--broadcast" => parsed.broadcast = Some(value()?.into()),
+                "--watch" => parsed.watch = Some(value()?.into()),
+                "--greet" => parsed.greet = true,
+                "--frame" => parsed.frame = true,
+                "--stream" => parsed.stream = Some(value()?.into()),
+                "--transparent-bg" => parsed.transparent_bg = true,
+                "--map" => parsed.map = Some(value()?.into()),
+                "--edit" => parsed.edit = Some(value()?.into()),
+                "--narrate" => parsed.narrate = true,
+                "--reduce-motion" => parsed.reduce_motion = true,
+                "--cozy" => parsed.cozy = true,
+                "--theme" => parsed.theme = Some(Theme::parse(&value()?)?),
+                "--export-world" => parsed.export_world = Some(value()?.into()),
+                "--import-world" => parsed.import_world = Some(value()?.into()),
+                "--stress" => parsed.stress = Some(value()?.into()),
+                "--world-code" => parsed.world = Some(match value()?.as_str() {
+                    "new" => Params { seed: rand::random(), ..Params::default() },
+                    code => Params::from_code(code)?,
+                }),
+                "--duration" => {
+                    let secs = value()?;
+                    parsed.duration = Some(secs.parse().ok().filter(|s: &f32| *s > 0.0)
+                        .ok_or_else(|| anyhow!("Invalid duration: {}", secs))?);
+                }
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use crate::palette::Palette;
+use crate::sprite::Sprite;
+use crate::{npc, toast, AsciiArt, Consts};
+
+/// Folder of art overriding the built-in art, set with TN_ASSETS. Every file is named after the art it
+/// replaces, like "tree.txt" or "house.txt", colors.toml replaces the colors and theme of the scene,
+/// and dialogue.toml replaces what the NPCs say.
+pub fn dir() -> Option<PathBuf> {
+    env::var("TN_ASSETS").ok().map(PathBuf::from)
+}
+
+/// Load the built-in art with the overrides of the assets folder
+pub fn load() -> Arc<Consts> {
+    load_with(dir().as_deref(), &[])
+}
+
+/// Load the built-in art with the overrides of an assets folder, then replace art by name with the
+/// given art, like an embedding app would
+pub fn load_with(dir: Option<&Path>, overrides: &[(String, String)]) -> Arc<Consts> {
+    let mut cn = Consts::new();
+    if let Some(dir) = dir {
+        apply(&mut cn, dir);
+        cn.colors = colors(dir);
+        cn.dialogue = dialogue(dir);
+    }
+    for (name, text) in overrides {
+        match arts(&mut cn).into_iter().find(|(n, _)| n == name) {
+            Some((_, art)) => *art = AsciiArt::new(text, &art.credit),
+            None => log::warn!("Ignoring the art for {}, which isn't in the game", name),
+        }
+    }
+    // The cat looks neutral in its own art, which its other moods are drawn next to
+    cn.cat_sprites[0] = Sprite::still(&cn.asc_cat.art, &cn.asc_cat.credit);
+    Arc::new(cn)
+}
+
+/// The art that can be replaced, by the name of its file in the assets folder
+fn arts(cn: &mut Consts) -> [(&'static str, &mut AsciiArt); 22] {
+    [
+        ("cat", &mut cn.asc_cat),
+        ("cat-sitting", &mut cn.asc_cat_sitting),
+        ("cat-skating", &mut cn.asc_cat_skating),
+        ("cat-loaf", &mut cn.asc_cat_loaf),
+        ("cat-sleeping", &mut cn.asc_cat_sleeping),
+        ("fish", &mut cn.asc_fish),
+        ("sign", &mut cn.asc_sign),
+        ("board", &mut cn.asc_board),
+        ("website", &mut cn.asc_website),
+        ("tree", &mut cn.asc_tree),
+        ("house", &mut cn.asc_house),
+        ("title", &mut cn.asc_title),
+        ("soon", &mut cn.asc_soon),
+        ("sled", &mut cn.asc_sled),
+        ("snowman", &mut cn.asc_snowman),
+        ("lamp", &mut cn.asc_lamp),
+        ("campfire", &mut cn.asc_campfire),
+        ("bench", &mut cn.asc_bench),
+        ("signpost", &mut cn.asc_signpost),
+        ("bulletin", &mut cn.asc_bulletin),
+        ("sun", &mut cn.asc_sun),
+        ("moon", &mut cn.asc_moon),
+    ]
+}
+
+/// Replace the art that has a file in the assets folder, keeping the credit of the built-in art
+fn apply(cn: &mut Consts, dir: &Path) {
+    for (name, art) in arts(cn) {
+        let path = dir.join(format!("{}.txt", name));
+        match fs::read_to_string(&path) {
+            Ok(text) if !text.trim().is_empty() => *art = AsciiArt::new(&text, &art.credit),
+            Ok(_) => {
+                log::warn!("Ignoring the empty art in {}", path.display());
+                toast::report(format!("The art in {}.txt is empty", name));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", path.display(), e);
+                toast::report(format!("Couldn't load the art in {}.txt", name));
+            }
+        }
+    }
+}
+
+/// The colors of the scene, with the ones set in colors.toml in the assets folder replaced. A file
+/// that can't be used is left out as a whole.
+fn colors(dir: &Path) -> Palette {
+    let path = dir.join("colors.toml");
+    let parsed = match fs::read_to_string(&path) {
+        Ok(text) => Palette::parse(&text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Palette::default(),
+        Err(e) => Err(e.into()),
+    };
+    parsed.unwrap_or_else(|e| {
+        log::warn!("Failed to load the colors in {}: {}", path.display(), e);
+        toast::report("Couldn't load the colors in colors.toml");
+        Palette::default()
+    })
+}
+
+/// What the NPCs say, with the lines set in dialogue.toml in the assets folder replacing the built-in
+/// ones. Every NPC has a table of lines by the number of the node in its conversation, counted from 0:
+///
+/// [Fox]
+/// 0 = "Hey there!"
+///
+/// A file that can't be used is left out as a whole.
+fn dialogue(dir: &Path) -> HashMap<(usize, usize), String> {
+    let path = dir.join("dialogue.toml");
+    let parsed = match fs::read_to_string(&path) {
+        Ok(text) => parse_dialogue(&text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+        Err(e) => Err(e.into()),
+    };
+    parsed.unwrap_or_else(|e| {
+        log::warn!("Failed to load the dialogue in {}: {}", path.display(), e);
+        toast::report("Couldn't load the dialogue in dialogue.toml");
+        HashMap::new()
+    })
+}
+
+fn parse_dialogue(text: &str) -> Result<HashMap<(usize, usize), String>> {
+    let file: HashMap<String, HashMap<String, String>> = toml::from_str(text)?;
+    let mut lines = HashMap::new();
+    for (name, nodes) in file {
+        let i = npc::NPCS.iter().position(|n| n.name == name).ok_or_else(|| anyhow!("Unknown NPC: {}", name))?;
+        for (node, line) in nodes {
+            let node = node.parse::<usize>().ok().filter(|&n| n < npc::NPCS[i].nodes.len())
+                .ok_or_else(|| anyhow!("{} has no node {}", name, node))?;
+            lines.insert((i, node), line);
+        }
+    }
+    Ok(lines)
+}
+
+/// Reload the art whenever a file in the assets folder changes, so artists can see their changes
+/// while the game is running. The art given by the embedding app stays on top of the folder's.
+#[cfg(feature = "hot-reload")]
+pub fn watch(dir: PathBuf, overrides: Vec<(String, String)>, mt: std::sync::Arc<tokio::sync::Mutex<crate::Mutes>>) {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use notify::event::ModifyKind;
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    // Editors save in several steps, so changes are collected for a moment before reloading
+    const SETTLE: Duration = Duration::from_millis(100);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(w) => w,
+        Err(e) => {
+            log::warn!("Failed to watch the assets: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        log::warn!("Failed to watch {}: {}", dir.display(), e);
+        return;
+    }
+    while let Ok(event) = rx.recv() {
+        // Only changed files count, since reading them to reload reports events too
+        match event {
+            Ok(e) if matches!(e.kind, EventKind::Create(_) | EventKind::Remove(_)
+                | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)) => (),
+            Ok(_) => continue,
+            Err(e) => {
+                log::warn!("Error watching the assets: {}", e);
+                continue;
+            }
+        }
+        std::thread::sleep(SETTLE);
+        while rx.try_recv().is_ok() {}
+        let cn = load_with(Some(&dir), &overrides);
+        let mut mt = mt.blocking_lock();
+        if mt.should_exit { break; }
+        // A theme only takes over when the file changes it, so one picked in the settings stays
+        if cn.colors.theme != mt.assets.colors.theme {
+            mt.renderer.theme = cn.colors.theme.unwrap_or(mt.renderer.theme);
+        }
+        mt.assets = cn;
+        drop(mt);
+        log::info!("Reloaded the assets from {}", dir.display());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::affection::Affection;
+use crate::inventory::Inventory;
+use crate::{leaderboard, save, toast, Mutes};
+
+/// Seconds of play between autosaves
+pub const INTERVAL: f32 = 10.0;
+
+/// Where the cat was and what it had, written every few seconds while playing and removed when the
+/// game is quit. Finding one when the game starts means the last visit ended suddenly, like when an
+/// SSH connection dropped, and it can be picked up from there.
+#[derive(Serialize, Deserialize)]
+pub struct Autosave {
+    x: i32,
+    inventory: Inventory,
+    affection: f32,
+    /// Snowmen only last for the session outside of server mode, so they are kept here too
+    snowmen: Vec<i32>,
+}
+
+impl Autosave {
+    /// Location of the autosave, which can be overridden with TN_AUTOSAVE. The visitors of a server
+    /// share its data folder and can't be told apart, so in server mode there is none, rather than one
+    /// they would be offered and remove for each other.
+    fn path(server: bool) -> Option<PathBuf> {
+        if server { return None; }
+        save::data_path("TN_AUTOSAVE", "autosave.json")
+    }
+
+    /// Whether this game has a place to keep an autosave
+    pub fn available() -> bool {
+        Self::path(leaderboard::server_mode()).is_some()
+    }
+
+    /// The autosave left behind by a visit that didn't end by quitting, if there is one
+    pub fn load() -> Option<Self> {
+        let path = Self::path(leaderboard::server_mode())?;
+        let json = fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&json).map_err(|e| {
+            log::warn!("Failed to parse the autosave {}: {}", path.display(), e);
+            toast::report("Couldn't read the autosave");
+        }).ok()
+    }
+
+    pub fn take(mt: &Mutes) -> Self {
+        Self {
+            x: mt.world.x,
+            inventory: mt.world.inventory.clone(),
+            affection: mt.affection.value(),
+            snowmen: mt.shared.snowmen.clone(),
+        }
+    }
+
+    /// Put the cat back where it was, with what it had
+    pub fn restore(self, mt: &mut Mutes) {
+        mt.world.x = self.x;
+        mt.world.draw_x = self.x as f32;
+        mt.world.inventory = self.inventory;
+        mt.affection = Affection::with_value(self.affection);
+        for x in self.snowmen {
+            mt.shared.build_snowman(x);
+        }
+    }
+
+    /// Write the autosave in one rename, so a visit ending halfway through leaves the last one intact
+    pub fn write(&self) -> Result<()> {
+        let path = Self::path(leaderboard::server_mode()).ok_or_else(|| anyhow!("No location for the autosave"))?;
+        let dir = path.parent().ok_or_else(|| anyhow!("No folder for the autosave"))?;
+        fs::create_dir_all(dir)?;
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Remove the autosave once the game was quit
+    pub fn remove() {
+        let Some(path) = Self::path(leaderboard::server_mode()) else { return; };
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => log::warn!("Failed to remove the autosave {}: {}", path.display(), e),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    fn mutes() -> Mutes {
+        Mutes::detached(assets::load_with(None, &[]), (80, 24))
+    }
+
+    #[test]
+    fn restores_the_visit_it_was_taken_from() {
+        let mut mt = mutes();
+        mt.world.x = 123;
+        mt.world.inventory.fish = 2;
+        mt.world.inventory.coins = 7;
+        mt.affection = Affection::with_value(80.0);
+        mt.shared.build_snowman(50);
+        let json = serde_json::to_string(&Autosave::take(&mt)).unwrap();
+
+        let mut restored = mutes();
+        serde_json::from_str::<Autosave>(&json).unwrap().restore(&mut restored);
+        assert_eq!((restored.world.x, restored.world.draw_x), (123, 123.0));
+        assert_eq!((restored.world.inventory.fish, restored.world.inventory.coins), (2, 7));
+        assert_eq!(restored.affection.value(), 80.0);
+        assert_eq!(restored.shared.snowmen, mt.shared.snowmen);
+    }
+
+    #[test]
+    fn server_sessions_share_no_autosave() {
+        // Every visitor of a server would get the same file, so none of them gets one
+        assert_eq!(Autosave::path(true), None);
+    }
+}
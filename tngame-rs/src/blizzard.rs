@@ -0,0 +1,72 @@
+use rand::Rng;
+
+use crate::utils;
+
+/// Seconds of game time between the end of a blizzard and the next one, at least and at most
+const INTERVAL: (f32, f32) = (240.0, 600.0);
+
+/// Seconds a blizzard rages, at least and at most, and the seconds it takes to build up and to die down
+const DURATION: (f32, f32) = (30.0, 60.0);
+const RAMP: f32 = 6.0;
+
+/// Seconds the warning shows before a blizzard hits
+const WARNING: f32 = 10.0;
+
+/// How much more snow falls and how much harder the wind blows at the height of a blizzard
+pub const DENSITY: f32 = 3.0;
+pub const WIND: f32 = 4.0;
+
+/// Columns around the cat that stay clear at the height of a blizzard, past which everything fades
+/// into the snow
+pub const VISIBILITY: f32 = 12.0;
+
+/// Blizzards sweeping over the world now and then, announced a few seconds before they hit
+pub struct Blizzard {
+    /// Game time the next or current blizzard hits and ends at
+    hits: f32,
+    ends: f32,
+}
+
+impl Blizzard {
+    /// Schedule the first blizzard after a game time
+    pub fn new(time: f32) -> Self {
+        let mut b = Self { hits: 0.0, ends: 0.0 };
+        b.schedule(time + interval());
+        b
+    }
+
+    fn schedule(&mut self, hits: f32) {
+        self.hits = hits;
+        self.ends = hits + utils::rng().gen_range(DURATION.0..DURATION.1);
+    }
+
+    /// Schedule the next blizzard once the last one is over
+    pub fn update(&mut self, time: f32) {
+        if time >= self.ends {
+            self.schedule(time + interval());
+        }
+    }
+
+    /// Call up a blizzard right away, after the warning, unless one is already coming or raging
+    pub fn start(&mut self, time: f32) {
+        if time < self.hits - WARNING {
+            self.schedule(time + WARNING);
+        }
+    }
+
+    /// Whether a blizzard is about to hit, for the warning
+    pub fn coming(&self, time: f32) -> bool {
+        (self.hits - WARNING..self.hits).contains(&time)
+    }
+
+    /// How hard the blizzard blows at a game time, from 0 when there is none to 1 at its height
+    pub fn strength(&self, time: f32) -> f32 {
+        if !(self.hits..self.ends).contains(&time) { return 0.0; }
+        ((time - self.hits) / RAMP).min((self.ends - time) / RAMP).clamp(0.0, 1.0)
+    }
+}
+
+/// Seconds until the next blizzard
+fn interval() -> f32 {
+    utils::rng().gen_range(INTERVAL.0..INTERVAL.1)
+}
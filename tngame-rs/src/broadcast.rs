@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+#[cfg(unix)]
+use tokio::io::{stdin, stdout, AsyncReadExt, AsyncWriteExt};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::transport::FRAME_END;
+
+/// Windows has no unix sockets to broadcast on
+#[cfg(not(unix))]
+const NO_SOCKETS: &str = "Broadcasting needs unix sockets, which Windows doesn't have";
+
+/// Frames queued for a viewer, after which new frames are skipped until the viewer catches up
+#[cfg(unix)]
+const VIEWER_QUEUE: usize = 4;
+
+/// Frames waiting to be written to a viewer
+type Viewer = mpsc::Sender<Arc<[u8]>>;
+
+/// Mirrors the frames of an interactive session to any number of read-only viewers connected to a
+/// unix socket. Every frame clears the screen, so viewers can join at any time.
+pub struct Broadcast {
+    path: PathBuf,
+    viewers: Arc<Mutex<Vec<Viewer>>>,
+}
+
+impl Broadcast {
+    /// Listen for viewers on the socket in the background, which needs a running tokio runtime
+    #[cfg(not(unix))]
+    pub fn listen(_path: &Path) -> Result<Self> {
+        anyhow::bail!(NO_SOCKETS)
+    }
+
+    #[cfg(unix)]
+    pub fn listen(path: &Path) -> Result<Self> {
+        // Remove the socket of an earlier session that didn't exit cleanly
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        let viewers = Arc::new(Mutex::new(Vec::new()));
+        let accepted = viewers.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let (tx, rx) = mpsc::channel(VIEWER_QUEUE);
+                        accepted.lock().unwrap().push(tx);
+                        tokio::spawn(feed(stream, rx));
+                    }
+                    Err(e) => {
+                        log::warn!("Stopped accepting viewers: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Self { path: path.to_path_buf(), viewers })
+    }
+
+    /// Send a drawn frame to every viewer, forgetting the ones that left
+    pub fn send(&self, frame: &str) {
+        let frame: Arc<[u8]> = [frame, FRAME_END].concat().into_bytes().into();
+        self.viewers.lock().unwrap().retain(|tx| !matches!(tx.try_send(frame.clone()), Err(TrySendError::Closed(_))));
+    }
+
+    /// Number of viewers watching
+    pub fn viewers(&self) -> usize {
+        self.viewers.lock().unwrap().len()
+    }
+}
+
+impl Drop for Broadcast {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Write the frames queued for a viewer until it disconnects
+#[cfg(unix)]
+async fn feed(mut stream: UnixStream, mut frames: mpsc::Receiver<Arc<[u8]>>) {
+    while let Some(frame) = frames.recv().await {
+        if stream.write_all(&frame).await.is_err() { break; }
+    }
+}
+
+/// Show the frames of a broadcasting session until it ends or the viewer presses q
+#[cfg(not(unix))]
+pub async fn watch(_path: &Path) -> Result<()> {
+    anyhow::bail!(NO_SOCKETS)
+}
+
+#[cfg(unix)]
+pub async fn watch(path: &Path) -> Result<()> {
+    let mut stream = UnixStream::connect(path).await?;
+    let (mut out, mut keys) = (stdout(), stdin());
+    let mut buf = vec![0; 64 * 1024];
+    let mut key = [0; 64];
+    loop {
+        tokio::select! {
+            n = stream.read(&mut buf) => {
+                let n = n?;
+                if n == 0 { break; }
+                out.write_all(&buf[..n]).await?;
+                out.flush().await?;
+            }
+            n = keys.read(&mut key) => {
+                let n = n?;
+                if n == 0 || key[..n].iter().any(|&k| k == b'q' || k == 0x03) { break; }
+            }
+        }
+    }
+    Ok(())
+}
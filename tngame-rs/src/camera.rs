@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+
+use crate::shake::Shake;
+use crate::AsciiArt;
+
+/// Which part of the world is on screen: how far it is scrolled, how many world cells each column
+/// shows, and how much the screen shakes
+pub struct Camera {
+    /// Columns of the screen
+    w: i32,
+    /// World cells drawn in each column of the screen, more than one when zoomed out
+    pub zoom: i32,
+    /// Shake of the screen applied when the buffer is drawn
+    pub shake: Shake,
+}
+
+impl Camera {
+    pub fn new(w: i32) -> Self {
+        Self { w, zoom: 1, shake: Shake::default() }
+    }
+
+    /// World x of the left edge of the screen, keeping a world x three quarters of the way across
+    pub fn scroll(&self, follow: f32) -> i32 {
+        0.max(follow.floor() as i32 - (self.view_w() * 3 / 4))
+    }
+
+    /// Cells of the world on screen
+    pub fn view_w(&self) -> i32 {
+        self.w * self.zoom
+    }
+
+    /// Screen column of a world x, with the screen scrolled to a world x
+    pub fn screen_x(&self, x: i32, scroll: i32) -> i32 {
+        (x - scroll).div_euclid(self.zoom)
+    }
+
+    /// Art as it is drawn at the current zoom, keeping the first character that isn't a space out of
+    /// every few columns when zoomed out
+    pub fn zoomed<'a>(&self, art: &'a AsciiArt) -> Cow<'a, AsciiArt> {
+        if self.zoom == 1 { return Cow::Borrowed(art); }
+        let lines: Vec<String> = art.art.lines().map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            chars.chunks(self.zoom as usize).map(|c| c.iter().copied().find(|c| *c != ' ').unwrap_or(' ')).collect()
+        }).collect();
+        Cow::Owned(AsciiArt { art: lines.join("\n"), w: (art.w + self.zoom - 1) / self.zoom, ..art.clone() })
+    }
+}
@@ -0,0 +1,151 @@
+use std::env;
+
+use chrono::{Local, Timelike};
+
+use crate::color::{self, Direction, Gradient, Rgb};
+use crate::render::Pixel;
+use crate::{AsciiArt, Mutes, utils};
+
+/// Clock constants
+const DAY_LENGTH: f32 = 600.0; // Real seconds per in-game day when not synced to real time
+const START_HOUR: f32 = 10.0; // Time of day when the game starts when not synced to real time
+const SUNRISE: f32 = 6.0;
+const SUNSET: f32 = 20.0;
+
+/// Sky constants
+const STAR_DENSITY: u32 = 40; // One in every STAR_DENSITY pixels of the upper sky has a star
+const STAR_CHARS: [char; 3] = ['.', '+', '*'];
+const COLOR_STAR: &str = "\x1b[38;2;200;210;255m";
+const COLOR_SUN: &str = "\x1b[38;2;255;220;110m";
+const COLOR_MOON: &str = "\x1b[38;2;235;235;210m";
+
+/// Aurora constants
+const AURORA_SEED: u32 = 0xa42;
+const AURORA_BANDS: u32 = 2;
+const AURORA_BEND: f32 = 16.0; // Columns between two bends of a band
+const AURORA_WAVE: f32 = 0.05; // Bends a band moves through every second
+const AURORA_OCTAVES: u32 = 3;
+const AURORA_GAPS: f32 = 0.55; // Share of a band left out, where the light fades
+const AURORA_CHARS: [char; 3] = ['~', ':', '.']; // From the bright top of a band to its faint rays
+const AURORA_COLORS: &[Rgb] = &[[120, 255, 170], [190, 140, 255]]; // From the low parts of a band to the high ones
+
+/// Part of the day, used to pick the sky and the behavior of characters
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+/// In-game time of day, either running on its own accelerated cycle or following the local time
+#[derive(Clone, Copy)]
+pub struct Clock {
+    real_time: bool,
+    hours: f32,
+}
+
+impl Clock {
+    /// Create a clock, synced to the local time if TN_REAL_TIME is set. Since the local time zone is
+    /// read from TZ, front-ends can pass the visitor's time zone through it.
+    pub fn new() -> Self {
+        let mut clock = Self {
+            real_time: env::var("TN_REAL_TIME").is_ok(),
+            hours: START_HOUR,
+        };
+        clock.update(0.0);
+        clock
+    }
+
+    /// Advance the clock
+    pub fn update(&mut self, dt: f32) {
+        if self.real_time {
+            let now = Local::now();
+            self.hours = now.hour() as f32 + now.minute() as f32 / 60.0 + now.second() as f32 / 3600.0;
+        } else {
+            self.hours = (self.hours + dt * 24.0 / DAY_LENGTH) % 24.0;
+        }
+    }
+
+    /// Time of day in hours
+    pub fn hours(&self) -> f32 {
+        self.hours
+    }
+
+    /// Jump to a time of day, leaving the local time behind if the clock followed it
+    pub fn set(&mut self, hours: f32) {
+        self.real_time = false;
+        self.hours = hours % 24.0;
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self.hours {
+            h if h < SUNRISE - 1.0 => Phase::Night,
+            h if h < SUNRISE + 1.0 => Phase::Dawn,
+            h if h < SUNSET - 1.0 => Phase::Day,
+            h if h < SUNSET + 1.0 => Phase::Dusk,
+            _ => Phase::Night,
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.phase() == Phase::Night
+    }
+
+    /// Progress of the sun through the day, or of the moon through the night, across the sky from its
+    /// left to its right edge, from 0 to 1, and whether it is the sun
+    pub fn light(&self) -> (f32, bool) {
+        if (SUNRISE..SUNSET).contains(&self.hours) {
+            ((self.hours - SUNRISE) / (SUNSET - SUNRISE), true)
+        } else {
+            let night = 24.0 - SUNSET + SUNRISE;
+            (((self.hours - SUNSET).rem_euclid(24.0)) / night, false)
+        }
+    }
+
+    /// Draw the stars, and the sun or the moon moving along an arc across the sky
+    pub fn draw_sky(&self, mt: &mut Mutes, sun: &AsciiArt, moon: &AsciiArt) {
+        let sky_h = mt.renderer.h / 2;
+
+        // Stars only come out at night and twinkle slowly
+        if self.is_night() {
+            let tick = (mt.time / 2.0) as u32;
+            for y in 0..sky_h {
+                for x in 0..mt.renderer.w {
+                    let hash = utils::hash((y * mt.renderer.w + x) as u32 ^ 0x57a5);
+                    if !hash.is_multiple_of(STAR_DENSITY) { continue; }
+                    let char = STAR_CHARS[((hash / STAR_DENSITY + tick) % STAR_CHARS.len() as u32) as usize];
+                    mt.renderer.put(x, y, Pixel { color: COLOR_STAR, char, dim: false });
+                }
+            }
+            draw_aurora(mt, sky_h);
+        }
+
+        let (t, day) = self.light();
+        let (art, color) = if day { (sun, COLOR_SUN) } else { (moon, COLOR_MOON) };
+        let x = (t * (mt.renderer.w - art.w) as f32).round() as i32;
+        let y = ((1.0 - (t * std::f32::consts::PI).sin()) * (sky_h - art.h) as f32).round() as i32;
+        mt.renderer.put_ascii(art, x, y, color);
+    }
+}
+
+/// Draw bands of northern lights waving across the upper sky, each hanging down in rays that fade
+/// away in places
+fn draw_aurora(mt: &mut Mutes, sky_h: i32) {
+    let time = mt.flicker_time() * AURORA_WAVE;
+    let rows = (sky_h - AURORA_CHARS.len() as i32).max(1) as f32;
+    let gradient = Gradient::new(AURORA_COLORS, Direction::Lines);
+    for band in 0..AURORA_BANDS {
+        let seed = AURORA_SEED + band * AURORA_OCTAVES;
+        for x in 0..mt.renderer.w {
+            let bx = x as f32 / AURORA_BEND;
+            let height = utils::fractal_2d(seed, bx, time, AURORA_OCTAVES);
+            if utils::noise_2d(seed ^ 0x6a9, bx * 2.0, time) < AURORA_GAPS { continue; }
+            let color = color::code(gradient.at(height));
+            let top = ((1.0 - height) * rows) as i32;
+            for (dy, &char) in AURORA_CHARS.iter().enumerate() {
+                mt.renderer.put(x, top + dy as i32, Pixel { color, char, dim: false });
+            }
+        }
+    }
+}
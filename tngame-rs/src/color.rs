@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// A color as red, green and blue
+pub type Rgb = [u8; 3];
+
+/// Steps a gradient is rounded to between two stops, which bounds the number of colors it creates
+const GRADIENT_STEPS: f32 = 32.0;
+
+/// Pastel rainbow, starting and ending on the same color so it can loop
+pub const RAINBOW: &[Rgb] = &[
+    [255, 150, 150], [255, 205, 140], [250, 245, 150], [150, 240, 160], [140, 200, 255], [200, 160, 255], [255, 150, 150],
+];
+
+/// Escape codes of the colors made so far
+static CODES: Mutex<BTreeMap<Rgb, &'static str>> = Mutex::new(BTreeMap::new());
+
+/// Escape code setting the text color, made once per color so pixels can keep referring to it
+pub fn code([r, g, b]: Rgb) -> &'static str {
+    CODES.lock().unwrap().entry([r, g, b])
+        .or_insert_with(|| Box::leak(format!("\x1b[38;2;{};{};{}m", r, g, b).into_boxed_str()))
+}
+
+/// Color of a true color escape code like "\x1b[38;2;r;g;bm", if it is one
+pub fn parse(code: &str) -> Option<Rgb> {
+    let c: Vec<u8> = code.strip_prefix("\x1b[38;2;")?.strip_suffix('m')?.split(';').filter_map(|c| c.parse().ok()).collect();
+    match c.as_slice() {
+        &[r, g, b] => Some([r, g, b]),
+        _ => None,
+    }
+}
+
+/// Mix two colors, t going from 0 for the first to 1 for the second
+pub fn lerp(a: Rgb, b: Rgb, t: f32) -> Rgb {
+    [0, 1, 2].map(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8)
+}
+
+/// Add a share of a light's color to a color, from 0 for none to 1 for all of it, rounded to a few
+/// steps so that lights fading out don't create a color for every cell
+pub fn brighten(c: Rgb, light: Rgb, t: f32) -> Rgb {
+    let t = (t.clamp(0.0, 1.0) * GRADIENT_STEPS).round() / GRADIENT_STEPS;
+    [0, 1, 2].map(|i| (c[i] as f32 + light[i] as f32 * t).min(255.0).round() as u8)
+}
+
+/// Which way a gradient runs across a text
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    /// From the first column to the last
+    Columns,
+    /// From the first line to the last
+    Lines,
+}
+
+/// Colors spread evenly across a string or an ascii art, optionally shifted along it and wrapping
+/// around, which animates gradients like the rainbow that loop
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient<'a> {
+    pub stops: &'a [Rgb],
+    pub direction: Direction,
+    /// Offset along the gradient, where 1 is its whole length
+    pub shift: f32,
+}
+
+impl<'a> Gradient<'a> {
+    pub fn new(stops: &'a [Rgb], direction: Direction) -> Self {
+        Self { stops, direction, shift: 0.0 }
+    }
+
+    /// Color at t from 0 at the first stop to 1 at the last
+    pub fn at(&self, t: f32) -> Rgb {
+        let segments = self.stops.len().saturating_sub(1);
+        if segments == 0 { return self.stops.first().copied().unwrap_or([255; 3]); }
+        let pos = (t.clamp(0.0, 1.0) * segments as f32 * GRADIENT_STEPS).round() / GRADIENT_STEPS;
+        let i = (pos as usize).min(segments - 1);
+        lerp(self.stops[i], self.stops[i + 1], pos - i as f32)
+    }
+
+    /// Escape code of the color at column x and line y of a text w columns wide and h lines tall
+    pub fn color(&self, x: i32, y: i32, w: i32, h: i32) -> &'static str {
+        let (pos, len) = match self.direction {
+            Direction::Columns => (x, w),
+            Direction::Lines => (y, h),
+        };
+        let t = pos as f32 / (len - 1).max(1) as f32;
+        let t = if self.shift == 0.0 { t } else { (t + self.shift).rem_euclid(1.0) };
+        code(self.at(t))
+    }
+}
@@ -0,0 +1,83 @@
+use crate::weather::{Precipitation, Weather};
+use crate::{blizzard, npc};
+
+/// A command typed after ":" by power users, when cheats are on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    /// Move the cat to a world x, or the free tile closest to it inside the world
+    Teleport(i32),
+    /// Change the weather, with a snow density multiplier
+    Weather(Weather, f32),
+    /// Set the time of day in hours
+    Time(f32),
+    /// Seed the random number generator for what happens from now on, like the snow and the weather.
+    /// The terrain is generated from the world code given with --world-code, and stays as it is.
+    Seed(u64),
+    /// Launch a number of fireworks rockets
+    Fireworks(u32),
+    /// Call up a blizzard, which hits after its warning
+    Blizzard,
+    /// Walk the cat somewhere
+    Go(Destination),
+}
+
+/// Where the cat can be told to walk
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Destination {
+    /// A world x
+    X(i32),
+    /// The NPC with an index in npc::NPCS
+    Npc(usize),
+}
+
+/// Rockets launched by "fireworks" without a count
+const DEFAULT_FIREWORKS: u32 = 5;
+
+pub const USAGE: &str = "tp <x>, weather <snow|rain|clear|blizzard>,\ntime <hour|dawn|day|dusk|night>, seed <n> (keeps terrain),\nfireworks [n], go <x|fox|penguin>, blizzard";
+
+/// Parse a command line like "tp 500" or "time night"
+pub fn parse(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().ok_or_else(|| USAGE.to_string())?;
+    let arg = words.next().unwrap_or("");
+    match name {
+        "tp" | "teleport" => arg.parse().map(Command::Teleport).map_err(|_| "Usage: tp <x>".to_string()),
+        "weather" => {
+            let (kind, intensity, density) = match arg {
+                "snow" => (Precipitation::Snow, 1.0, 1.0),
+                "rain" => (Precipitation::Rain, 1.0, 1.0),
+                "clear" => (Precipitation::Clear, 0.0, 1.0),
+                "blizzard" => (Precipitation::Snow, 1.0, blizzard::DENSITY),
+                _ => return Err("Usage: weather <snow|rain|clear|blizzard>".to_string()),
+            };
+            Ok(Command::Weather(Weather { kind, intensity }, density))
+        }
+        "time" => parse_time(arg).map(Command::Time).ok_or_else(|| "Usage: time <hour|dawn|day|dusk|night>".to_string()),
+        "seed" => arg.parse().map(Command::Seed).map_err(|_| "Usage: seed <n>, which keeps the terrain".to_string()),
+        "go" => {
+            let npc = npc::NPCS.iter().position(|n| n.name.eq_ignore_ascii_case(arg));
+            npc.map(Destination::Npc).or_else(|| arg.parse().ok().map(Destination::X))
+                .map(Command::Go).ok_or_else(|| "Usage: go <x|fox|penguin>".to_string())
+        }
+        "fireworks" if arg.is_empty() => Ok(Command::Fireworks(DEFAULT_FIREWORKS)),
+        "fireworks" => arg.parse().map(Command::Fireworks).map_err(|_| "Usage: fireworks [n]".to_string()),
+        "blizzard" => Ok(Command::Blizzard),
+        _ => Err(format!("Unknown command: {}\n{}", name, USAGE)),
+    }
+}
+
+/// Parse a time of day like "night", "7.5" or "18:30" into hours
+fn parse_time(arg: &str) -> Option<f32> {
+    let hours = match arg {
+        "dawn" => 6.0,
+        "day" | "noon" => 12.0,
+        "dusk" => 20.0,
+        "night" => 23.0,
+        "midnight" => 0.0,
+        _ => match arg.split_once(':') {
+            Some((h, m)) => h.parse::<f32>().ok()? + m.parse::<f32>().ok()? / 60.0,
+            None => arg.parse().ok()?,
+        },
+    };
+    (0.0..24.0).contains(&hours).then_some(hours)
+}
@@ -0,0 +1,46 @@
+use std::collections::BTreeSet;
+
+/// Arrows pointing left and right
+const LEFT: &str = "<-";
+const RIGHT: &str = "->";
+
+/// A place worth walking to, spanning world x from x to x + w
+pub struct Landmark {
+    pub name: &'static str,
+    pub x: i32,
+    pub w: i32,
+}
+
+/// Points the way to the nearest landmark the cat hasn't been to yet
+#[derive(Default)]
+pub struct Compass {
+    discovered: BTreeSet<&'static str>,
+}
+
+impl Compass {
+    /// Remember the landmarks the cat is standing at, spanning world x from x to x + w, returning the
+    /// names of the ones found just now
+    pub fn discover(&mut self, x: i32, w: i32, landmarks: &[Landmark]) -> Vec<&'static str> {
+        landmarks.iter().filter(|l| x + w > l.x && x < l.x + l.w)
+            .filter(|l| self.discovered.insert(l.name))
+            .map(|l| l.name)
+            .collect()
+    }
+
+    /// Nearest undiscovered landmark from world x, and the steps to its nearest edge, which are
+    /// negative to the left
+    pub fn nearest<'a>(&self, x: i32, landmarks: &'a [Landmark]) -> Option<(&'a Landmark, i32)> {
+        landmarks.iter().filter(|l| !self.discovered.contains(l.name))
+            .map(|l| (l, if l.x > x { l.x - x } else { (l.x + l.w - x).min(0) }))
+            .min_by_key(|(_, d)| d.abs())
+    }
+
+    /// HUD text pointing to the nearest undiscovered landmark, empty once they were all found
+    pub fn hud(&self, x: i32, landmarks: &[Landmark]) -> String {
+        match self.nearest(x, landmarks) {
+            Some((l, d)) if d < 0 => format!("{} {} {}", LEFT, l.name, -d),
+            Some((l, d)) => format!("{} {} {}", l.name, d, RIGHT),
+            None => String::new(),
+        }
+    }
+}
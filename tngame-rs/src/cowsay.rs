@@ -1,25 +1,204 @@
-use crate::AsciiArt;
+use std::env;
+
+use crate::{term, AsciiArt};
+
+/// Columns a tab is expanded to
+const TAB_WIDTH: usize = 4;
+
+/// Border style of speech bubbles
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BubbleStyle {
+    /// .=====.
+    /// | ... |
+    /// '====='
+    Classic,
+    /// ╭─────╮
+    /// │ ... │
+    /// ╰─────╯
+    Rounded,
+    /// ╔═════╗
+    /// ║ ... ║
+    /// ╚═════╝
+    Double,
+}
+
+impl BubbleStyle {
+    /// The style configured with TN_BUBBLE_STYLE ("classic", "rounded" or "double"), falling back to
+    /// classic when the terminal can't show Unicode borders
+    pub fn from_env() -> Self {
+        let style = match env::var("TN_BUBBLE_STYLE").unwrap_or_default().to_lowercase().as_str() {
+            "rounded" => Self::Rounded,
+            "double" => Self::Double,
+            _ => Self::Classic,
+        };
+        if term::supports_utf8() { style } else { Self::Classic }
+    }
+
+    /// Top left, top right, bottom left and bottom right corners, then the horizontal and vertical borders
+    pub fn chars(self) -> [char; 6] {
+        match self {
+            Self::Classic => ['.', '.', '\'', '\'', '=', '|'],
+            Self::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            Self::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+        }
+    }
+}
+
+/// How a line is said, which changes the shape or the color of its bubble
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BubbleKind {
+    /// A bubble with the border of the bubble style
+    Say,
+    /// A rounded cloud trailing off with o O bubbles
+    Think,
+    /// A jagged border
+    Shout,
+    /// A bubble with the border of the bubble style drawn in a dim color
+    Whisper,
+}
+
+/// Color whispers are drawn in
+const COLOR_WHISPER: &str = "\x1b[38;2;140;140;150m";
+
+impl BubbleKind {
+    /// Color to draw a bubble of this kind in, given the color of the speaker
+    pub fn color(self, speaker: &'static str) -> &'static str {
+        if self == Self::Whisper { COLOR_WHISPER } else { speaker }
+    }
+}
+
+/// Split text into the lines shown in a bubble, expanding tabs and dropping surrounding whitespace and
+/// blank lines at the end. Empty text gives a single empty line, so the bubble is never degenerate.
+fn bubble_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = text.lines()
+        .map(|line| line.replace('\t', &" ".repeat(TAB_WIDTH)).trim().to_string())
+        .collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Word wrap text to lines of at most width characters, breaking up words that are too long
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if !line.is_empty() { line.push(' '); }
+        line.extend(word);
+    }
+    lines.push(line);
+    lines.join("\n")
+}
+
+/// A border of alternating characters, used for the jagged edges of shouts
+fn zigzag(a: char, b: char, len: usize) -> String {
+    (0..len).map(|i| if i % 2 == 0 { a } else { b }).collect()
+}
+
+pub fn gen_bubble(text: &str, style: BubbleStyle, kind: BubbleKind) -> String {
+    let lines = bubble_lines(text);
+    let max_width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+    // Borders at the top and the bottom, and the characters on the left and right of every line
+    let [tl, tr, bl, br, hor, ver] = style.chars();
+    let (top, bottom, left, right) = match kind {
+        BubbleKind::Say | BubbleKind::Whisper => {
+            let border = hor.to_string().repeat(max_width + 2);
+            (format!("{tl}{border}{tr}"), format!("{bl}{border}{br}"), ver, ver)
+        }
+        BubbleKind::Think => {
+            (format!(" {} ", "_".repeat(max_width + 2)), format!(" {} ", "-".repeat(max_width + 2)), '(', ')')
+        }
+        BubbleKind::Shout => (zigzag('/', '\\', max_width + 4), zigzag('\\', '/', max_width + 4), '<', '>'),
+    };
 
-pub fn gen_bubble(text: &str) -> String {
     let mut o = String::with_capacity(text.len() + 100);
-    let mut lines = text.lines().map(|line| line.trim());
-    let max_width = lines.clone().map(|line| line.len()).max().unwrap();
-
-    o.push_str(".");
-    o.push_str("=".repeat(max_width + 2).as_str());
-    o.push_str(".\n");
-    for line in lines {
-        o.push_str("| ");
+    o.push_str(&top);
+    o.push('\n');
+    for line in &lines {
+        o.push(left);
+        o.push(' ');
         o.push_str(line);
-        o.push_str(" ".repeat(max_width - line.len()).as_str());
-        o.push_str(" |\n");
+        o.push_str(" ".repeat(max_width - line.chars().count()).as_str());
+        o.push(' ');
+        o.push(right);
+        o.push('\n');
+    }
+    o.push_str(&bottom);
+    o.push('\n');
+
+    // Thoughts trail off towards the thinker below
+    if kind == BubbleKind::Think {
+        o.push_str("  O\n o\n");
     }
-    o.push_str("'");
-    o.push_str("=".repeat(max_width + 2).as_str());
-    o.push_str("'\n");
     o
 }
 
-pub fn gen_bubble_ascii(text: &str) -> AsciiArt {
-    AsciiArt::new(&gen_bubble(text), "cowsay")
-}
\ No newline at end of file
+pub fn gen_bubble_ascii(text: &str, style: BubbleStyle, kind: BubbleKind) -> AsciiArt {
+    AsciiArt::new(&gen_bubble(text, style, kind), "cowsay")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(wrap("the quick brown fox jumps", 10), "the quick\nbrown fox\njumps");
+        assert_eq!(wrap("  spaced   out  ", 20), "spaced out");
+        assert_eq!(wrap("", 10), "");
+    }
+
+    #[test]
+    fn breaks_up_long_words_by_characters() {
+        assert_eq!(wrap("abcdefghij", 4), "abcd\nefgh\nij");
+        assert_eq!(wrap("hi ❄❄❄❄❄", 3), "hi\n❄❄❄\n❄❄");
+    }
+
+    #[test]
+    fn fits_the_longest_line() {
+        assert_eq!(gen_bubble("hi\nthere", BubbleStyle::Classic, BubbleKind::Say), ".=======.\n| hi    |\n| there |\n'======='\n");
+        let wide = gen_bubble("❄ snow", BubbleStyle::Classic, BubbleKind::Say);
+        assert!(wide.lines().all(|l| l.chars().count() == 10), "{}", wide);
+    }
+
+    #[test]
+    fn draws_the_border_of_the_style() {
+        assert_eq!(gen_bubble("hi", BubbleStyle::Rounded, BubbleKind::Say), "╭────╮\n│ hi │\n╰────╯\n");
+        assert_eq!(gen_bubble("hi", BubbleStyle::Double, BubbleKind::Whisper), "╔════╗\n║ hi ║\n╚════╝\n");
+    }
+
+    #[test]
+    fn thinks_and_shouts_in_their_own_shapes() {
+        assert_eq!(gen_bubble("hm", BubbleStyle::Double, BubbleKind::Think), " ____ \n( hm )\n ---- \n  O\n o\n");
+        assert_eq!(gen_bubble("hey", BubbleStyle::Classic, BubbleKind::Shout), "/\\/\\/\\/\n< hey >\n\\/\\/\\/\\\n");
+    }
+
+    #[test]
+    fn whispers_in_a_dim_color() {
+        assert_eq!(BubbleKind::Whisper.color("\x1b[31m"), COLOR_WHISPER);
+        assert_eq!(BubbleKind::Shout.color("\x1b[31m"), "\x1b[31m");
+    }
+
+    #[test]
+    fn cleans_up_the_lines() {
+        assert_eq!(gen_bubble("\ta\t\n\n", BubbleStyle::Classic, BubbleKind::Say), ".===.\n| a |\n'==='\n");
+        assert_eq!(gen_bubble("  a\n\n b  ", BubbleStyle::Classic, BubbleKind::Say), ".===.\n| a |\n|   |\n| b |\n'==='\n");
+        assert_eq!(gen_bubble("", BubbleStyle::Classic, BubbleKind::Say), ".==.\n|  |\n'=='\n");
+        assert_eq!(gen_bubble("\n \n", BubbleStyle::Classic, BubbleKind::Say), ".==.\n|  |\n'=='\n");
+    }
+}
@@ -0,0 +1,362 @@
+use crate::command::{self, Command};
+use crate::weather::Weather;
+use crate::{npc, Consts, Mutes};
+
+/// Cells a second the NPCs walk at in cutscenes
+const NPC_SPEED: f32 = 6.0;
+
+/// Seconds a line stays up before the next directive, and the seconds added for each character in it
+const SAY_TIME: f32 = 1.5;
+const SAY_TIME_PER_CHAR: f32 = 0.05;
+
+/// A short scene played out by a script, where the player watches instead of playing
+pub struct Script {
+    /// Name the scene is remembered by in the save once it was watched
+    pub name: &'static str,
+    pub text: &'static str,
+}
+
+/// The intro played when the game starts for the first time
+pub const INTRO: Script = Script {
+    name: "intro",
+    text: r#"
+        # The cat wakes up in the snow and has a look around
+        fade out 0
+        weather snow
+        fade in 3
+        say cat Mmm... I dozed off\nin the snow again.
+        wait 0.5
+        move cat 8
+        say cat What a snowy day!\nLet's look around.
+    "#,
+};
+
+/// The end credits rolled when the cat reaches the end of the world
+pub const END: Script = Script {
+    name: "end",
+    text: r#"
+        say cat This is where\nthe world ends...
+        fade out 2
+        roll 20
+        fade in 2
+    "#,
+};
+
+/// The end credits rolled when quitting from the menu, before the game exits
+pub const GOODBYE: Script = Script {
+    name: "goodbye",
+    text: r#"
+        fade out 1.5
+        roll 20
+        quit
+    "#,
+};
+
+/// Sleeping through the night, played when the cat falls asleep after dusk
+pub const SLEEP: Script = Script {
+    name: "sleep",
+    text: r#"
+        fade out 2
+        wake 7
+        wait 1
+        fade in 2
+        say cat What a good sleep!\nGood morning!
+    "#,
+};
+
+/// Story moments played when the cat finds a landmark for the first time, by the landmark's name
+pub const STORIES: &[(&str, Script)] = &[
+    ("House", Script {
+        name: "house",
+        text: r#"
+            say cat Home sweet home!
+            wait 0.5
+            say cat Someone lit the\nfireplace for me.
+        "#,
+    }),
+    ("Fox", Script {
+        name: "fox",
+        text: r#"
+            move fox -4
+            say fox Oh! A visitor!
+            move fox 4
+            say cat Press [e] to say hi.
+        "#,
+    }),
+    ("Penguin", Script {
+        name: "penguin",
+        text: r#"
+            say penguin ...
+            wait 1
+            say penguin Is it snowing\nagain? Wonderful.
+        "#,
+    }),
+    ("Sled", Script {
+        name: "sled",
+        text: r#"
+            weather clear
+            say cat The snow stopped.\nPerfect for sledding!
+            wait 0.5
+            weather snow
+        "#,
+    }),
+];
+
+/// Who a directive is about
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Actor {
+    Cat,
+    /// The NPC with an index in npc::NPCS
+    Npc(usize),
+}
+
+/// A step of a cutscene, which runs once the one before it is done
+#[derive(Clone, PartialEq, Debug)]
+pub enum Directive {
+    /// Walk someone by a number of cells, to the right when positive
+    Move(Actor, i32),
+    /// Show a speech bubble above someone
+    Say(Actor, String),
+    /// Do nothing for some seconds
+    Wait(f32),
+    /// Change the weather, with a snow density multiplier
+    Weather(Weather, f32),
+    /// Wake the cat up at a time of day in hours, after sleeping until then
+    Wake(f32),
+    /// Fade the screen in or out over some seconds
+    Fade { out: bool, secs: f32 },
+    /// Scroll the end credits up the screen over some seconds
+    Roll(f32),
+    /// Exit the game
+    Quit,
+}
+
+/// Parse a script, one directive a line, leaving out empty lines and comments starting with #
+pub fn parse(text: &str) -> Result<Vec<Directive>, String> {
+    text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).map(parse_line).collect()
+}
+
+/// Parse a directive like "move fox -4", "say cat Hi!\nHello", "wait 1.5", "weather snow", "wake 7",
+/// "fade in 2", "roll 20" or "quit"
+fn parse_line(line: &str) -> Result<Directive, String> {
+    let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let (first, second) = rest.split_once(' ').unwrap_or((rest, ""));
+    match name {
+        "move" => Ok(Directive::Move(parse_actor(first)?, second.parse().map_err(|_| format!("Bad cells: {}", line))?)),
+        "say" => Ok(Directive::Say(parse_actor(first)?, second.replace("\\n", "\n"))),
+        "wait" => rest.parse().map(Directive::Wait).map_err(|_| format!("Bad seconds: {}", line)),
+        "roll" => rest.parse().map(Directive::Roll).map_err(|_| format!("Bad seconds: {}", line)),
+        "quit" => Ok(Directive::Quit),
+        "weather" => match command::parse(line)? {
+            Command::Weather(weather, density) => Ok(Directive::Weather(weather, density)),
+            _ => unreachable!(),
+        },
+        "wake" => match command::parse(&format!("time {}", rest))? {
+            Command::Time(hours) => Ok(Directive::Wake(hours)),
+            _ => unreachable!(),
+        },
+        "fade" => {
+            let out = match first {
+                "in" => false,
+                "out" => true,
+                _ => return Err(format!("Fade in or out: {}", line)),
+            };
+            let secs = second.parse().map_err(|_| format!("Bad seconds: {}", line))?;
+            Ok(Directive::Fade { out, secs })
+        }
+        _ => Err(format!("Unknown directive: {}", line)),
+    }
+}
+
+fn parse_actor(name: &str) -> Result<Actor, String> {
+    if name == "cat" { return Ok(Actor::Cat); }
+    npc::NPCS.iter().position(|n| n.name.eq_ignore_ascii_case(name)).map(Actor::Npc)
+        .ok_or_else(|| format!("Unknown actor: {}", name))
+}
+
+/// What the running directive waits for before the next one starts
+enum Wait {
+    Nothing,
+    /// A game time
+    Until(f32),
+    /// The cat arriving where it walks to
+    Walk,
+    /// An NPC walking from an offset to another between two game times
+    Glide { npc: usize, from: f32, to: f32, start: f32, end: f32 },
+}
+
+/// A script being played out
+pub struct Cutscene {
+    directives: Vec<Directive>,
+    next: usize,
+    wait: Wait,
+    /// Line someone is saying
+    pub bubble: Option<(Actor, String)>,
+    /// Share of the screen faded out from one value to another between two game times
+    fade: (f32, f32, f32, f32),
+    /// Game times the end credits start and stop rolling between
+    roll: Option<(f32, f32)>,
+}
+
+impl Cutscene {
+    pub fn new(directives: Vec<Directive>) -> Self {
+        Self { directives, next: 0, wait: Wait::Nothing, bubble: None, fade: (0.0, 0.0, 0.0, 0.0), roll: None }
+    }
+
+    /// Share of the screen faded out at a game time, from 0 to 1
+    pub fn faded(&self, time: f32) -> f32 {
+        let (from, to, start, end) = self.fade;
+        if time >= end { return to; }
+        from + (to - from) * ((time - start) / (end - start)).clamp(0.0, 1.0)
+    }
+
+    /// How far the end credits rolled at a game time, from 0 to 1, while they are rolling
+    pub fn rolled(&self, time: f32) -> Option<f32> {
+        let (start, end) = self.roll?;
+        (time < end).then(|| ((time - start) / (end - start)).clamp(0.0, 1.0))
+    }
+
+    /// Run the directives that are due, returning whether the cutscene is over
+    pub fn update(&mut self, mt: &mut Mutes, cn: &Consts) -> bool {
+        loop {
+            if !self.waited(mt) { return false; }
+            let Some(d) = self.directives.get(self.next).cloned() else { return true; };
+            self.next += 1;
+            self.start(d, mt, cn);
+        }
+    }
+
+    /// Whether the running directive is done, moving the NPC it walks along
+    fn waited(&mut self, mt: &mut Mutes) -> bool {
+        match self.wait {
+            Wait::Nothing => true,
+            Wait::Until(time) => mt.time >= time,
+            Wait::Walk => mt.world.move_to.is_none(),
+            Wait::Glide { npc, from, to, start, end } => {
+                let t = if mt.time >= end { 1.0 } else { ((mt.time - start) / (end - start)).clamp(0.0, 1.0) };
+                mt.npc_dx[npc] = from + (to - from) * t;
+                t >= 1.0
+            }
+        }
+    }
+
+    fn start(&mut self, d: Directive, mt: &mut Mutes, cn: &Consts) {
+        self.wait = Wait::Nothing;
+        match d {
+            Directive::Move(Actor::Cat, cells) => {
+                if mt.go_to(mt.world.x + cells) { self.wait = Wait::Walk; }
+            }
+            Directive::Move(Actor::Npc(npc), cells) => {
+                let from = mt.npc_dx[npc];
+                let end = mt.time + cells.abs() as f32 / NPC_SPEED;
+                self.wait = Wait::Glide { npc, from, to: from + cells as f32, start: mt.time, end };
+            }
+            Directive::Say(actor, text) => {
+                self.wait = Wait::Until(mt.time + SAY_TIME + text.chars().count() as f32 * SAY_TIME_PER_CHAR);
+                self.bubble = Some((actor, text));
+            }
+            Directive::Wait(secs) => {
+                self.bubble = None;
+                self.wait = Wait::Until(mt.time + secs);
+            }
+            Directive::Weather(weather, density) => mt.set_weather(cn, weather, density),
+            Directive::Wake(hours) => mt.wake(hours),
+            Directive::Fade { out, secs } => {
+                let to = if out { 1.0 } else { 0.0 };
+                self.fade = (self.faded(mt.time), to, mt.time, mt.time + secs);
+                self.wait = Wait::Until(mt.time + secs);
+            }
+            Directive::Roll(secs) => {
+                self.bubble = None;
+                self.roll = Some((mt.time, mt.time + secs));
+                self.wait = Wait::Until(mt.time + secs);
+            }
+            Directive::Quit => mt.should_exit = true,
+        }
+    }
+
+    /// Skip to the end, leaving the world as it would be after the directives left
+    pub fn skip(self, mt: &mut Mutes, cn: &Consts) {
+        match self.wait {
+            Wait::Walk => if let Some(m) = mt.world.move_to.take() { mt.world.x = m.target; },
+            Wait::Glide { npc, to, .. } => mt.npc_dx[npc] = to,
+            _ => (),
+        }
+        for d in self.directives.into_iter().skip(self.next) {
+            match d {
+                Directive::Move(Actor::Cat, cells) => mt.world.x += cells,
+                Directive::Move(Actor::Npc(npc), cells) => mt.npc_dx[npc] += cells as f32,
+                Directive::Weather(weather, density) => mt.set_weather(cn, weather, density),
+                Directive::Wake(hours) => mt.wake(hours),
+                Directive::Quit => mt.should_exit = true,
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    fn mutes() -> Mutes {
+        Mutes::detached(assets::load_with(None, &[]), (80, 24))
+    }
+
+    #[test]
+    fn the_scripts_parse() {
+        for script in [&INTRO, &END, &GOODBYE, &SLEEP].into_iter().chain(STORIES.iter().map(|(_, s)| s)) {
+            assert!(parse(script.text).is_ok_and(|d| !d.is_empty()), "{}", script.name);
+        }
+    }
+
+    #[test]
+    fn parses_directives() {
+        let text = "# A comment\n\n  move fox -4\nsay cat Hi!\\nHello\nwait 1.5\nfade in 2\nroll 20\nquit";
+        assert_eq!(parse(text), Ok(vec![
+            Directive::Move(Actor::Npc(0), -4),
+            Directive::Say(Actor::Cat, "Hi!\nHello".to_string()),
+            Directive::Wait(1.5),
+            Directive::Fade { out: false, secs: 2.0 },
+            Directive::Roll(20.0),
+            Directive::Quit,
+        ]));
+        assert!(parse("move walrus 2").is_err());
+        assert!(parse("fade sideways 2").is_err());
+        assert!(parse("wait soon").is_err());
+        assert!(parse("dance").is_err());
+    }
+
+    #[test]
+    fn directives_run_one_after_another() {
+        let cn = assets::load_with(None, &[]);
+        let mut mt = mutes();
+        let mut c = Cutscene::new(parse("say cat Hi\nmove penguin 3\nfade out 1\nquit").unwrap());
+        assert!(!c.update(&mut mt, &cn));
+        assert_eq!(c.bubble, Some((Actor::Cat, "Hi".to_string())));
+        mt.time += SAY_TIME + 2.0 * SAY_TIME_PER_CHAR;
+        assert!(!c.update(&mut mt, &cn));
+        assert_eq!(mt.npc_dx[1], 0.0);
+        mt.time += 3.0 / NPC_SPEED;
+        assert!(!c.update(&mut mt, &cn));
+        assert_eq!(mt.npc_dx[1], 3.0);
+        assert_eq!(c.faded(mt.time + 0.5), 0.5);
+        assert!(!mt.should_exit);
+        mt.time += 1.0;
+        assert!(c.update(&mut mt, &cn));
+        assert!(mt.should_exit);
+    }
+
+    #[test]
+    fn skipping_leaves_the_world_as_the_end_would() {
+        let cn = assets::load_with(None, &[]);
+        let mut mt = mutes();
+        let x = mt.world.x;
+        let mut c = Cutscene::new(parse("move fox 4\nwait 10\nmove cat -3\nmove fox 2").unwrap());
+        c.update(&mut mt, &cn);
+        c.skip(&mut mt, &cn);
+        assert_eq!(mt.npc_dx[0], 6.0);
+        assert_eq!(mt.world.x, x - 3);
+    }
+}
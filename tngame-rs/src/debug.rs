@@ -0,0 +1,35 @@
+use crate::Mutes;
+
+/// Simulation state compared before and after stepping a frame in debug mode
+pub struct Snapshot(Vec<(&'static str, String)>);
+
+impl Snapshot {
+    pub fn take(mt: &Mutes) -> Self {
+        let active = mt.active_snow();
+        let lowest = mt.world.snow.particles.iter().take(active).map(|p| p.y).fold(0.0, f32::max);
+        Self(vec![
+            ("time", format!("{:.3}", mt.time)),
+            ("hours", format!("{:.3}", mt.world.clock.hours())),
+            ("state", format!("{:?}", mt.state)),
+            ("x", mt.world.x.to_string()),
+            ("draw_x", format!("{:.3}", mt.world.draw_x)),
+            ("slide_v", format!("{:.3}", mt.world.slide_v)),
+            ("move_to", mt.world.move_to.as_ref().map_or("-".to_string(), |m| m.target.to_string())),
+            ("emotion", format!("{:?}", mt.emotions.current())),
+            ("mood", format!("{:?}", mt.affection.mood())),
+            ("snow", active.to_string()),
+            ("lowest_flake", format!("{:.3}", lowest)),
+            ("effects", mt.world.effects.particles.len().to_string()),
+            ("chunks", mt.world.tiles.loaded().to_string()),
+            ("sled", mt.world.sled.as_ref().map_or("-".to_string(), |s| s.distance().to_string())),
+        ])
+    }
+
+    /// Lines describing the values that changed since an earlier snapshot
+    pub fn diff(&self, before: &Snapshot) -> Vec<String> {
+        self.0.iter().zip(&before.0)
+            .filter(|((_, now), (_, was))| now != was)
+            .map(|((name, now), (_, was))| format!("{}: {} -> {}", name, was, now))
+            .collect()
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::input::Key;
+use crate::render::Pixel;
+use crate::{Mutes, State};
+
+/// Seconds an emote floats above a cat, and rows it rises by meanwhile
+pub const DURATION: f32 = 2.5;
+const RISE: f32 = 2.0;
+
+/// Share of the duration after which an emote fades out
+const FADE: f32 = 0.7;
+
+/// A small glyph the cat shows above its head, picked from the quick-pick
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Emote {
+    Heart,
+    Exclaim,
+    Question,
+    Music,
+    Sleep,
+}
+
+impl Emote {
+    /// Emotes in the order of the quick-pick, picked with the number keys from 1
+    pub const ALL: [Emote; 5] = [Emote::Heart, Emote::Exclaim, Emote::Question, Emote::Music, Emote::Sleep];
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Emote::Heart => "♥",
+            Emote::Exclaim => "!",
+            Emote::Question => "?",
+            Emote::Music => "♪",
+            Emote::Sleep => "zzz",
+        }
+    }
+
+    /// The emote picked with a key of the quick-pick
+    pub fn pick(key: char) -> Option<Emote> {
+        key.to_digit(10).and_then(|i| Self::ALL.get((i as usize).wrapping_sub(1))).copied()
+    }
+}
+
+/// Text of the quick-pick, shown in the cat's bubble while it is open
+pub fn picker() -> String {
+    let emotes: Vec<String> = Emote::ALL.iter().enumerate().map(|(i, e)| format!("[{}] {}", i + 1, e.glyph())).collect();
+    format!("{}\n[esc] cancel", emotes.join("  "))
+}
+
+/// Draw an emote shown seconds ago floating up above a cat at world x, w cells wide, whose top is at
+/// screen y
+pub fn draw(mt: &mut Mutes, emote: Emote, age: f32, x: i32, w: i32, y: i32, color: &'static str) {
+    if !(0.0..DURATION).contains(&age) { return; }
+    let glyph = emote.glyph();
+    let sx = mt.screen_x(x + w / 2) - glyph.chars().count() as i32 / 2;
+    let sy = y - 1 - (age / DURATION * RISE).round() as i32;
+    let dim = age > DURATION * FADE;
+    for (i, char) in glyph.chars().enumerate() {
+        mt.renderer.put(sx + i as i32, sy, Pixel { color, char, dim });
+    }
+}
+
+/// Handle a key while the quick-pick is open, where the number keys show an emote above the cat, which
+/// the other visitors see too
+pub fn handle_key(mt: &mut Mutes, key: Key) {
+    match key {
+        Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+        Key::Char('v') | Key::Esc => mt.state = State::Exploring,
+        Key::Char(c) => {
+            if let Some(e) = Emote::pick(c) {
+                mt.ui.emote = Some((e, mt.now_ms()));
+                mt.state = State::Exploring;
+            }
+        },
+        _ => (),
+    }
+}
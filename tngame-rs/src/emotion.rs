@@ -0,0 +1,69 @@
+use crate::affection::Mood;
+
+/// Emotion constants
+const MIN_DWELL: f32 = 1.0; // Seconds an emotion is kept before switching to another one
+const SURPRISE_DURATION: f32 = 1.5;
+const SLEEPY_IDLE: f32 = 30.0; // Seconds without input before the cat gets sleepy
+
+/// What the cat's face shows
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Emotion {
+    Neutral,
+    Happy,
+    Lonely,
+    Surprised,
+    Sleepy,
+    Cold,
+}
+
+/// Game state that the cat's emotions react to
+pub struct Context {
+    pub mood: Mood,
+    pub night: bool,
+    pub near_warmth: bool,
+    pub idle: f32,
+}
+
+/// Small state machine picking the cat's emotion, keeping each emotion for a while so the face doesn't flicker
+pub struct Emotions {
+    current: Emotion,
+    since: f32,
+    surprised_until: f32,
+}
+
+impl Emotions {
+    pub fn new() -> Self {
+        Self { current: Emotion::Neutral, since: 0.0, surprised_until: f32::NEG_INFINITY }
+    }
+
+    pub fn current(&self) -> Emotion {
+        self.current
+    }
+
+    /// Surprise the cat right away, interrupting any other emotion
+    pub fn surprise(&mut self, time: f32) {
+        self.current = Emotion::Surprised;
+        self.since = time;
+        self.surprised_until = time + SURPRISE_DURATION;
+    }
+
+    /// Switch to the emotion fitting the context once the current one was shown long enough
+    pub fn update(&mut self, time: f32, ctx: &Context) {
+        if time < self.surprised_until { return; }
+
+        let target = if ctx.night && !ctx.near_warmth { Emotion::Cold }
+            else if ctx.night || ctx.idle >= SLEEPY_IDLE { Emotion::Sleepy }
+            else {
+                match ctx.mood {
+                    Mood::Lonely => Emotion::Lonely,
+                    Mood::Content => Emotion::Neutral,
+                    Mood::Happy => Emotion::Happy,
+                }
+            };
+
+        if target != self.current && (time - self.since >= MIN_DWELL || self.current == Emotion::Surprised) {
+            self.current = target;
+            self.since = time;
+        }
+    }
+}
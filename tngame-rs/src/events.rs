@@ -0,0 +1,176 @@
+use std::env;
+
+use chrono::{Datelike, Local, NaiveDate};
+use rand::Rng;
+
+use crate::particles::{Emitter, Particle, Velocity};
+use crate::render::Pixel;
+use crate::{Mutes, utils};
+
+/// Where a decoration is placed in the world
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Next to the trunk of the first tree
+    Tree,
+    /// In front of the house
+    House,
+}
+
+/// An ascii art placed on the ground next to an anchor
+pub struct Decoration {
+    pub anchor: Anchor,
+    pub art: &'static str,
+    pub dx: i32,
+    pub color: &'static str,
+}
+
+/// Particles that replace a share of the snow flakes
+pub struct Flakes {
+    pub char: char,
+    pub colors: &'static [&'static str],
+    pub share: f32,
+}
+
+/// A seasonal event that is active between two dates (inclusive) every year
+pub struct Event {
+    pub name: &'static str,
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+    pub greeting: Option<&'static str>,
+    pub decorations: &'static [Decoration],
+    pub tree_lights: &'static [&'static str],
+    pub flakes: Option<Flakes>,
+    pub fireworks: bool,
+}
+
+impl Event {
+    /// Whether the event is active on the given month and day, also for events wrapping around new year
+    pub fn is_active(&self, month: u32, day: u32) -> bool {
+        let date = (month, day);
+        if self.start <= self.end { self.start <= date && date <= self.end }
+        else { self.start <= date || date <= self.end }
+    }
+}
+
+const WHITE: &str = "\x1b[38;2;255;255;255m";
+const RED: &str = "\x1b[38;2;255;90;90m";
+const GREEN: &str = "\x1b[38;2;120;230;120m";
+const GOLD: &str = "\x1b[38;2;255;215;90m";
+const BLUE: &str = "\x1b[38;2;110;170;255m";
+const PINK: &str = "\x1b[38;2;255;140;200m";
+
+/// All seasonal events
+pub const EVENTS: &[Event] = &[
+    Event {
+        name: "December",
+        start: (12, 1),
+        end: (12, 30),
+        greeting: Some("Happy holidays from\nmy snowy world!"),
+        decorations: &[
+            Decoration { anchor: Anchor::Tree, art: " _||_\n|_||_|", dx: -7, color: RED },
+            Decoration { anchor: Anchor::Tree, art: " _\\/_\n|_/\\_|", dx: 6, color: GREEN },
+            Decoration { anchor: Anchor::House, art: ".-\"-.\n|_|_|", dx: -6, color: GOLD },
+        ],
+        tree_lights: &[RED, GOLD, BLUE, GREEN],
+        flakes: None,
+        fireworks: false,
+    },
+    Event {
+        name: "New Year's Eve",
+        start: (12, 31),
+        end: (1, 1),
+        greeting: Some("Happy new year!"),
+        decorations: &[],
+        tree_lights: &[GOLD],
+        flakes: None,
+        fireworks: true,
+    },
+    Event {
+        name: "Valentine's",
+        start: (2, 10),
+        end: (2, 18),
+        greeting: Some("Welcome to my\nlovely world!"),
+        decorations: &[],
+        tree_lights: &[],
+        flakes: Some(Flakes { char: '♥', colors: &[PINK, RED], share: 0.3 }),
+        fireworks: false,
+    },
+];
+
+/// Today's date, which can be overridden for testing with TN_DATE in the format "yyyy-mm-dd"
+pub fn today() -> NaiveDate {
+    env::var("TN_DATE").ok()
+        .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+/// Events active on a date
+pub fn active(date: NaiveDate) -> Vec<&'static Event> {
+    EVENTS.iter().filter(|e| e.is_active(date.month(), date.day())).collect()
+}
+
+/// Fireworks constants
+const FIREWORK_INTERVAL: f32 = 1.2; // Average seconds between two fireworks
+const ROCKET_FLIGHT: f32 = 1.0; // Seconds a rocket flies up before it bursts
+const ROCKET_GLYPHS: &[char] = &['|'];
+const ROCKET_COLORS: &[&str] = &[GOLD];
+const FIREWORK_SPARKS: usize = 16; // Sparks per firework
+const FIREWORK_SPEED: f32 = 10.0; // Initial spark speed in pixels per second
+const FIREWORK_GRAVITY: f32 = 6.0; // Downwards acceleration of sparks in pixels per second squared
+const FIREWORK_LIFE: f32 = 1.5; // Seconds until a spark fades out
+const FIREWORK_GLYPHS: &[char] = &['*', '*', '.']; // Sparks shrink as they fade
+/// Sparks flash white, then glow in the color of the firework and fade into the night
+const FIREWORK_PALETTES: &[&[&str]] = &[
+    &[WHITE, RED, "\x1b[38;2;120;40;40m"],
+    &[WHITE, GOLD, "\x1b[38;2;120;100;40m"],
+    &[WHITE, BLUE, "\x1b[38;2;50;80;120m"],
+    &[WHITE, PINK, "\x1b[38;2;120;65;95m"],
+];
+
+/// Launch new fireworks at random while an event celebrates with them
+pub fn launch_fireworks(mt: &mut Mutes, dt: f32) {
+    if utils::rng().gen_bool((dt / FIREWORK_INTERVAL).clamp(0.0, 1.0) as f64) {
+        launch_rocket(mt);
+    }
+}
+
+/// Launch a rocket from the bottom of the screen, flying up to burst somewhere in the upper half of the sky
+pub fn launch_rocket(mt: &mut Mutes) {
+    let (x, y) = {
+        let mut rng = utils::rng();
+        (rng.gen_range(0.0..mt.renderer.w as f32), rng.gen_range(1.0..(mt.renderer.h as f32 / 2.0).max(2.0)))
+    };
+    let start = mt.renderer.h as f32;
+    let mut rocket = Particle::endless(x, start, 0.0, (y - start) / ROCKET_FLIGHT, ROCKET_COLORS, ROCKET_GLYPHS);
+    rocket.life = ROCKET_FLIGHT;
+    mt.world.rockets.particles.push(rocket);
+}
+
+/// Fly the rockets, bursting the ones that reached their height into sparks, and draw them
+pub fn update_fireworks(mt: &mut Mutes, dt: f32) {
+    for rocket in mt.world.rockets.update(dt) {
+        let velocity = Velocity::Radial { speed: FIREWORK_SPEED };
+        let palette = utils::rng().gen_range(0..FIREWORK_PALETTES.len());
+        let mut emitter = Emitter::new(rocket.x, rocket.y, velocity, FIREWORK_LIFE, &FIREWORK_PALETTES[palette..=palette], FIREWORK_GLYPHS);
+        emitter.gravity = FIREWORK_GRAVITY;
+        mt.world.effects.burst(&emitter, FIREWORK_SPARKS);
+    }
+    let rockets = std::mem::take(&mut mt.world.rockets);
+    rockets.draw(mt, false);
+    mt.world.rockets = rockets;
+}
+
+/// Decorate a tree drawn at screen x and y with blinking lights
+pub fn draw_lights(mt: &mut Mutes, tree: &crate::AsciiArt, x: i32, y: i32, colors: &[&'static str], time: f32) {
+    if colors.is_empty() { return; }
+    let tick = (time * 2.0) as u32;
+    for (i, line) in tree.art.lines().enumerate() {
+        for (j, c) in line.chars().enumerate() {
+            // Only hang lights on the leaves, at pseudo-random spots
+            let hash = utils::hash((i * 97 + j) as u32);
+            if c != '%' || !hash.is_multiple_of(5) { continue; }
+            let color = colors[((hash / 5 + tick) % colors.len() as u32) as usize];
+            mt.renderer.put(x + j as i32, y + i as i32, Pixel { color, char: 'o', dim: false });
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::map::Map;
+use crate::shared::SharedWorld;
+use crate::terrain::Params;
+use crate::Mutes;
+
+/// Version of the world file format, raised whenever it changes in a way older games can't read
+const VERSION: u32 = 1;
+
+/// A world shared as one JSON file: the map with its entities, the world code of the terrain it is
+/// generated on, and the snowmen and the snow visitors left in it
+#[derive(Serialize, Deserialize)]
+pub struct WorldFile {
+    pub version: u32,
+    pub terrain: String,
+    pub map: Map,
+    #[serde(default)]
+    pub snowmen: Vec<i32>,
+    #[serde(default)]
+    pub snow_cover: f32,
+}
+
+impl WorldFile {
+    /// The world as it is in the game
+    pub fn take(mt: &Mutes) -> Self {
+        Self {
+            version: VERSION,
+            terrain: mt.world.tiles.terrain().code(),
+            map: mt.world.map.clone(),
+            snowmen: mt.shared.snowmen.clone(),
+            snow_cover: mt.shared.snow_cover,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("Failed to write the world {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read the world {}: {}", path.display(), e))?;
+        let file: Self = serde_json::from_str(&json).map_err(|e| anyhow!("Failed to parse the world {}: {}", path.display(), e))?;
+        if file.version > VERSION {
+            bail!("The world {} was exported by a newer version of the game", path.display());
+        }
+        Params::from_code(&file.terrain)?;
+        Ok(file)
+    }
+
+    /// Terrain of the world, which load made sure is a valid code
+    pub fn terrain(&self) -> Params {
+        Params::from_code(&self.terrain).unwrap_or_default()
+    }
+
+    /// Snowmen and snow of the world, which only last for the session rather than being saved into
+    /// the player's own world
+    pub fn shared(&self) -> SharedWorld {
+        SharedWorld::session(self.snowmen.clone(), self.snow_cover)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tngame-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn worlds_round_trip() {
+        let terrain = Params { seed: 99, hill_height: 7, ice_chance: 5 };
+        let world = WorldFile {
+            version: VERSION, terrain: terrain.code(), map: Map::default_world(), snowmen: vec![120, 340], snow_cover: 0.75,
+        };
+        let path = path("world");
+        world.write(&path).unwrap();
+        let loaded = WorldFile::load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.terrain(), terrain);
+        assert_eq!((loaded.snowmen, loaded.snow_cover), (vec![120, 340], 0.75));
+        assert_eq!(serde_json::to_value(&loaded.map).unwrap(), serde_json::to_value(&world.map).unwrap());
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_bad_terrain() {
+        let load = |name: &str, json: String| {
+            let path = path(name);
+            fs::write(&path, json).unwrap();
+            let loaded = WorldFile::load(&path);
+            fs::remove_file(&path).unwrap();
+            loaded
+        };
+        let newer = load("newer", format!(r#"{{"version": {}, "terrain": "00000000", "map": {{}}}}"#, VERSION + 1));
+        assert!(newer.is_err_and(|e| e.to_string().contains("newer version")));
+        assert!(load("terrain", r#"{"version": 1, "terrain": "nope", "map": {}}"#.to_string()).is_err());
+        assert!(load("json", "{".to_string()).is_err());
+        let old = load("old", r#"{"version": 1, "terrain": "00000000", "map": {}}"#.to_string()).unwrap();
+        assert!(old.snowmen.is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::{stdin, stdout, AsyncRead, AsyncWrite};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::Mutex;
+
+use crate::input::{Key, KeyEvent, Kind};
+#[cfg(feature = "ratatui")]
+use crate::render::Pixel;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::terminal::{pull_input, start_update_loop, term_size};
+use crate::theme::Theme;
+use crate::utils::Instant;
+#[cfg(target_arch = "wasm32")]
+use crate::DEFAULT_TERM_SIZE;
+use crate::{assets, handle_key, update_frame, utils, Mutes};
+
+/// Sets up the snowy scene for another app to show, like a splash screen or a widget in a TUI
+#[derive(Default)]
+pub struct GameBuilder {
+    size: Option<(u16, u16)>,
+    seed: Option<u64>,
+    theme: Option<Theme>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input: Option<Box<dyn AsyncRead + Unpin + Send>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    output: Option<Box<dyn AsyncWrite + Unpin + Send>>,
+    assets: Vec<(String, String)>,
+    assets_dir: Option<PathBuf>,
+    persist: bool,
+    releases: bool,
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cells to draw the scene in, instead of the whole terminal
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Seed of the random numbers, so the scene looks the same every time
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Where Game::run reads keys from, stdin by default
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn input(mut self, input: impl AsyncRead + Unpin + Send + 'static) -> Self {
+        self.input = Some(Box::new(input));
+        self
+    }
+
+    /// Where Game::run writes frames to, stdout by default
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn output(mut self, output: impl AsyncWrite + Unpin + Send + 'static) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+    /// Replace a piece of art by the name of its file in the assets folder, like "tree" or "house"
+    pub fn asset(mut self, name: &str, art: &str) -> Self {
+        self.assets.push((name.to_string(), art.to_string()));
+        self
+    }
+
+    /// Folder of art replacing the built-in art, laid out like the one set with TN_ASSETS, which an
+    /// embedded game doesn't read
+    pub fn assets_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = Some(dir.into());
+        self
+    }
+
+    /// Load and keep the player's save, guestbook and shared world like the terminal game does. Off by
+    /// default, so that the scene leaves nothing behind in the app embedding it.
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// Whether the app passes on key releases, with Game::handle_key_event or in the input of Game::run,
+    /// so that the cat walks while a key is held instead of a step for every press
+    pub fn key_releases(mut self, releases: bool) -> Self {
+        self.releases = releases;
+        self
+    }
+
+    pub fn build(self) -> Game {
+        if let Some(seed) = self.seed {
+            utils::seed_rng(seed);
+        }
+        let cn = assets::load_with(self.assets_dir.as_deref(), &self.assets);
+        // Browsers have no terminal to measure
+        #[cfg(not(target_arch = "wasm32"))]
+        let size = self.size.map_or_else(term_size, |(w, h)| (w as i32, h as i32));
+        #[cfg(target_arch = "wasm32")]
+        let size = self.size.map_or((DEFAULT_TERM_SIZE.0 as i32, DEFAULT_TERM_SIZE.1 as i32), |(w, h)| (w as i32, h as i32));
+        let mut mt = if self.persist { Mutes::new(cn, size) } else { Mutes::detached(cn, size) };
+        mt.renderer.theme = self.theme.or(mt.assets.colors.theme).unwrap_or(mt.renderer.theme);
+        Game {
+            now: mt.last_update,
+            mt,
+            releases: self.releases,
+            frame: String::new(),
+            #[cfg(feature = "ratatui")]
+            pixels: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            input: self.input,
+            #[cfg(not(target_arch = "wasm32"))]
+            output: self.output,
+        }
+    }
+}
+
+/// The snowy scene embedded in another app, either driven frame by frame with tick, render and
+/// handle_key, or left to run on its own with run
+pub struct Game {
+    mt: Mutes,
+    // Whether the app passes on key releases
+    releases: bool,
+    // Time of the last tick, moved on by the time each tick passes
+    now: Instant,
+    frame: String,
+    // Pixels of the last frame, for drawing it as a ratatui widget
+    #[cfg(feature = "ratatui")]
+    pixels: Vec<Vec<Option<Pixel>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input: Option<Box<dyn AsyncRead + Unpin + Send>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    output: Option<Box<dyn AsyncWrite + Unpin + Send>>,
+}
+
+impl Game {
+    /// Move the world on by some time and draw it. Drawing empties the buffer, so every tick draws a
+    /// frame even when only the last one is rendered.
+    pub fn tick(&mut self, dt: Duration) -> Result<()> {
+        self.now += dt;
+        let cn = self.mt.assets.clone();
+        update_frame(&mut self.mt, &cn, self.now);
+        let (dx, dy) = self.mt.camera.shake.offset(self.mt.time);
+        if (dx, dy) != (0, 0) {
+            self.mt.renderer.shift_buf(dx, dy);
+        }
+        #[cfg(feature = "ratatui")]
+        {
+            self.pixels = self.mt.renderer.pixels().to_vec();
+        }
+        self.frame = self.mt.renderer.draw_buf((0, 0))?;
+        Ok(())
+    }
+
+    /// The last drawn frame as text with escape codes, to be written at the top left of the screen
+    pub fn render(&self) -> &str {
+        &self.frame
+    }
+
+    pub fn handle_key(&mut self, key: Key) {
+        self.handle_key_event(KeyEvent::new(key, Kind::Press));
+    }
+
+    /// Handle a key being pressed, repeated or released, with shift held or not
+    pub fn handle_key_event(&mut self, event: KeyEvent) {
+        let cn = self.mt.assets.clone();
+        handle_key(&mut self.mt, &cn, event, self.releases);
+    }
+
+    /// Pixels of the last frame by row, and the theme to show their colors in
+    #[cfg(feature = "ratatui")]
+    pub(crate) fn pixels(&self) -> (&[Vec<Option<Pixel>>], Theme) {
+        (&self.pixels, self.mt.renderer.theme)
+    }
+
+    /// Whether the player quit the game
+    pub fn should_exit(&self) -> bool {
+        self.mt.should_exit
+    }
+
+    /// Run the game in real time, reading keys from the input and writing frames to the output until
+    /// the player quits
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn run(mut self) -> Result<()> {
+        let input = self.input.take().unwrap_or_else(|| Box::new(stdin()));
+        let output = self.output.take().unwrap_or_else(|| Box::new(stdout()));
+        self.mt.kitty |= self.releases;
+        let mt = Arc::new(Mutex::new(self.mt));
+        tokio::try_join!(start_update_loop(mt.clone(), None, output, false), pull_input(mt, input))?;
+        Ok(())
+    }
+}
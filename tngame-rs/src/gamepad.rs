@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use tokio::sync::Mutex;
+
+use crate::input::{Key, KeyEvent, Kind};
+use crate::{handle_key, toast, Mutes};
+
+/// How often the controller is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Stick positions past which the stick counts as pushed, and within which it counts as released again
+const STICK_PUSH: f32 = 0.5;
+const STICK_RELEASE: f32 = 0.3;
+
+/// The key a controller button stands for
+fn key(button: Button) -> Option<Key> {
+    Some(match button {
+        Button::DPadLeft => Key::Left,
+        Button::DPadRight => Key::Right,
+        Button::DPadUp => Key::Up,
+        Button::DPadDown => Key::Down,
+        // A interacts like e or enter
+        Button::South => Key::Enter,
+        // Start pauses like space
+        Button::Start => Key::Char(' '),
+        _ => return None,
+    })
+}
+
+/// Poll connected controllers and handle their buttons like keys, until the game exits. Controllers
+/// report releases, so holding the d-pad or the stick walks the cat until it is let go.
+pub fn poll(mt: Arc<Mutex<Mutes>>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(e) => {
+            log::warn!("Failed to open game controllers: {}", e);
+            toast::report("Couldn't open the game controllers");
+            return;
+        }
+    };
+
+    // Direction the left stick is pushed in
+    let mut stick = 0;
+    loop {
+        let mut events = Vec::new();
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(b, _) => events.extend(key(b).map(|key| KeyEvent::new(key, Kind::Press))),
+                EventType::ButtonReleased(b, _) => events.extend(key(b).map(|key| KeyEvent::new(key, Kind::Release))),
+                EventType::AxisChanged(Axis::LeftStickX, v, _) => {
+                    let dir = match v {
+                        v if v <= -STICK_PUSH => -1,
+                        v if v >= STICK_PUSH => 1,
+                        v if v.abs() <= STICK_RELEASE => 0,
+                        _ => stick,
+                    };
+                    if dir == stick { continue; }
+                    let arrow = |d| if d < 0 { Key::Left } else { Key::Right };
+                    if stick != 0 { events.push(KeyEvent::new(arrow(stick), Kind::Release)); }
+                    if dir != 0 { events.push(KeyEvent::new(arrow(dir), Kind::Press)); }
+                    stick = dir;
+                }
+                _ => (),
+            }
+        }
+
+        {
+            let mut mt = mt.blocking_lock();
+            let cn = mt.assets.clone();
+            for event in events {
+                handle_key(&mut mt, &cn, event, true);
+            }
+            if mt.should_exit { break; }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
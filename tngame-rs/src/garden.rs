@@ -0,0 +1,113 @@
+use crate::save::Save;
+use crate::{utils, AsciiArt, Mutes};
+
+/// Seconds in a day, after each of which a plant grows by one stage
+const DAY: u64 = 24 * 60 * 60;
+
+/// Coins the cat finds in the soil when picking a flower
+const FLOWER_COINS: u32 = 2;
+
+/// Art of an empty plot, and of a plant at each stage from the seed to the flower, with the soil on
+/// the last line
+const EMPTY: &str = "___";
+const STAGES: [&str; 4] = ["_._", " v \n_|_", " o \n\\|/\n_|_", "(@)\n\\|/\n_|_"];
+
+const COLOR_SOIL: &str = "\x1b[38;2;150;110;80m";
+const COLOR_FLOWERS: &[&str] = &["\x1b[38;2;246;170;183m", "\x1b[38;2;255;215;90m", "\x1b[38;2;170;140;220m"];
+
+/// Stage a plant planted at a time has grown to by now, both in unix seconds
+fn stage(planted: u64, now: u64) -> usize {
+    ((now.saturating_sub(planted) / DAY) as usize).min(STAGES.len() - 1)
+}
+
+/// Whether any plant in the garden grew since a time, to tell returning visitors
+pub fn grew(save: &Save, since: u64, now: u64) -> bool {
+    save.garden.iter().flatten().any(|&p| stage(p, since.max(p)) < stage(p, now))
+}
+
+/// Tend to a plot: plant a seed in it when it is empty, or pick the flower once it has grown.
+/// Returns what the cat says about it, and the coins it found.
+pub fn tend(save: &mut Save, plot: usize, now: u64) -> (String, u32) {
+    if save.garden.len() <= plot {
+        save.garden.resize(plot + 1, None);
+    }
+    match save.garden[plot] {
+        None => {
+            save.garden[plot] = Some(now);
+            ("Planted a seed!\nIt grows a bit every day.".to_string(), 0)
+        }
+        Some(p) if stage(p, now) == STAGES.len() - 1 => {
+            save.garden[plot] = None;
+            (format!("Picked a flower, and found\n{} coins in the soil!", FLOWER_COINS), FLOWER_COINS)
+        }
+        Some(_) => ("Still growing...\nI'll come back tomorrow.".to_string(), 0),
+    }
+}
+
+/// Draw the garden plots with their plants as grown by now, in unix seconds
+pub fn draw(mt: &mut Mutes, plots: &[i32], now: u64, leaf: &'static str) {
+    for (i, &x) in plots.iter().enumerate() {
+        let planted = mt.save.garden.get(i).copied().flatten();
+        let stage = planted.map(|p| stage(p, now));
+        let art = AsciiArt::new(stage.map_or(EMPTY, |s| STAGES[s]), "");
+        let flower = COLOR_FLOWERS[utils::hash(i as u32 ^ planted.unwrap_or(0) as u32) as usize % COLOR_FLOWERS.len()];
+        let bloomed = stage == Some(STAGES.len() - 1);
+
+        let (sx, y) = (mt.screen_x(x), mt.rest_y(&art, x));
+        let art = mt.camera.zoomed(&art);
+        mt.renderer.put_ascii_with(&art, sx, y, |_, row| {
+            if row == art.h - 1 { COLOR_SOIL }
+            else if row == 0 && bloomed { flower }
+            else { leaf }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn plants_grow_a_stage_a_day() {
+        assert_eq!(stage(NOW, NOW), 0);
+        assert_eq!(stage(NOW, NOW + DAY - 1), 0);
+        assert_eq!(stage(NOW, NOW + DAY), 1);
+        assert_eq!(stage(NOW, NOW + DAY * 100), STAGES.len() - 1);
+        // A clock set back doesn't make plants shrink below seeds
+        assert_eq!(stage(NOW, NOW - DAY), 0);
+    }
+
+    #[test]
+    fn plots_go_from_seed_to_flower_and_back() {
+        let mut save = Save::default();
+        assert_eq!(tend(&mut save, 2, NOW).1, 0);
+        assert_eq!(save.garden, vec![None, None, Some(NOW)]);
+        let (msg, coins) = tend(&mut save, 2, NOW + DAY);
+        assert_eq!((msg.as_str(), coins), ("Still growing...\nI'll come back tomorrow.", 0));
+        assert_eq!(save.garden[2], Some(NOW));
+        assert_eq!(tend(&mut save, 2, NOW + DAY * 3).1, FLOWER_COINS);
+        assert_eq!(save.garden[2], None);
+    }
+
+    #[test]
+    fn gardens_survive_the_save() {
+        let mut save = Save::default();
+        tend(&mut save, 0, NOW);
+        let save: Save = serde_json::from_str(&serde_json::to_string(&save).unwrap()).unwrap();
+        assert_eq!(save.garden, vec![Some(NOW)]);
+    }
+
+    #[test]
+    fn returning_visitors_hear_about_plants_that_grew() {
+        let mut save = Save::default();
+        tend(&mut save, 0, NOW);
+        assert!(!grew(&save, NOW, NOW + DAY / 2));
+        assert!(grew(&save, NOW, NOW + DAY));
+        assert!(!grew(&save, NOW + DAY * 5, NOW + DAY * 6));
+        // Plants planted after the last visit count from when they were planted
+        assert!(grew(&save, NOW - DAY * 10, NOW + DAY));
+        assert!(!grew(&Save::default(), 0, NOW));
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+
+use crate::save;
+
+/// Guestbook constants
+pub const MAX_LENGTH: usize = 60; // Maximum characters per message
+pub const SHOWN: usize = 3; // Number of recent messages shown on the signpost
+
+/// Messages left by visitors, stored one per line in a local file shared by everyone running the game,
+/// or only kept for the session without a file
+#[derive(Default)]
+pub struct Guestbook {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl Guestbook {
+    /// Location of the guestbook file, which can be overridden with TN_GUESTBOOK
+    pub fn path() -> Option<PathBuf> {
+        save::data_path("TN_GUESTBOOK", "guestbook.txt")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let entries = path.as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .map(|s| s.lines().filter(|l| !l.trim().is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// The most recent messages, oldest first
+    pub fn recent(&self) -> &[String] {
+        &self.entries[self.entries.len().saturating_sub(SHOWN)..]
+    }
+
+    /// Append a message to the guestbook, dated with today's date
+    pub fn sign(&mut self, msg: &str) -> Result<()> {
+        // Keep every message on a single line without control characters
+        let msg: String = msg.chars().filter(|c| !c.is_control()).take(MAX_LENGTH).collect();
+        let msg = msg.trim();
+        if msg.is_empty() { return Err(anyhow!("Empty message")); }
+        let entry = format!("{}: {}", chrono::Local::now().format("%Y-%m-%d"), msg);
+
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", entry)?;
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+}
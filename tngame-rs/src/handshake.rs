@@ -0,0 +1,100 @@
+use std::env;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::term::Colors;
+use crate::transport::Compression;
+
+/// Version of the handshake front-ends send in server mode, bumped whenever the way the game talks to
+/// them changes
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// What a networked front-end like relay.py tells the game about the visitor before it starts, passed
+/// as JSON in TN_HANDSHAKE
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Handshake {
+    pub version: u32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub colors: Colors,
+    #[serde(default)]
+    pub player: Option<String>,
+    /// Frame compression methods the front-end can decompress, most preferred first
+    #[serde(default)]
+    pub compression: Vec<String>,
+}
+
+impl Handshake {
+    /// Read the handshake from TN_HANDSHAKE, failing with a message meant for the visitor when the
+    /// front-end didn't send one or speaks another version
+    pub fn from_env() -> Result<Self> {
+        let json = env::var("TN_HANDSHAKE").map_err(|_| anyhow!(
+            "This server's front-end is too old for this version of tngame (protocol {} is needed). \
+            Please ask the host to update relay.py.", PROTOCOL_VERSION))?;
+        Self::parse(&json)
+    }
+
+    /// Read a handshake sent as JSON, checking that the game can talk to the front-end that sent it
+    fn parse(json: &str) -> Result<Self> {
+        let hs: Handshake = serde_json::from_str(json)
+            .map_err(|e| anyhow!("The server's front-end sent a handshake tngame doesn't understand: {}", e))?;
+        if hs.version != PROTOCOL_VERSION {
+            let newer = if hs.version > PROTOCOL_VERSION { "tngame" } else { "relay.py" };
+            bail!("The server's front-end speaks protocol {} but tngame speaks protocol {}. \
+                Please ask the host to update {}.", hs.version, PROTOCOL_VERSION, newer);
+        }
+        if hs.width <= 0 || hs.height <= 0 {
+            bail!("The server's front-end reported a terminal size of {}x{}, which is too small to play in.", hs.width, hs.height);
+        }
+        Ok(hs)
+    }
+
+    /// Pass what the handshake says on to the settings read by the rest of the game
+    pub fn apply(&self) {
+        env::set_var("TN_TERM_SIZE", format!("{}x{}", self.width, self.height));
+        env::set_var("TN_COLORS", self.colors.name());
+        env::set_var("TN_COMPRESSION", Compression::negotiate(&self.compression).name());
+        match &self.player {
+            Some(name) => env::set_var("TN_PLAYER", name),
+            None => env::remove_var("TN_PLAYER"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_handshake_with_defaults() {
+        let hs = Handshake::parse(r#"{"version": 1, "width": 80, "height": 24}"#).unwrap();
+        assert_eq!((hs.version, hs.width, hs.height), (PROTOCOL_VERSION, 80, 24));
+        assert_eq!(hs.player, None);
+        assert!(hs.compression.is_empty());
+    }
+
+    #[test]
+    fn reads_the_player_and_the_compression() {
+        let hs = Handshake::parse(r#"{"version": 1, "width": 100, "height": 30, "player": "Mika", "compression": ["brotli", "rle"]}"#).unwrap();
+        assert_eq!(hs.player.as_deref(), Some("Mika"));
+        assert_eq!(Compression::negotiate(&hs.compression), Compression::Rle);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let older = Handshake::parse(r#"{"version": 0, "width": 80, "height": 24}"#).unwrap_err();
+        assert!(older.to_string().contains("update relay.py"), "{}", older);
+        let newer = Handshake::parse(r#"{"version": 2, "width": 80, "height": 24}"#).unwrap_err();
+        assert!(newer.to_string().contains("update tngame"), "{}", newer);
+    }
+
+    #[test]
+    fn rejects_bad_sizes_and_json() {
+        assert!(Handshake::parse(r#"{"version": 1, "width": 0, "height": 24}"#).is_err());
+        assert!(Handshake::parse(r#"{"version": 1, "width": 80, "height": -1}"#).is_err());
+        assert!(Handshake::parse("not json").is_err());
+        assert!(Handshake::parse(r#"{"version": 1}"#).is_err());
+    }
+}
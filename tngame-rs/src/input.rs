@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+/// Kitty keyboard protocol flags: disambiguate escape codes (1), report event types (2), report all keys
+/// as escape codes (8), and report associated text (16)
+pub const KITTY_ENABLE: &str = "\x1b[>27u";
+pub const KITTY_DISABLE: &str = "\x1b[<u";
+pub const KITTY_QUERY: &str = "\x1b[?u";
+
+/// Report the terminal gaining and losing focus
+pub const FOCUS_ENABLE: &str = "\x1b[?1004h";
+pub const FOCUS_DISABLE: &str = "\x1b[?1004l";
+
+/// A key on the keyboard
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Enter,
+    Tab,
+    Esc,
+    Backspace,
+    CtrlC,
+    /// Shift on its own, only reported with the kitty keyboard protocol
+    Shift,
+    /// The terminal answered the kitty keyboard protocol query, so press and release events are reported
+    KittySupported,
+    /// The terminal gained or lost focus, reported after FOCUS_ENABLE
+    FocusIn,
+    FocusOut,
+    Unknown,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Kind {
+    Press,
+    Repeat,
+    Release,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub kind: Kind,
+    /// Whether shift was held, when the terminal reports it
+    pub shift: bool,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, kind: Kind) -> Self {
+        Self { key, kind, shift: false }
+    }
+
+    fn press(key: Key) -> Self {
+        Self::new(key, Kind::Press)
+    }
+}
+
+/// Splits the bytes read from the terminal into key events, keeping incomplete escape codes and
+/// multi-byte characters around until the rest arrives
+#[derive(Default)]
+pub struct Parser {
+    buf: Vec<u8>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<KeyEvent> {
+        self.buf.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut i = 0;
+        while i < self.buf.len() {
+            match parse(&self.buf[i..]) {
+                Some((event, n)) => {
+                    events.extend(event);
+                    i += n;
+                }
+                None => break,
+            }
+        }
+        self.buf.drain(..i);
+        events
+    }
+}
+
+/// Parse one key from the start of the bytes, returning it and the number of bytes it used, or None if
+/// more bytes are needed
+fn parse(b: &[u8]) -> Option<(Option<KeyEvent>, usize)> {
+    let press = |key| Some((Some(KeyEvent::press(key)), 1));
+    match b[0] {
+        0x1b => match b.get(1) {
+            // A lone escape key, which can't be told apart from the start of an escape code that got
+            // split between reads, so it is only accepted at the end of the bytes read
+            None => press(Key::Esc),
+            Some(b'[') => parse_csi(b),
+            Some(b'O') => {
+                let key = match b.get(2)? {
+                    b'A' => Key::Up,
+                    b'B' => Key::Down,
+                    b'C' => Key::Right,
+                    b'D' => Key::Left,
+                    _ => Key::Unknown,
+                };
+                Some((Some(KeyEvent::press(key)), 3))
+            }
+            Some(_) => press(Key::Esc),
+        },
+        b'\r' | b'\n' => press(Key::Enter),
+        b'\t' => press(Key::Tab),
+        0x7f | 0x08 => press(Key::Backspace),
+        0x03 => press(Key::CtrlC),
+        c if c < 0x20 => Some((None, 1)),
+        _ => {
+            // Wait for all bytes of a multi-byte character
+            let len = match b[0] {
+                c if c >= 0xf0 => 4,
+                c if c >= 0xe0 => 3,
+                c if c >= 0xc0 => 2,
+                _ => 1,
+            };
+            if b.len() < len { return None; }
+            let key = std::str::from_utf8(&b[..len]).ok().and_then(|s| s.chars().next()).map_or(Key::Unknown, Key::Char);
+            Some((Some(KeyEvent::press(key)), len))
+        }
+    }
+}
+
+/// Parse a CSI escape code starting with "\x1b["
+fn parse_csi(b: &[u8]) -> Option<(Option<KeyEvent>, usize)> {
+    // Find the final byte of the escape code
+    let end = b.iter().skip(2).position(|c| (0x40..=0x7e).contains(c))? + 2;
+    let params = std::str::from_utf8(&b[2..end]).unwrap_or("");
+    let n = end + 1;
+
+    // Answer to the kitty keyboard protocol query
+    if params.starts_with('?') && b[end] == b'u' {
+        return Some((Some(KeyEvent::press(Key::KittySupported)), n));
+    }
+
+    // Modifiers and event type are in the second parameter as "modifiers:event"
+    let mut fields = params.split(';');
+    let code = fields.next().unwrap_or("");
+    let (mods, kind) = parse_modifiers(fields.next().unwrap_or(""));
+    let shift = mods & 1 != 0;
+    let ctrl = mods & 4 != 0;
+
+    let key = match b[end] {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'I' => Key::FocusIn,
+        b'O' => Key::FocusOut,
+        b'u' => {
+            let code: u32 = code.split(':').next().unwrap_or("").parse().unwrap_or(0);
+            // Text produced by the key, such as an upper case letter when shift is held
+            let text = fields.next().and_then(|t| t.split(':').next()?.parse::<u32>().ok()).and_then(char::from_u32);
+            match code {
+                13 => Key::Enter,
+                9 => Key::Tab,
+                27 => Key::Esc,
+                127 | 8 => Key::Backspace,
+                57441 | 57447 => Key::Shift,
+                99 if ctrl => Key::CtrlC,
+                _ if ctrl => Key::Unknown,
+                _ => match text.or_else(|| char::from_u32(code)) {
+                    Some(c) if shift && text.is_none() => Key::Char(c.to_ascii_uppercase()),
+                    Some(c) if !c.is_control() && !('\u{e000}'..='\u{f8ff}').contains(&c) => Key::Char(c),
+                    _ => Key::Unknown,
+                },
+            }
+        }
+        _ => Key::Unknown,
+    };
+    Some((Some(KeyEvent { key, kind, shift }), n))
+}
+
+/// Parse the "modifiers:event" parameter of a kitty key event into the modifier bits and the event kind
+fn parse_modifiers(field: &str) -> (u32, Kind) {
+    let mut parts = field.split(':');
+    let mods = parts.next().and_then(|m| m.parse::<u32>().ok()).unwrap_or(1).saturating_sub(1);
+    let kind = match parts.next() {
+        Some("2") => Kind::Repeat,
+        Some("3") => Kind::Release,
+        _ => Kind::Press,
+    };
+    (mods, kind)
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
+
+pub const FISH_ART: &str = "><>";
+pub const COIN_ART: &str = "$";
+
+/// Something lying around in the world for the cat to pick up
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Pickup {
+    Fish,
+    Coin,
+}
+
+impl Pickup {
+    /// Width of the sections that may each contain one, one in every few of which has one, and a
+    /// seed so that the fish and the coins lie in different places
+    fn spacing(self) -> i32 {
+        match self { Pickup::Fish => 60, Pickup::Coin => 30 }
+    }
+    fn chance(self) -> u32 {
+        match self { Pickup::Fish => 2, Pickup::Coin => 2 }
+    }
+    fn seed(self) -> u32 {
+        match self { Pickup::Fish => 0xf15, Pickup::Coin => 0xc01 }
+    }
+
+    pub fn art(self) -> &'static str {
+        match self { Pickup::Fish => FISH_ART, Pickup::Coin => COIN_ART }
+    }
+}
+
+/// Items the cat carries around
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    pub fish: u32,
+    /// Spent in the shop in the house
+    #[serde(default)]
+    pub coins: u32,
+
+    // Sections whose fish, or coin, has already been picked up
+    collected: HashSet<i32>,
+    #[serde(default)]
+    coins_collected: HashSet<i32>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self { fish: 0, coins: 0, collected: HashSet::new(), coins_collected: HashSet::new() }
+    }
+
+    fn collected(&self, kind: Pickup) -> &HashSet<i32> {
+        match kind { Pickup::Fish => &self.collected, Pickup::Coin => &self.coins_collected }
+    }
+
+    /// World x of the fish or coin lying in the section containing world x, if it wasn't picked up yet
+    fn in_section(&self, kind: Pickup, x: i32) -> Option<i32> {
+        let (spacing, chance) = (kind.spacing(), kind.chance());
+        let i = x.div_euclid(spacing);
+        let hash = utils::hash(i as u32 ^ kind.seed());
+        if i <= 0 || !hash.is_multiple_of(chance) || self.collected(kind).contains(&i) { return None; }
+        Some(i * spacing + (hash / chance % (spacing - kind.art().len() as i32) as u32) as i32)
+    }
+
+    /// World x of all fish or coins lying between two world x positions
+    pub fn between(&self, kind: Pickup, start: i32, end: i32) -> Vec<i32> {
+        let spacing = kind.spacing();
+        (start.div_euclid(spacing)..=end.div_euclid(spacing))
+            .filter_map(|i| self.in_section(kind, i * spacing))
+            .collect()
+    }
+
+    /// Pick up the fish or coin the cat is standing on, returning whether there was one
+    pub fn pick_up(&mut self, kind: Pickup, start: i32, end: i32) -> bool {
+        let w = kind.art().chars().count() as i32;
+        let Some(x) = self.between(kind, start, end).into_iter().find(|x| x + w > start && *x < end) else { return false; };
+        let section = x.div_euclid(kind.spacing());
+        match kind {
+            Pickup::Fish => {
+                self.collected.insert(section);
+                self.fish += 1;
+            }
+            Pickup::Coin => {
+                self.coins_collected.insert(section);
+                self.coins += 1;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coins_are_picked_up_once() {
+        let mut inv = Inventory::new();
+        let x = inv.between(Pickup::Coin, 0, 100_000)[0];
+        assert!(!inv.pick_up(Pickup::Coin, x - 10, x - 5));
+        assert!(inv.pick_up(Pickup::Coin, x, x + 4));
+        assert_eq!((inv.coins, inv.fish), (1, 0));
+        assert!(!inv.pick_up(Pickup::Coin, x, x + 4));
+        assert!(!inv.between(Pickup::Coin, 0, 100_000).contains(&x));
+    }
+
+    #[test]
+    fn nothing_lies_left_of_the_world() {
+        let inv = Inventory::new();
+        for kind in [Pickup::Fish, Pickup::Coin] {
+            assert!(inv.between(kind, -100_000, kind.spacing() - 1).is_empty());
+        }
+    }
+}
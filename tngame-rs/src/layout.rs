@@ -0,0 +1,116 @@
+use std::sync::LazyLock;
+
+/// A rectangle on the screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.w && other.x < self.x + self.w && self.y < other.y + other.h && other.y < self.y + self.h
+    }
+}
+
+/// Farthest a rectangle moves from where it wants to be, sideways and up or down. Text further away
+/// than that wouldn't look like it belongs to what it's next to anymore.
+const REACH: (i32, i32) = (24, 8);
+
+/// Moves within reach, nearest first. Moving vertically moves text further than moving horizontally,
+/// so it costs twice as much.
+static OFFSETS: LazyLock<Vec<(i32, i32)>> = LazyLock::new(|| {
+    let (rx, ry) = REACH;
+    let mut offsets: Vec<_> = (-rx..=rx).flat_map(|dx| (-ry..=ry).map(move |dy| (dx, dy))).collect();
+    offsets.sort_by_key(|(dx, dy)| dx.abs() + dy.abs() * 2);
+    offsets
+});
+
+/// Move rectangles, in order, to the free spot nearest to where they want to be, so that they don't
+/// overlap the obstacles or the rectangles placed before them. Rectangles stay within the screen below
+/// the top rows, and keep their spot when there's no free one within reach.
+pub fn arrange(rects: &mut [Rect], obstacles: &[Rect], w: i32, h: i32, top: i32) {
+    let mut taken = obstacles.to_vec();
+    for rect in rects.iter_mut() {
+        if !taken.iter().any(|t| t.overlaps(rect)) {
+            taken.push(*rect);
+            continue;
+        }
+
+        let free = OFFSETS.iter()
+            .map(|(dx, dy)| Rect { x: rect.x + dx, y: rect.y + dy, ..*rect })
+            .filter(|r| r.x >= 0 && r.x + r.w <= w && r.y >= top && r.y + r.h <= h)
+            .find(|r| !taken.iter().any(|t| t.overlaps(r)));
+        if let Some(free) = free {
+            *rect = free;
+        }
+        taken.push(*rect);
+    }
+}
+
+/// Logical size of the world when letterboxing is on without a size
+const LETTERBOX_SIZE: (i32, i32) = (120, 36);
+
+/// Where the world is drawn in a terminal of a size, when letterboxing is opted into with TN_LETTERBOX.
+/// Very large terminals stretch the scene until it looks sparse, so it can be drawn at a fixed logical
+/// size like "120x36" instead, centered with a border around it. In terminals too small for the size,
+/// the world shrinks to fit inside the border.
+pub fn letterbox(term_w: i32, term_h: i32) -> Option<Rect> {
+    let size = std::env::var("TN_LETTERBOX").ok()?;
+    let (w, h) = size.split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .unwrap_or(LETTERBOX_SIZE);
+    let (w, h) = (w.min(term_w - 2), h.min(term_h - 2));
+    if (w, h) == (term_w - 2, term_h - 2) || w <= 0 || h <= 0 { return None; }
+    Some(Rect { x: (term_w - w) / 2, y: (term_h - h) / 2, w, h })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+        Rect { x, y, w, h }
+    }
+
+    #[test]
+    fn leaves_free_rectangles_alone() {
+        let mut rects = [rect(0, 5, 4, 2), rect(10, 5, 4, 2)];
+        arrange(&mut rects, &[rect(20, 5, 4, 2)], 80, 24, 1);
+        assert_eq!(rects, [rect(0, 5, 4, 2), rect(10, 5, 4, 2)]);
+    }
+
+    #[test]
+    fn moves_sideways_before_up_or_down() {
+        // Six cells to the left cost less than four rows up or down
+        let mut rects = [rect(10, 10, 4, 2)];
+        arrange(&mut rects, &[rect(8, 8, 8, 6)], 80, 24, 1);
+        assert_eq!(rects, [rect(4, 10, 4, 2)]);
+    }
+
+    #[test]
+    fn later_rectangles_make_way_for_earlier_ones() {
+        let mut rects = [rect(10, 10, 4, 2), rect(11, 10, 4, 2)];
+        arrange(&mut rects, &[], 80, 24, 1);
+        assert_eq!(rects[0], rect(10, 10, 4, 2));
+        assert!(!rects[0].overlaps(&rects[1]));
+    }
+
+    #[test]
+    fn stays_on_the_screen_below_the_top() {
+        let mut rects = [rect(0, 1, 4, 2)];
+        arrange(&mut rects, &[rect(0, 0, 30, 3)], 80, 24, 1);
+        let r = rects[0];
+        assert!(r.x >= 0 && r.y >= 1 && r.x + r.w <= 80 && r.y + r.h <= 24, "{:?}", r);
+        assert!(!r.overlaps(&rect(0, 0, 30, 3)));
+    }
+
+    #[test]
+    fn keeps_its_spot_with_nothing_free() {
+        let mut rects = [rect(50, 20, 4, 2)];
+        arrange(&mut rects, &[rect(0, 0, 80, 24)], 80, 24, 0);
+        assert_eq!(rects, [rect(50, 20, 4, 2)]);
+    }
+}
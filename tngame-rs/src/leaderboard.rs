@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::save;
+
+/// Number of entries shown per board
+pub const SHOWN: usize = 3;
+
+/// Minigames with a board on the leaderboard
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Board {
+    Sled,
+    Fish,
+    Snowball,
+}
+
+/// Best scores of every visitor, shared between all sessions of a server
+#[derive(Serialize, Deserialize, Default)]
+pub struct Leaderboard {
+    #[serde(default)]
+    sled: BTreeMap<String, u32>,
+    #[serde(default)]
+    fish: BTreeMap<String, u32>,
+    #[serde(default)]
+    snowball: BTreeMap<String, u32>,
+}
+
+/// Whether the game runs for visitors of a server, which the relay signals with TN_SERVER
+pub fn server_mode() -> bool {
+    env::var("TN_SERVER").is_ok()
+}
+
+/// Name of the visitor, passed by the relay in TN_PLAYER
+pub fn player_name() -> String {
+    env::var("TN_PLAYER").ok()
+        .map(|n| n.chars().filter(|c| !c.is_control()).take(16).collect::<String>())
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| "Visitor".to_string())
+}
+
+impl Leaderboard {
+    /// Location of the leaderboard file, which can be overridden with TN_LEADERBOARD
+    pub fn path() -> Option<PathBuf> {
+        save::data_path("TN_LEADERBOARD", "leaderboard.json")
+    }
+
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn scores(&self, board: Board) -> &BTreeMap<String, u32> {
+        match board {
+            Board::Sled => &self.sled,
+            Board::Fish => &self.fish,
+            Board::Snowball => &self.snowball,
+        }
+    }
+
+    fn scores_mut(&mut self, board: Board) -> &mut BTreeMap<String, u32> {
+        match board {
+            Board::Sled => &mut self.sled,
+            Board::Fish => &mut self.fish,
+            Board::Snowball => &mut self.snowball,
+        }
+    }
+
+    /// Record a score if it beats the visitor's best. Other sessions may have written the file in the
+    /// meantime, so it is loaded again first and replaced in one rename.
+    pub fn record(&mut self, board: Board, name: &str, score: u32) -> Result<()> {
+        *self = Self::load();
+        let best = self.scores_mut(board).entry(name.to_string()).or_insert(0);
+        if score <= *best { return Ok(()); }
+        *best = score;
+
+        let path = Self::path().ok_or_else(|| anyhow!("No location for the leaderboard"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// The best scores of a board, highest first
+    pub fn top(&self, board: Board) -> Vec<(String, u32)> {
+        let mut top: Vec<_> = self.scores(board).iter().map(|(n, s)| (n.clone(), *s)).collect();
+        top.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        top.truncate(SHOWN);
+        top
+    }
+}
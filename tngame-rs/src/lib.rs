@@ -0,0 +1,2791 @@
+// Some of the game is only used by the terminal front-end, which the browser build leaves out
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::string::ToString;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::admin::Admin;
+use crate::affection::Affection;
+use crate::autosave::Autosave;
+use crate::camera::Camera;
+use crate::clock::Phase;
+use crate::color::{Direction, Gradient};
+use crate::command::{Command, Destination};
+use crate::compass::{Compass, Landmark};
+use crate::cowsay::{gen_bubble_ascii, BubbleKind, BubbleStyle};
+use crate::cutscene::{Actor, Cutscene, Script};
+use crate::emotion::{Context, Emotions};
+use crate::events::{Anchor, Decoration, Event, Flakes};
+use crate::guestbook::Guestbook;
+use crate::inventory::Pickup;
+use crate::layout::Rect;
+use crate::leaderboard::{Board, Leaderboard};
+use crate::map::{Asset, Editor, Entity, Role};
+use crate::minimap::Minimap;
+use crate::movement::Movement;
+use crate::narration::Narrator;
+use crate::path::MoveTo;
+use crate::palette::Palette;
+use crate::photo::Photo;
+use crate::settle::{Settled, Surfaces};
+use crate::phrases::{Phrases, Place};
+use crate::particles::{Emitter, Particle, Velocity};
+use crate::presence::Visitors;
+use crate::quality::Quality;
+use crate::reader::Reader;
+use crate::render::{Pixel, Renderer};
+use crate::replay::{Playback, Record, Recorder};
+use crate::save::Save;
+use crate::secrets::Secrets;
+use crate::shared::SharedWorld;
+use crate::pose::Pose;
+use crate::sled::Sled;
+use crate::snowball::Fight;
+use crate::sprite::Sprite;
+use crate::stats::Stats;
+use crate::stress::Stress;
+use crate::tilemap::Ground;
+use crate::toast::Kind as ToastKind;
+use crate::weather::{Precipitation, Weather};
+use crate::widget::{Action, Menu, Widget};
+use crate::utils::Instant;
+use crate::warmth::Warmth;
+use crate::ui::Ui;
+use crate::world::World;
+
+mod admin;
+mod affection;
+#[cfg(not(target_arch = "wasm32"))]
+mod args;
+mod assets;
+mod autosave;
+mod blizzard;
+#[cfg(not(target_arch = "wasm32"))]
+mod broadcast;
+mod clock;
+mod command;
+mod camera;
+mod compass;
+mod color;
+mod cowsay;
+mod cutscene;
+mod debug;
+mod emote;
+mod emotion;
+mod events;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+mod game;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod garden;
+mod guestbook;
+#[cfg(not(target_arch = "wasm32"))]
+mod handshake;
+mod input;
+mod inventory;
+mod layout;
+mod light;
+mod leaderboard;
+mod map;
+mod minimap;
+mod movement;
+mod narration;
+mod npc;
+mod particles;
+mod path;
+mod phrases;
+mod palette;
+mod photo;
+mod pose;
+mod pond;
+mod presence;
+mod quality;
+mod reader;
+mod render;
+mod replay;
+mod save;
+mod secrets;
+mod settle;
+mod shadow;
+mod shake;
+mod shared;
+mod shop;
+mod sled;
+mod snowball;
+mod sprite;
+mod stats;
+mod stress;
+mod term;
+#[cfg(not(target_arch = "wasm32"))]
+mod terminal;
+mod terrain;
+mod theme;
+mod tilemap;
+mod toast;
+#[cfg(not(target_arch = "wasm32"))]
+mod twitch;
+mod transport;
+#[cfg(feature = "ratatui")]
+mod tui;
+mod ui;
+mod utils;
+mod warmth;
+#[cfg(target_arch = "wasm32")]
+mod web;
+mod weather;
+mod widget;
+mod world;
+
+pub use crate::game::{Game, GameBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::terminal::run;
+pub use crate::input::{Key, KeyEvent, Kind};
+pub use crate::theme::Theme;
+
+const RESET: &str = "\x1b[0m";
+
+/// Constants
+const SNOW_DENSITY: f32 = 0.04; // Snow particles per pixel on screen
+const SNOW_SPEED: f32 = 6.0; // Snow fall speed in pixels per second
+const SNOW_X_RAND: f32 = 0.5; // Snow x velocity randomization factor
+const SNOW_VELOCITY: Velocity = Velocity::Uniform {
+    vx: (-SNOW_X_RAND * SNOW_SPEED, SNOW_X_RAND * SNOW_SPEED),
+    vy: (SNOW_SPEED, 2.0 * SNOW_SPEED),
+};
+const SNOW_REDUCED_MOTION: f32 = 0.2; // Snow fall speed multiplier with --reduce-motion
+const SNOW_TUNING_STEP: f32 = 0.25; // How much the snow tuning keys change the density and speed multipliers
+const SNOW_TUNING_RANGE: (f32, f32) = (0.25, 4.0); // Range of the density and speed multipliers
+const SNOW_GLYPH: &[char] = &['*'];
+const MAX_SNOW: usize = 5000; // Most snow particles, however large the screen and the density
+const SNOW_FRONT: f64 = 0.5; // Share of the snow flakes falling in front of the scene, while the others fall behind it
+const WIND_SEED: u32 = 0x3d1;
+const WIND_STRENGTH: f32 = 1.5; // Strongest gust in pixels per second, blowing either way
+const WIND_CHANGE: f32 = 0.1; // Gusts per second
+const WIND_OCTAVES: u32 = 3;
+const CHIMNEY_DX: i32 = 9; // Column of the chimney in the house art, where smoke rises from
+const SMOKE_RATE: f32 = 3.0; // Smoke puffs per second
+const SMOKE_VELOCITY: Velocity = Velocity::Uniform { vx: (0.5, 2.5), vy: (-3.0, -2.0) }; // Rising and drifting with the wind
+const SMOKE_GRAVITY: f32 = 0.6; // Smoke slows down as it rises, pulling upwards motion back towards zero
+const SMOKE_LIFE: f32 = 3.0;
+const SMOKE_GLYPHS: &[char] = &['o', 'o', '~']; // Puffs thin out before they dissipate
+
+/// Snowball splat constants, bursting where a snowball hits someone
+const SPLAT_PARTICLES: usize = 8;
+const SPLAT_SPEED: f32 = 10.0;
+const SPLAT_LIFE: f32 = 0.4;
+const SPLAT_COLORS: &[&[&str]] = &[&["\x1b[38;2;255;255;255m", "\x1b[38;2;190;210;230m"]];
+const SPLAT_GLYPHS: &[char] = &['*', '.'];
+
+const TITLE_SHIMMER_SPEED: f32 = 0.15; // Times per second the rainbow on the title moves across it
+const SNOW_COVER_CHAR: char = '_';
+const ICE_CHAR: char = '=';
+
+
+
+/// World cells drawn in each column of the screen when zoomed out
+const ZOOM_OUT: i32 = 2;
+
+/// World cells beyond each side of the screen in which particles in world space are still moved
+const CULL_MARGIN: f32 = 16.0;
+
+/// Screen size when there is no terminal to measure, like when printing a frame to a pipe or running
+/// in a browser
+const DEFAULT_TERM_SIZE: (u16, u16) = (80, 24);
+
+
+/// Speeds time can run at in debug and photo mode, from slow motion to a time-lapse
+const TIME_SCALES: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Time between two frames
+const FRAME_TIME: Duration = Duration::from_millis(1000 / 20);
+
+/// Longest message the cat can be made to say, and the width it is wrapped to in its bubble
+const CHAT_MAX_LENGTH: usize = 80;
+const CHAT_WRAP: usize = 30;
+
+/// Rows at the top of the screen taken by the HUD
+const HUD_HEIGHT: i32 = 1;
+
+/// Line on the HUD a few seconds before a blizzard hits
+const BLIZZARD_WARNING: &str = "! A blizzard is coming !";
+
+/// Banner shown at the top of the screen while the game is paused
+const PAUSED: &str = "Paused - press [space] to resume";
+
+/// How long a message said by the cat stays on screen
+const MESSAGE_DURATION: Duration = Duration::from_secs(4);
+
+/// How long an announcement from the host stays on screen
+const ANNOUNCEMENT_DURATION: Duration = Duration::from_secs(10);
+
+/// Distance from the house within which the cat doesn't feel cold at night
+const WARMTH_RADIUS: i32 = 30;
+
+/// Cat gliding constants
+const GLIDE_RATE: f32 = 25.0; // How quickly the drawn cat catches up with its position, per second
+const GLIDE_SNAP: f32 = 0.05; // Distance in cells at which the drawn cat snaps to its position
+const GLIDE_MAX: f32 = 6.0; // Distance in cells above which the cat jumps instead of gliding
+
+/// Screen shakes as amplitude in cells and duration in seconds
+const SHAKE_JUMP: (f32, f32) = (1.0, 0.2); // The cat landing from a jump
+const SHAKE_CRASH: (f32, f32) = (2.0, 0.5); // The sled crashing into an obstacle
+
+/// Ice sliding constants
+const ICE_PUSH: f32 = 10.0; // Velocity added to the cat by a keypress on ice in pixels per second
+const ICE_MAX_SPEED: f32 = 30.0; // Maximum sliding velocity in pixels per second
+const ICE_FRICTION: f32 = 0.35; // Fraction of the sliding velocity that remains after one second
+
+
+/// AsciiArt is a struct that holds the ascii art and the credit for the art.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AsciiArt {
+    art: String,
+    h: i32,
+    w: i32,
+    credit: String,
+}
+
+impl AsciiArt {
+    fn new(art: &str, credit: &str) -> Self {
+        // Trim empty line breaks from the art and calculate the height and width
+        let art = art.trim_matches('\n');
+        let h = art.lines().count();
+        let w = art.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        Self {
+            art: art.to_string(),
+            h: h as i32,
+            w: w as i32,
+            credit: credit.to_string(),
+        }
+    }
+}
+
+/// Start a snow flake falling again from the top of the screen
+fn respawn_flake(p: &mut Particle) {
+    let (vx, vy) = SNOW_VELOCITY.sample(&mut *utils::rng(), 0, 1);
+    p.vx = vx;
+    p.vy = vy;
+    p.y = 0.0;
+}
+
+/// Number of snow particles for a screen size and a density multiplier, capped for huge screens
+fn snow_count(width: i32, height: i32, density: f32) -> usize {
+    (((width * height) as f32 * SNOW_DENSITY * density) as usize).min(MAX_SNOW)
+}
+
+/// Emitter of the smoke rising from the chimney, moved onto the chimney every frame
+fn chimney_smoke(cn: &Consts) -> Emitter {
+    let mut e = Emitter::new(0.0, 0.0, SMOKE_VELOCITY, SMOKE_LIFE, &[&cn.colors.smoke], SMOKE_GLYPHS);
+    e.rate = SMOKE_RATE;
+    e.gravity = SMOKE_GRAVITY;
+    e
+}
+
+fn create_snow(width: i32, height: i32, count: usize, cn: &Consts) -> Vec<Particle> {
+    let flakes = cn.flakes();
+    let colors = &cn.colors.snow;
+    let mut snow = Vec::with_capacity(count);
+    let mut rng = utils::rng();
+    for _ in 0..count {
+        let x = rng.gen_range(0.0..width as f32);
+        let y = rng.gen_range(0.0..height as f32);
+        let (vx, vy) = SNOW_VELOCITY.sample(&mut *rng, 0, 1);
+        let mut color = colors[rng.gen_range(0..colors.len())];
+        let mut glyph = SNOW_GLYPH;
+
+        // Seasonal events can replace some of the snow flakes
+        if let Some(f) = flakes.filter(|f| rng.gen_bool(f.share as f64)) {
+            color = f.colors[rng.gen_range(0..f.colors.len())];
+            glyph = std::slice::from_ref(&f.char);
+        }
+        let mut flake = Particle::endless(x, y, vx, vy, [color], glyph);
+        flake.front = rng.gen_bool(SNOW_FRONT);
+        snow.push(flake);
+    }
+    snow
+}
+
+struct Consts {
+    asc_cat: AsciiArt,
+    // The cat sitting on a bench, with its tail hanging off the seat
+    asc_cat_sitting: AsciiArt,
+    asc_cat_skating: AsciiArt,
+    asc_cat_loaf: AsciiArt,
+    asc_cat_sleeping: AsciiArt,
+    // Cat sprites for every emotion, in the order of the Emotion enum
+    cat_sprites: Vec<Sprite>,
+    asc_fish: AsciiArt,
+    asc_coin: AsciiArt,
+    npc_arts: Vec<AsciiArt>,
+    asc_sign: AsciiArt,
+    asc_signpost: AsciiArt,
+    asc_bulletin: AsciiArt,
+    asc_board: AsciiArt,
+    asc_website: AsciiArt,
+    asc_tree: AsciiArt,
+    asc_house: AsciiArt,
+    asc_title: AsciiArt,
+    asc_soon: AsciiArt,
+    asc_sled: AsciiArt,
+    asc_snowman: AsciiArt,
+    asc_lamp: AsciiArt,
+    asc_campfire: AsciiArt,
+    asc_bench: AsciiArt,
+    asc_sun: AsciiArt,
+    asc_moon: AsciiArt,
+
+    // Seasonal events active today, and the decorations they place in the world
+    events: Vec<&'static Event>,
+    decorations: Vec<(&'static Decoration, AsciiArt)>,
+
+    // Colors the scene is drawn in
+    colors: Palette,
+
+    // Lines of the NPCs replacing the built-in ones, by NPC and node of the conversation
+    dialogue: HashMap<(usize, usize), String>,
+}
+
+struct Mutes {
+    // Art and colors the game is drawn with, swapped for new ones when the assets folder changes
+    assets: Arc<Consts>,
+
+    // The world and the cat in it, the part of it on screen, and the screen it is drawn on
+    world: World,
+    camera: Camera,
+    renderer: Renderer,
+
+    // What is open on screen on top of the scene, and what the visitor typed and said
+    ui: Ui,
+
+    // The map editor, when editing a map
+    editor: Option<Editor>,
+
+    // The cutscene playing, whether cutscenes play at all, where the NPCs are as a percentage of the
+    // screen width, and the cells they walked away from there in cutscenes
+    cutscene: Option<Cutscene>,
+    cutscenes: bool,
+    npc_vw: Vec<f32>,
+    npc_dx: Vec<f32>,
+
+    // Map of the explored world along the top of the screen, and the compass pointing to the places
+    // the cat hasn't been to yet
+    minimap: Minimap,
+    compass: Compass,
+
+    last_update: Instant,
+    time: f32,
+    // How fast the simulation runs, multiplying the time of every frame
+    time_scale: f32,
+
+    // Multipliers of the snow density and fall speed, tuned by the visitor
+    snow_density: f32,
+    snow_speed: f32,
+    snow_enabled: bool,
+    // Drawing less over slow connections
+    quality: Quality,
+    // Calm the screen for visitors sensitive to motion: slow snow, no shaking and nothing flashing
+    reduce_motion: bool,
+    should_exit: bool,
+    paused: bool,
+    // Whether the terminal has focus. The game stands still and draws rarely while it doesn't.
+    focused: bool,
+
+    // Debug mode lets the visitor step through paused frames one at a time, reporting what changed
+    debug: bool,
+    step: bool,
+    step_log: Vec<String>,
+
+    // Cheats let the visitor type commands after ":", and are on with TN_CHEATS or in debug mode
+    cheats: bool,
+
+    // Frames simulated so far, and the input being recorded or replayed. Both run the simulation at a
+    // fixed frame time so that replays match the recording.
+    frame: u64,
+    recorder: Option<Recorder>,
+    playback: Option<Playback>,
+    state: State,
+
+    // A message the cat says regardless of its location, and the unix time in milliseconds it was said at
+    message: Option<(String, u64)>,
+    // The lines picked for the places the cat is at
+    phrases: Phrases,
+
+    stats: Stats,
+
+    // The last keys pressed, watched for secret codes, and the easter eggs they set off: the time a UFO
+    // started flying over, and whether the cat is a rainbow
+    secrets: Secrets,
+    ufo: Option<f32>,
+    nyan: bool,
+    affection: Affection,
+    emotions: Emotions,
+
+    // Whether cozy mode is on, where the cat gets cold at night and in blizzards, and how warm it is
+    cozy: bool,
+    warmth: Warmth,
+
+    // Game time of the last keypress, and the real time of the last key or focus event, which wakes
+    // up a game drawing slowly
+    last_input: f32,
+    woken: Instant,
+
+    // Whether the terminal reports key releases
+    kitty: bool,
+
+    // Whether the progress is written to the save file, which greeters and embedded games leave alone
+    persist: bool,
+    save: Save,
+    guestbook: Guestbook,
+    shared: SharedWorld,
+
+    // Whether the game is autosaved while playing, when it was last autosaved, and the autosave of a
+    // visit that ended suddenly while the player decides whether to restore it
+    autosave: bool,
+    last_autosave: f32,
+    recovered: Option<Autosave>,
+
+    // Shared leaderboard in server mode, when it was last loaded, and the name of the visitor on it
+    leaderboard: Option<Leaderboard>,
+    leaderboard_loaded: f32,
+    player: String,
+
+    // Other visitors' cats in server mode
+    visitors: Option<Visitors>,
+
+    // Orders from the host's admin console in server mode
+    admin: Option<Admin>,
+
+    // Crowd and frame times of stress test mode
+    stress: Option<Stress>,
+
+    // Border style of speech bubbles
+    bubble_style: BubbleStyle,
+
+    // Plain sentences told instead of drawing the screen in narration mode, and what the cat says
+    // in the current frame for them
+    narrator: Option<Narrator>,
+    said: Vec<String>,
+
+    // Speech bubbles of the current frame, waiting to be laid out
+    bubbles: Vec<(AsciiArt, Rect, &'static str)>,
+
+    // Tops of the things snow can land on, and the snow resting on them
+    surfaces: Surfaces,
+    settled: Settled,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    Welcome,
+    Exploring,
+    Sledding,
+    // Talking to an NPC, at a node of its conversation
+    Talking { npc: usize, node: usize },
+    // Typing a message for the guestbook
+    Writing,
+    // Typing something for the cat to say
+    Chatting,
+    // Typing the line an entity says in the map editor
+    Labeling,
+    Commanding,
+    // Reading the credits
+    Credits,
+    // Picking an emote to show above the cat
+    Emoting,
+    // Buying and putting on cosmetics in the shop in the house
+    Shopping,
+}
+
+impl Consts {
+    fn new() -> Self {
+        // Initialize the ascii art
+        let asc_cat = AsciiArt::new(
+            r#"
+ /\_/\
+( | | )
+ >   < "#, "Azalea");
+        let asc_cat_sitting = AsciiArt::new(
+            r#"
+ /\_/\
+( ^.^ )
+ (")(")~"#, "Azalea");
+        let asc_cat_skating = AsciiArt::new(
+            r#"
+ /\_/\
+( ^.^ )
+_>   <_"#, "Azalea");
+        let asc_cat_loaf = AsciiArt::new(
+            r#"
+ /\_/\
+( o.o )
+(_____)~"#, "Azalea");
+        let asc_cat_sleeping = AsciiArt::new(
+            r#"
+  /\_/\
+ ( -.- )
+(__ __ _)~"#, "Azalea");
+        let cat_sprites = vec![
+            // Neutral
+            Sprite::still(&asc_cat.art, "Azalea"),
+            // Happy
+            Sprite::still(r#"
+ /\_/\
+( ^ ^ )
+ >   < "#, "Azalea"),
+            // Lonely
+            Sprite::still(r#"
+ /\_/\
+( . . )
+ >   < "#, "Azalea"),
+            // Surprised
+            Sprite::still(r#"
+ /\_/\ !
+( O O )
+ >   < "#, "Azalea"),
+            // Sleepy, with floating z's
+            Sprite::new(&[r#"
+ /\_/\ z
+( - - )
+ >   < "#, r#"
+ /\_/\  Z
+( - - )
+ >   < "#], 1.0, "Azalea"),
+            // Cold, shivering left and right
+            Sprite::new(&[r#"
+ /\_/\
+( > < )
+ >   < "#, r#"
+  /\_/\
+ ( > < )
+  >   < "#], 8.0, "Azalea"),
+        ];
+        let asc_fish = AsciiArt::new(inventory::FISH_ART, "Azalea");
+        let asc_coin = AsciiArt::new(inventory::COIN_ART, "Azalea");
+        let asc_sign = AsciiArt::new(
+            r#"
+.------.
+|GUEST |
+| BOOK |
+'------'
+   ||"#, "Azalea");
+        let asc_signpost = AsciiArt::new(
+            r#"
+.-----.
+| ~~~ |
+'-----'
+   |"#, "Azalea");
+        let asc_bulletin = AsciiArt::new(
+            r#"
+.----------.
+| == = === |
+| = ==  == |
+'----------'
+ |        |"#, "Azalea");
+        let asc_board = AsciiArt::new(
+            r#"
+.-------.
+| TOP 3 |
+|_______|
+ |     |"#, "Azalea");
+        let asc_website = AsciiArt::new(
+            r#"
+.-----------.
+| hydev.org |
+'-----------'
+      ||"#, "Azalea");
+        let npc_arts = npc::NPCS.iter().map(|n| AsciiArt::new(n.art, "Azalea")).collect();
+        let asc_tree = AsciiArt::new(
+            r#"
+          %%%,%%%%%%%
+       ,'%% \\-*%%%%%%%
+ ;%%%%%*%   _%%%%"
+  ,%%%       \(_.*%%%%.
+  % *%%, ,%%%%*(    '
+%^     ,*%%% )\|,%%*%,_
+     *%    \/ #).-"*%%*
+         _.) ,/ *%,
+          /)#(
+         /   \ "#, "b'ger from ascii.co.uk/art/tree");
+        let asc_house = AsciiArt::new(
+            r#"
+         _
+     ,--l l--------,
+    / /^/    /^/  / \
+   /_.--.___.--._/   \
+   | ,--,   ,--, |  ,|
+ ,%| '--'._.'--' |,o%o
+.*%|_,%%_| |_%%,_|#%%%*"#, "Modified from hjw from ascii.co.uk/art/house");
+        let asc_title = AsciiArt::new(
+            r#"
+                 .       *
+         _.__. _.| _  _. ' __
+        (_] /_(_]|(/,(_]  _)
+                                .  .
+ __._  _ .    ,  .  .    , _ ._.| _|
+_) [ )(_) \/\/ \_|   \/\/ (_)[  |(_]
+               ._|                  "#, "Generated by patorjk.com/software/taag with font Contessa");
+        let asc_soon = AsciiArt::new(
+            r#"
+ _. _ ._ _ *._  _    __ _  _ ._ ._ ._ ._  (TM)
+(_.(_)[ | )|[ )(_]  _) (_)(_)[ )[ )[ )[ ) *  *  *  *  *
+               ._|
+
+Still paving the paths for the journey ahead...
+(btw, isn't 3d-ish holographic text like suuuper cool??!)"#, "Generated by patorjk.com/software/taag with font Contessa");
+        let asc_snowman = AsciiArt::new(
+            r#"
+ _|_
+ (")
+( : )"#, "Azalea");
+        let asc_lamp = AsciiArt::new(
+            r#"
+ _
+[o]
+ |
+ |
+_|_"#, "Azalea");
+        let asc_campfire = AsciiArt::new(
+            r#"
+  (
+ )\)
+'---'"#, "Azalea");
+        let asc_bench = AsciiArt::new(
+            r#"
+.-------.
+|_______|
+ |     |"#, "Azalea");
+        let asc_sled = AsciiArt::new(
+            r#"
+.-----.
+'-'-'-'"#, "Azalea");
+        let asc_sun = AsciiArt::new(
+            r#"
+\|/
+-O-
+/|\"#, "Azalea");
+        let asc_moon = AsciiArt::new(
+            r#"
+ .-.
+(  (
+ '-'"#, "Azalea");
+
+        // Check the calendar for seasonal events
+        let events = events::active(events::today());
+        for e in &events {
+            log::info!("Seasonal event active: {}", e.name);
+        }
+        let decorations = events.iter()
+            .flat_map(|e| e.decorations.iter())
+            .map(|d| (d, AsciiArt::new(d.art, "Azalea")))
+            .collect();
+        Self {
+            asc_cat,
+            asc_cat_sitting,
+            asc_cat_skating,
+            asc_cat_loaf,
+            asc_cat_sleeping,
+            cat_sprites,
+            asc_fish,
+            asc_coin,
+            npc_arts,
+            asc_sign,
+            asc_signpost,
+            asc_bulletin,
+            asc_board,
+            asc_website,
+            asc_tree,
+            asc_house,
+            asc_title,
+            asc_soon,
+            asc_sled,
+            asc_snowman,
+            asc_lamp,
+            asc_campfire,
+            asc_bench,
+            asc_sun,
+            asc_moon,
+            events,
+            decorations,
+            colors: Palette::default(),
+            dialogue: HashMap::new(),
+        }
+    }
+}
+
+impl Consts {
+    /// Art of an asset placed by a map
+    fn art(&self, asset: Asset) -> &AsciiArt {
+        match asset {
+            Asset::Tree => &self.asc_tree,
+            Asset::House => &self.asc_house,
+            Asset::Sign => &self.asc_sign,
+            Asset::Board => &self.asc_board,
+            Asset::Website => &self.asc_website,
+            Asset::Snowman => &self.asc_snowman,
+            Asset::Sled => &self.asc_sled,
+            Asset::Fish => &self.asc_fish,
+            Asset::Lamp => &self.asc_lamp,
+            Asset::Campfire => &self.asc_campfire,
+            Asset::Bench => &self.asc_bench,
+            Asset::Signpost => &self.asc_signpost,
+            Asset::Bulletin => &self.asc_bulletin,
+        }
+    }
+
+    /// Line an NPC says at a node of its conversation
+    fn line(&self, npc: usize, node: usize) -> &str {
+        self.dialogue.get(&(npc, node)).map_or(npc::NPCS[npc].nodes[node].text, String::as_str)
+    }
+
+    /// Particles replacing some of the snow flakes during a seasonal event
+    fn flakes(&self) -> Option<&'static Flakes> {
+        self.events.iter().copied().find_map(|e| e.flakes.as_ref())
+    }
+
+    /// Credit lines for all the art in the world
+    fn credits(&self) -> Vec<String> {
+        let arts = [("Cat", &self.asc_cat), ("Tree", &self.asc_tree), ("House", &self.asc_house), ("Title", &self.asc_title)];
+        arts.iter().map(|(what, art)| format!("{}: {}", what, art.credit)).collect()
+    }
+}
+
+impl Mutes {
+    /// A game that keeps the progress, the guestbook and the shared world in their files, and meets
+    /// the other visitors in server mode
+    fn new(consts: Arc<Consts>, size: (i32, i32)) -> Self {
+        let mut mt = Self::detached(consts, size);
+        mt.save = Save::load();
+        mt.persist = !mt.save.newer();
+        mt.guestbook = Guestbook::load();
+        mt.shared = SharedWorld::load();
+        mt.debug = env::var("TN_DEBUG").is_ok();
+        mt.cheats = env::var("TN_CHEATS").is_ok() || mt.debug;
+        mt.bubble_style = BubbleStyle::from_env();
+        mt.player = leaderboard::player_name();
+        if leaderboard::server_mode() {
+            mt.leaderboard = Some(Leaderboard::load());
+            mt.visitors = Visitors::open();
+            mt.admin = Admin::open();
+        }
+        mt
+    }
+
+    /// A game that starts fresh and keeps nothing, for greeters and apps embedding the scene
+    fn detached(consts: Arc<Consts>, (width, height): (i32, i32)) -> Self {
+        // Very large terminals can draw the world in a box of a fixed size
+        let renderer = Renderer::new(width, height, consts.colors.border);
+        let (width, height) = (renderer.w, renderer.h);
+        let world = World::new(width, height, &consts);
+        let npc_vw = npc::NPCS.iter().map(|n| n.slot(world.clock.hours()).x_vw).collect();
+
+        Self {
+            assets: consts,
+            world,
+            camera: Camera::new(width),
+            renderer,
+            ui: Ui::default(),
+            editor: None,
+            cutscene: None,
+            cutscenes: false,
+            npc_vw,
+            npc_dx: vec![0.0; npc::NPCS.len()],
+            minimap: Minimap::new(0, width),
+            compass: Compass::default(),
+            last_update: Instant::now(),
+            time: 0.0,
+            time_scale: 1.0,
+            snow_density: 1.0,
+            snow_speed: 1.0,
+            quality: Quality::default(),
+            reduce_motion: false,
+            snow_enabled: true,
+            should_exit: false,
+            paused: false,
+            focused: true,
+            debug: false,
+            cheats: false,
+            step: false,
+            step_log: Vec::new(),
+            frame: 0,
+            recorder: None,
+            playback: None,
+            state: State::Welcome,
+            message: None,
+            phrases: Phrases::default(),
+            stats: Stats::default(),
+            secrets: Secrets::default(),
+            ufo: None,
+            nyan: false,
+            affection: Affection::new(),
+            cozy: false,
+            warmth: Warmth::new(),
+            emotions: Emotions::new(),
+            last_input: 0.0,
+            woken: Instant::now(),
+            kitty: false,
+            persist: false,
+            save: Save::default(),
+            guestbook: Guestbook::default(),
+            shared: SharedWorld::session(Vec::new(), 0.0),
+            autosave: false,
+            last_autosave: 0.0,
+            recovered: None,
+            leaderboard: None,
+            leaderboard_loaded: 0.0,
+            player: "Visitor".to_string(),
+            visitors: None,
+            admin: None,
+            stress: None,
+            bubble_style: BubbleStyle::Classic,
+            narrator: None,
+            said: Vec::new(),
+            bubbles: Vec::new(),
+            surfaces: Surfaces::default(),
+            settled: Settled::default(),
+        }
+    }
+
+    /// The fireplace in the house is lit from dusk until dawn
+    fn fireplace_lit(&self) -> bool {
+        self.world.clock.phase() != Phase::Day
+    }
+
+    /// Keep the smoke emitter on the chimney, puffing while the fireplace is lit, and draw the smoke
+    fn update_smoke(&mut self, cn: &Consts, dt: f32) {
+        let house = entity(self, cn, Role::House);
+        let (x, y) = house.map_or((0, 0), |(x, art)| (x + CHIMNEY_DX, self.rest_y(art, x) - 1));
+        let lit = self.fireplace_lit() && house.is_some();
+        let chimney = &mut self.world.smoke.emitters[0];
+        (chimney.x, chimney.y, chimney.on) = (x as f32, y as f32, lit);
+        self.world.smoke.window = Some(self.world_window());
+        self.world.smoke.update(dt);
+
+        let smoke = std::mem::take(&mut self.world.smoke);
+        smoke.draw(self, true);
+        self.world.smoke = smoke;
+    }
+
+    /// How hard a blizzard blows from 0 to 1, which only happens while it snows
+    fn blizzard(&self) -> f32 {
+        let snowing = self.snow_enabled && self.world.weather.kind == Precipitation::Snow;
+        if snowing { self.world.blizzard.strength(self.time) } else { 0.0 }
+    }
+
+    /// Snow density multiplier with the extra snow of a blizzard
+    fn blizzard_density(&self) -> f32 {
+        self.snow_density * (1.0 + (blizzard::DENSITY - 1.0) * self.blizzard())
+    }
+
+    /// Number of snow particles falling out of the pool, which the density, the weather and blizzards
+    /// decide
+    fn active_snow(&self) -> usize {
+        let total = snow_count(self.renderer.w, self.renderer.h, self.blizzard_density()).min(self.world.snow.particles.len());
+        let count = if self.snow_enabled { self.world.weather.active(total) } else { 0 };
+        (count as f32 * self.quality.particles()) as usize
+    }
+
+    /// Screen column of a snow particle, which scrolls at half the speed of the world
+    fn snow_column(&self, x: f32) -> i32 {
+        (x.round() as i32 + self.renderer.w - self.get_scroll() / 2).rem_euclid(self.renderer.w)
+    }
+
+    /// Update snow particles. Flakes never die, but fall again from the top after reaching the ground,
+    /// and only as many of them as the weather calls for are moved.
+    fn update_snow(&mut self, dt: f32) {
+        let scroll = self.get_scroll();
+
+        // The weather decides how many particles fall, and whether they fall as rain
+        let count = self.active_snow();
+        let rain = self.world.weather.kind == Precipitation::Rain;
+        let calm = if self.reduce_motion { SNOW_REDUCED_MOTION } else { 1.0 };
+        let speed = if rain { weather::RAIN_SPEED } else { 1.0 } * self.snow_speed * calm;
+
+        // Gusts of wind blow all flakes sideways together, slowly changing direction and strength, and
+        // much harder in a blizzard
+        let gust = (utils::fractal(WIND_SEED, self.time * WIND_CHANGE, WIND_OCTAVES) * 2.0 - 1.0) * WIND_STRENGTH;
+        let wind = gust * (1.0 + (blizzard::WIND - 1.0) * self.blizzard());
+
+        // Loop through all active snow particles
+        for p in self.world.snow.particles.iter_mut().take(count) {
+            // Update the snow particle position
+            let before = p.y.round() as i32;
+            p.step(dt * speed);
+            p.x += wind * dt * speed;
+
+            // If the snow particle is out of x bounds, wrap it around
+            if p.x < 0.0 {
+                p.x += self.renderer.w as f32;
+            } else if p.x > self.renderer.w as f32 {
+                p.x -= self.renderer.w as f32;
+            }
+
+            // Snow falling onto the top of something may rest there for a moment, starting over from
+            // the top of the screen
+            let sx = (p.x.round() as i32 + self.renderer.w - scroll / 2).rem_euclid(self.renderer.w);
+            let row = p.y.round() as i32;
+            if let Some(top) = self.surfaces.top(sx).filter(|&top| !rain && before < top && row >= top) {
+                let x = scroll + sx * self.camera.zoom;
+                if self.settled.land(x, top - 1, p.color(), p.glyph(), self.time) {
+                    respawn_flake(p);
+                    continue;
+                }
+            }
+
+            // If the snow particle reached the ground below it, reset it
+            if row >= self.renderer.h - 1 - self.world.tiles.tile(scroll + sx * self.camera.zoom).height {
+                respawn_flake(p);
+            }
+        }
+    }
+
+    /// Draw the falling snow particles in front of the scene or behind it
+    fn draw_snow(&mut self, front: bool) {
+        let rain = self.world.weather.kind == Precipitation::Rain;
+        let count = self.active_snow();
+        let snow = std::mem::take(&mut self.world.snow.particles);
+        for p in snow.iter().take(count).filter(|p| p.front == front) {
+            let pixel = if rain { Pixel { color: weather::COLOR_RAIN, char: weather::RAIN_CHAR, dim: false } } else { Pixel { color: p.color(), char: p.glyph(), dim: false } };
+            self.renderer.put(self.snow_column(p.x), p.y.round() as i32, pixel);
+        }
+        self.world.snow.particles = snow;
+    }
+
+    /// Change the snow density multiplier, adding new particles or removing the extra ones
+    fn tune_snow_density(&mut self, delta: f32, cn: &Consts) {
+        self.set_snow_density(self.snow_density + delta, cn);
+        let msg = format!("Snow density: x{:.2}", self.snow_density);
+        self.say(&msg);
+    }
+
+    /// Set the snow density multiplier. The flakes are pooled, so lowering the density leaves the extra
+    /// ones resting in the pool, and only raising it past the flakes made so far makes more.
+    fn set_snow_density(&mut self, density: f32, cn: &Consts) {
+        let (min, max) = SNOW_TUNING_RANGE;
+        self.snow_density = density.clamp(min, max);
+        self.grow_snow(self.snow_density, cn);
+    }
+
+    /// Make more flakes for the pool if a density calls for more than were made so far
+    fn grow_snow(&mut self, density: f32, cn: &Consts) {
+        let count = snow_count(self.renderer.w, self.renderer.h, density);
+        let flakes_now = self.world.snow.particles.len();
+        if count > flakes_now {
+            let more = create_snow(self.renderer.w, self.renderer.h, count - flakes_now, cn);
+            self.world.snow.particles.extend(more);
+        }
+    }
+
+    /// Load the renderer for a stress test reported to a file, with a crowd of cats and ten times the
+    /// snow, past what the density setting allows
+    fn start_stress(&mut self, cn: &Consts, report: PathBuf) {
+        self.world.weather = Weather::default();
+        self.snow_enabled = true;
+        self.snow_density = stress::PARTICLES;
+        self.grow_snow(self.snow_density, cn);
+        self.stress = Some(Stress::new(report, self.world.x, self.now_ms()));
+    }
+
+    /// Change the weather, with a snow density multiplier
+    fn set_weather(&mut self, cn: &Consts, weather: Weather, density: f32) {
+        self.world.weather = weather;
+        self.snow_enabled = true;
+        self.set_snow_density(density, cn);
+    }
+
+    /// Wake the cat up at a time of day after it slept until then
+    fn wake(&mut self, hours: f32) {
+        self.world.clock.set(hours);
+        self.world.pose = Pose::Stand;
+    }
+
+    /// Play a cutscene, remembering that it was watched
+    fn play(&mut self, script: &Script) {
+        match cutscene::parse(script.text) {
+            Ok(directives) => self.cutscene = Some(Cutscene::new(directives)),
+            Err(e) => log::warn!("Failed to parse the {} cutscene: {}", script.name, e),
+        }
+        if self.save.seen.insert(script.name.to_string()) {
+            self.write_save();
+        }
+    }
+
+    /// End the cutscene playing right away, leaving the world as it would be after it
+    fn skip_cutscene(&mut self, cn: &Consts) {
+        if let Some(c) = self.cutscene.take() {
+            c.skip(self, cn);
+        }
+    }
+
+    /// World x of an NPC, who is at a share of the screen width unless walked away in a cutscene or
+    /// dodging snowballs
+    fn npc_x(&self, i: usize) -> i32 {
+        match &self.world.fight {
+            Some(f) if f.npc == i => f.npc_x as i32,
+            _ => (self.renderer.w as f32 * self.npc_vw[i] + self.npc_dx[i]) as i32,
+        }
+    }
+
+    /// What an NPC is doing by its schedule, or None while it is still walking there
+    fn npc_activity(&self, i: usize) -> Option<npc::Activity> {
+        let slot = npc::NPCS[i].slot(self.world.clock.hours());
+        (self.npc_vw[i] == slot.x_vw).then_some(slot.activity)
+    }
+
+    fn npc_asleep(&self, i: usize) -> bool {
+        self.npc_activity(i) == Some(npc::Activity::Sleep)
+    }
+
+    /// Walk the NPCs to where their schedules have them at this time of day, except the one the cat
+    /// is talking to
+    fn update_npcs(&mut self, dt: f32) {
+        let step = npc::WALK_SPEED * dt / self.renderer.w as f32;
+        for (i, n) in npc::NPCS.iter().enumerate() {
+            if matches!(self.state, State::Talking { npc, .. } if npc == i) { continue; }
+            let to = n.slot(self.world.clock.hours()).x_vw;
+            let vw = self.npc_vw[i];
+            self.npc_vw[i] = if (to - vw).abs() <= step { to } else { vw + step * (to - vw).signum() };
+        }
+    }
+
+    /// Change the snow fall speed multiplier
+    fn tune_snow_speed(&mut self, delta: f32) {
+        let (min, max) = SNOW_TUNING_RANGE;
+        self.snow_speed = (self.snow_speed + delta).clamp(min, max);
+        let msg = format!("Snow speed: x{:.2}", self.snow_speed);
+        self.say(&msg);
+    }
+
+    /// Run time slower or faster by some steps through the time scales
+    fn scale_time(&mut self, steps: i32) {
+        let i = TIME_SCALES.iter().position(|&s| s == self.time_scale).unwrap_or(2) as i32;
+        self.time_scale = TIME_SCALES[(i + steps).clamp(0, TIME_SCALES.len() as i32 - 1) as usize];
+        let msg = format!("Time: x{}", self.time_scale);
+        self.say(&msg);
+    }
+
+    /// Move the cat by some cells, stopping in front of solid tiles like the ends of the world
+    fn move_cat(&mut self, amount: i32) {
+        if amount != 0 {
+            self.world.sitting = false;
+            self.world.pose = Pose::Stand;
+        }
+        let dir = amount.signum();
+        for _ in 0..amount.abs() {
+            if self.world.tiles.tile(self.world.x + dir).solid { break; }
+            self.world.x += dir;
+        }
+    }
+
+    /// Walk the cat by some cells, which also pushes it to keep sliding on ice
+    fn walk(&mut self, cat: &AsciiArt, amount: i32) {
+        if amount == 0 { return; }
+        self.move_cat(amount);
+
+        // On ice, every step also pushes the cat to keep sliding, which surprises it at first. On a
+        // frozen pond it skates on purpose, gliding faster and further.
+        if self.skating(cat) {
+            self.world.slide_v = (self.world.slide_v + amount as f32 * pond::PUSH).clamp(-pond::MAX_SPEED, pond::MAX_SPEED);
+        } else if self.on_ice(cat) {
+            if self.world.slide_v == 0.0 {
+                let time = self.time;
+                self.emotions.surprise(time);
+            }
+            self.world.slide_v = (self.world.slide_v + amount as f32 * ICE_PUSH).clamp(-ICE_MAX_SPEED, ICE_MAX_SPEED);
+        }
+
+        if self.state == State::Welcome {
+            self.state = State::Exploring;
+        }
+    }
+
+    /// Keep walking in the direction of the key being held
+    fn update_walk(&mut self, cat: &AsciiArt, dt: f32) {
+        let cells = self.world.movement.update(dt);
+        self.walk(cat, cells);
+    }
+
+    /// Put the cat at a world x, or the free tile closest to it inside the ends of the world, returning
+    /// where it landed or None when there is no free tile
+    fn teleport(&mut self, x: i32) -> Option<i32> {
+        let end = (self.world.tiles.w() as f32 * X_BOUND_VW) as i32;
+        let x = x.clamp(0, end);
+        let free = (0..=end).flat_map(|d| [x - d, x + d]).find(|&x| !self.world.tiles.tile(x).solid)?;
+        self.world.x = free;
+        self.world.move_to = None;
+        self.world.slide_v = 0.0;
+        Some(free)
+    }
+
+    /// Start walking the cat to a world x on its own, returning false when it can't get there
+    fn go_to(&mut self, x: i32) -> bool {
+        self.world.move_to = MoveTo::new(self.world.x, x, |x| self.world.tiles.tile(x).height, |x| self.world.tiles.tile(x).solid);
+        self.world.move_to.is_some()
+    }
+
+    /// Walk the cat along its path, stepping carefully so it doesn't slide on ice
+    fn update_move_to(&mut self, dt: f32) {
+        let Some(m) = &mut self.world.move_to else { return; };
+        let cells = m.update(self.world.x, dt, self.world.movement.pace());
+        let done = m.done();
+        self.move_cat(cells);
+        if done { self.world.move_to = None; }
+    }
+
+    /// Whether the cat is standing on an ice patch or a frozen pond
+    fn on_ice(&self, cat: &AsciiArt) -> bool {
+        matches!(self.world.tiles.tile(self.world.x + cat.w / 2).ground, Ground::Ice | Ground::Pond)
+    }
+
+    /// Whether the cat is skating on a frozen pond
+    fn skating(&self, cat: &AsciiArt) -> bool {
+        self.world.tiles.tile(self.world.x + cat.w / 2).ground == Ground::Pond
+    }
+
+    /// Keep the cat sliding on ice after a keypress, slowing down with the low ice friction
+    fn update_slide(&mut self, cat: &AsciiArt, dt: f32) {
+        if self.world.slide_v == 0.0 { return; }
+
+        // Accumulate the slid distance and move the cat by whole pixels
+        self.world.slide_acc += self.world.slide_v * dt;
+        let steps = self.world.slide_acc.trunc() as i32;
+        if steps != 0 {
+            self.world.slide_acc -= steps as f32;
+            let last_x = self.world.x;
+            self.move_cat(steps);
+
+            // Stop when bumping into the world edge
+            if self.world.x == last_x { self.world.slide_v = 0.0; }
+        }
+
+        // Apply friction, and stop sliding completely once the cat leaves the ice or is slow enough
+        let friction = if self.skating(cat) { pond::FRICTION } else { ICE_FRICTION };
+        self.world.slide_v *= friction.powf(dt);
+        if !self.on_ice(cat) || self.world.slide_v.abs() < 1.0 {
+            self.world.slide_v = 0.0;
+            self.world.slide_acc = 0.0;
+        }
+    }
+
+    /// World x of the left edge of the screen, following the cat unless the camera is panned around
+    /// in photo mode
+    fn get_scroll(&self) -> i32 {
+        match &self.ui.photo {
+            Some(photo) => photo.scroll,
+            None => self.camera.scroll(self.world.draw_x),
+        }
+    }
+
+    /// Range of world x on screen, with a margin on both sides so that particles drifting in from
+    /// just off screen keep moving
+    fn world_window(&self) -> (f32, f32) {
+        let scroll = self.get_scroll() as f32;
+        (scroll - CULL_MARGIN, scroll + (self.renderer.w * self.camera.zoom) as f32 + CULL_MARGIN)
+    }
+
+    /// Screen column of a world x
+    fn screen_x(&self, x: i32) -> i32 {
+        self.camera.screen_x(x, self.get_scroll())
+    }
+
+    /// Ease the position the cat is drawn at towards where it is, so that it glides between cells
+    fn update_draw_x(&mut self, dt: f32) {
+        let dist = self.world.x as f32 - self.world.draw_x;
+        self.world.draw_x += dist * (1.0 - (-GLIDE_RATE * dt).exp());
+        // Snap when close enough, or land after jumps that are too far to glide
+        if dist.abs() > GLIDE_MAX {
+            self.shake(SHAKE_JUMP);
+        }
+        if dist.abs() < GLIDE_SNAP || dist.abs() > GLIDE_MAX {
+            self.world.draw_x = self.world.x as f32;
+        }
+    }
+
+    /// Screen y of the ground surface at world x
+    fn ground_y(&self, x: i32) -> i32 {
+        self.renderer.h - 1 - self.world.tiles.tile(x).height
+    }
+
+    /// Shake the screen with an amplitude in cells for a duration in seconds
+    fn shake(&mut self, (amplitude, duration): (f32, f32)) {
+        if self.reduce_motion { return; }
+        let time = self.time;
+        self.camera.shake.start(amplitude, duration, time);
+    }
+
+    /// Time that blinking and shimmering effects follow, which stands still with reduced motion
+    fn flicker_time(&self) -> f32 {
+        if self.reduce_motion { 0.0 } else { self.time }
+    }
+
+    /// Unix time in milliseconds. Recordings and their replays count it by frames from when the
+    /// recording started instead, so that whatever depends on it happens at the same frames again.
+    fn now_ms(&self) -> u64 {
+        let started = self.playback.as_ref().map(|p| p.started).or(self.recorder.as_ref().map(|r| r.started));
+        match started {
+            Some(s) => s + self.frame * FRAME_TIME.as_millis() as u64,
+            None => presence::now_ms(),
+        }
+    }
+
+    /// Make the cat say something for a few seconds
+    fn say(&mut self, msg: &str) {
+        self.message = Some((msg.to_string(), self.now_ms()));
+    }
+
+    /// End the conversation with an NPC, remembering that the cat has met them
+    fn end_talking(&mut self, npc: usize) {
+        self.state = State::Exploring;
+        if self.save.met.insert(npc::NPCS[npc].name.to_string()) {
+            self.write_save();
+        }
+    }
+
+    /// Autosave every few seconds while playing
+    fn autosave(&mut self) {
+        if !self.autosave || self.time - self.last_autosave < autosave::INTERVAL { return; }
+        self.last_autosave = self.time;
+        // Stop trying after the first failure, which would only fail again
+        if let Err(e) = Autosave::take(self).write() {
+            log::warn!("Failed to autosave: {}", e);
+            self.ui.toasts.push(ToastKind::Error, "Autosave is off, it couldn't be written");
+            self.autosave = false;
+        }
+    }
+
+    /// Remember that the signpost or bulletin board of an entity was read
+    fn mark_read(&mut self, i: usize) {
+        if self.save.read.insert(self.world.map.entities[i].key()) {
+            self.write_save();
+        }
+    }
+
+    /// Start a visit, telling a returning visitor when their garden grew since the last one
+    fn visit(&mut self) {
+        let now = self.now_ms() / 1000;
+        if garden::grew(&self.save, self.save.visited, now) {
+            self.ui.toasts.push(ToastKind::Info, "The garden grew while you were away");
+        }
+        self.save.visited = now;
+        self.write_save();
+    }
+
+    fn write_save(&mut self) {
+        if !self.persist { return; }
+        if let Err(e) = self.save.write() {
+            log::warn!("Failed to write the save file: {}", e);
+            self.ui.toasts.push(ToastKind::Error, "Couldn't save the progress");
+        }
+    }
+
+    /// Record a score on the shared leaderboard in server mode
+    fn record_score(&mut self, board: Board, score: u32) {
+        let Some(lb) = &mut self.leaderboard else { return; };
+        if let Err(e) = lb.record(board, &self.player, score) {
+            log::warn!("Failed to record the score on the leaderboard: {}", e);
+            self.ui.toasts.push(ToastKind::Error, "Couldn't record the score");
+        }
+    }
+
+    /// Cool the cat down at night and in blizzards in cozy mode, warm it up by the fire or the house,
+    /// and slow it down while it is cold
+    fn update_warmth(&mut self, cn: &Consts, dt: f32) {
+        if !self.cozy {
+            self.world.movement.pace_share = 1.0;
+            return;
+        }
+        let (night, blizzard, heated) = (self.world.clock.is_night(), self.blizzard(), by_heat(self, cn));
+        if self.warmth.update(dt, night, blizzard, heated) {
+            self.say("Brr... I'm cold.\nI should warm up by a fire.");
+        }
+        self.world.movement.pace_share = self.warmth.pace();
+    }
+
+    /// Start a snowball fight with an NPC where it stands
+    fn start_fight(&mut self, npc: usize) {
+        self.world.fight = Some(Fight::new(npc, self.npc_x(npc), self.world.x));
+    }
+
+    /// Where the cat and the NPC it fights stand
+    fn fight_bodies(&self, cn: &Consts, npc: usize) -> (snowball::Body, snowball::Body) {
+        let (cat, art) = (&cn.asc_cat, &cn.npc_arts[npc]);
+        let (x, nx) = (self.world.x, self.npc_x(npc));
+        (snowball::Body { x, y: self.rest_y(cat, x), w: cat.w, h: cat.h },
+         snowball::Body { x: nx, y: self.rest_y(art, nx), w: art.w, h: art.h })
+    }
+
+    /// Throw a snowball at the NPC the cat fights
+    fn throw_snowball(&mut self, cn: &Consts) {
+        let Some(npc) = self.world.fight.as_ref().map(|f| f.npc) else { return; };
+        let (cat, target) = self.fight_bodies(cn, npc);
+        if let Some(f) = &mut self.world.fight { f.throw_at_npc(cat, target); }
+    }
+
+    /// Move the snowballs, burst the ones hitting someone, and end the fight once someone won
+    fn update_fight(&mut self, cn: &Consts, dt: f32) {
+        let Some(npc) = self.world.fight.as_ref().map(|f| f.npc) else { return; };
+        let (cat, target) = self.fight_bodies(cn, npc);
+        let h = self.renderer.h;
+        let Some(fight) = &mut self.world.fight else { return; };
+        let hits = fight.update(dt, cat, target, h);
+        let winner = fight.winner();
+        for (x, y) in hits {
+            let splat = Emitter::new(x, y, Velocity::Radial { speed: SPLAT_SPEED }, SPLAT_LIFE, SPLAT_COLORS, SPLAT_GLYPHS);
+            self.world.effects.burst(&splat, SPLAT_PARTICLES);
+        }
+        if let Some(won) = winner {
+            self.end_fight(won);
+        }
+    }
+
+    /// End the snowball fight, counting a win for the leaderboard, and let the NPC walk back from where
+    /// it dodged to
+    fn end_fight(&mut self, won: bool) {
+        let Some(fight) = self.world.fight.take() else { return; };
+        let name = npc::NPCS[fight.npc].name;
+        self.npc_vw[fight.npc] = (fight.npc_x - self.npc_dx[fight.npc]) / self.renderer.w as f32;
+        if won {
+            self.stats.snowball_wins += 1;
+            let wins = self.stats.snowball_wins;
+            self.record_score(Board::Snowball, wins);
+            self.say(&format!("I won {} to {}!\nSorry, {}.", fight.score.0, fight.score.1, name));
+        } else {
+            let time = self.time;
+            self.emotions.surprise(time);
+            self.say(&format!("{} won {} to {}.\nRematch soon!", name, fight.score.1, fight.score.0));
+        }
+    }
+
+    /// Start a downhill sled run
+    fn start_sledding(&mut self) {
+        self.world.sled = Some(Sled::new());
+        self.state = State::Sledding;
+    }
+
+    /// Update the sled run, and go back to exploring when the sled crashed
+    fn update_sled(&mut self, dt: f32) {
+        let Some(sled) = &mut self.world.sled else { return; };
+        if sled.update(dt, self.renderer.w) { return; }
+
+        // Record the score and tell the player about it
+        let dist = sled.distance();
+        self.world.sled = None;
+        self.state = State::Exploring;
+        self.emotions.surprise(self.time);
+        self.shake(SHAKE_CRASH);
+        self.record_score(Board::Sled, dist);
+        if self.stats.record_sled(dist) {
+            self.say(&format!("Whee! {}m,\na new record!", dist));
+        } else {
+            self.say(&format!("Oof! {}m this time.\nBest: {}m", dist, self.stats.sled_best));
+        }
+    }
+
+    /// Screen y at which an ascii art placed at world x rests on the ground, using the highest
+    /// ground point under the non-space characters of its bottom line
+    fn rest_y(&self, art: &AsciiArt, x: i32) -> i32 {
+        let bottom = art.art.lines().last().unwrap_or("");
+        let start = bottom.chars().position(|c| c != ' ').unwrap_or(0) as i32;
+        let end = bottom.trim_end().chars().count().max(1) as i32;
+        let ground = (x + start..x + end).map(|x| self.ground_y(x)).min().unwrap_or(self.renderer.h - 1);
+        ground - art.h + 1
+    }
+
+    /// Draw an ascii art at world x and screen y
+    fn print_ascii(&mut self, art: &AsciiArt, x: i32, y: i32, color: &'static str) {
+        let art = self.camera.zoomed(art);
+        self.renderer.put_ascii(&art, self.screen_x(x), y, color);
+    }
+
+    /// Draw an ascii art at a fractional world x and screen y. Halfway between two cells, the art is
+    /// drawn in the cell it is leaving, with thin block characters on its leading edge.
+    fn print_ascii_smooth(&mut self, art: &AsciiArt, x: f32, y: i32, color: &'static str, right: bool) {
+        let cell = self.smooth_cell(x, right);
+        self.print_ascii(art, cell, y, color);
+        if !self.halfway(x) { return; }
+
+        // The leading edge reaches into the next column
+        let scroll = self.get_scroll();
+        for (i, line) in art.art.lines().enumerate() {
+            let (edge, char) = if right {
+                (line.trim_end().chars().count() as i32, '▏')
+            } else {
+                (line.chars().position(|c| c != ' ').map_or(0, |p| p as i32) - 1, '▕')
+            };
+            if line.trim().is_empty() { continue; }
+            self.renderer.put(cell + edge - scroll, y + i as i32, Pixel { color, char, dim: false });
+        }
+    }
+
+    /// Whether a fractional world x is drawn halfway between two cells
+    fn halfway(&self, x: f32) -> bool {
+        (0.25..0.75).contains(&(x - x.floor())) && self.camera.zoom == 1
+    }
+
+    /// World x of the cell an art at a fractional world x is drawn in by print_ascii_smooth. Halfway
+    /// and moving right, the art is in the left cell with its right edge reaching into the next column,
+    /// and moving left, it is in the right cell with its left edge reaching into the previous one.
+    fn smooth_cell(&self, x: f32, right: bool) -> i32 {
+        let cell = x.floor() as i32;
+        if self.halfway(x) { if right { cell } else { cell + 1 } }
+        else { cell + (x - x.floor() >= 0.75) as i32 }
+    }
+
+    /// Add a speech bubble above a speaker at world x and screen y, keeping it on screen. The bubble
+    /// starts dx to the right of the speaker, flips over to its left when it doesn't fit, and stays
+    /// below the HUD. It is drawn with the other bubbles of the frame by draw_bubbles.
+    fn print_bubble(&mut self, bubble: AsciiArt, speaker_x: i32, speaker_w: i32, speaker_y: i32, dx: i32, color: &'static str) {
+        let (sx, speaker_w) = (self.screen_x(speaker_x), speaker_w / self.camera.zoom);
+        let mut x = sx + dx;
+        if x + bubble.w > self.renderer.w {
+            x = sx + speaker_w - dx - bubble.w;
+        }
+        let x = x.clamp(0, (self.renderer.w - bubble.w).max(0));
+        let y = (speaker_y - bubble.h).max(HUD_HEIGHT);
+        let rect = Rect { x, y, w: bubble.w, h: bubble.h };
+        self.bubbles.push((bubble, rect, color));
+    }
+
+    /// Lay out the bubbles of the frame so that they don't overlap each other or the obstacles given in
+    /// screen space, then draw them on top of everything else
+    fn draw_bubbles(&mut self, obstacles: &[Rect]) {
+        let bubbles = std::mem::take(&mut self.bubbles);
+        let mut rects: Vec<Rect> = bubbles.iter().map(|(_, r, _)| *r).collect();
+        layout::arrange(&mut rects, obstacles, self.renderer.w, self.renderer.h, HUD_HEIGHT);
+        for ((bubble, _, color), rect) in bubbles.iter().zip(rects) {
+            self.renderer.put_ascii(bubble, rect.x, rect.y, color);
+            self.surfaces.mark_row(rect.x, rect.y, rect.w);
+        }
+    }
+
+    fn draw_grass(&mut self, cn: &Consts) {
+        let scroll = self.get_scroll();
+
+        // The ground is in the dark at night
+        let dim = self.world.clock.is_night();
+
+        for x in 0..self.renderer.w {
+            // Ice patches replace the grass, and snow that built up covers some of it
+            let tile = self.world.tiles.tile(scroll + x * self.camera.zoom);
+            let pixel = match tile.ground {
+                Ground::Ice => Pixel { color: cn.colors.ice, char: ICE_CHAR, dim },
+                Ground::Pond => Pixel { color: cn.colors.ice, char: pond::CHAR, dim },
+                Ground::Grass if tile.cover < self.shared.snow_cover => Pixel { color: cn.colors.snow[0], char: SNOW_COVER_CHAR, dim },
+                Ground::Grass => {
+                    let chars = &cn.colors.grass_chars;
+                    Pixel { color: cn.colors.grass, char: chars[tile.decoration as usize % chars.len()], dim }
+                }
+            };
+
+            // Place the grass on the ground surface, following the hills
+            self.renderer.put(x, self.renderer.h - 1 - tile.height, pixel);
+        }
+    }
+
+}
+
+// Seconds between reloads of the leaderboard while the cat is looking at it
+const LEADERBOARD_RELOAD: f32 = 5.0;
+
+// Address on the website signpost
+const WEBSITE: &str = "hydev.org";
+
+// Mark above signposts and bulletin boards never read before
+const UNREAD: &str = "!";
+const X_BOUND_VW: f32 = 2.3;
+
+fn draw_ascii_frame(mt: &mut Mutes, cn: &Consts) {
+    let wf = mt.renderer.w as f32;
+    let xf = mt.world.x as f32;
+    mt.said.clear();
+
+    // Draw the entities of the map, with the lights of seasonal events on the trees. The leaderboard
+    // is only there in server mode, the website signpost has a clickable address, and the text of the
+    // signpost the cat is reading is shown above it. Signposts and bulletin boards never read before
+    // are marked.
+    let at = near_entity(mt, cn);
+    mt.ui.reading = mt.ui.reading.filter(|&i| at == Some(i));
+    let lights = cn.events.iter().find(|e| !e.tree_lights.is_empty()).map_or(&[][..], |e| e.tree_lights);
+    for (i, e) in mt.world.map.entities.clone().into_iter().enumerate() {
+        if e.role == Role::Leaderboard && mt.leaderboard.is_none() { continue; }
+        let art = cn.art(e.asset);
+        let x = e.world_x(mt.renderer.w);
+        let y = mt.rest_y(art, x) - e.y;
+        if e.y == 0 { shadow::draw(mt, x, art.w, cn.colors.shadow); }
+        mt.print_ascii(art, x, y, asset_color(cn, e.asset));
+        if matches!(e.asset, Asset::Tree | Asset::House) {
+            let (art, sx) = (mt.camera.zoomed(art), mt.screen_x(x));
+            mt.surfaces.mark(&art, sx, y);
+        }
+        if e.asset == Asset::Tree {
+            let art = mt.camera.zoomed(art);
+            events::draw_lights(mt, &art, mt.screen_x(x), y, lights, mt.flicker_time());
+        }
+        if matches!(e.role, Role::Signpost | Role::Bulletin) && !mt.save.read.contains(&e.key()) {
+            mt.renderer.put_str(mt.screen_x(x + art.w / 2), y - 1, UNREAD, cn.colors.announcement);
+        }
+        // The address doesn't fit on the sign when zoomed out
+        if e.role == Role::Website && mt.camera.zoom == 1 {
+            mt.renderer.put_link(mt.screen_x(x + 2), y + 1, WEBSITE, &format!("https://{}", WEBSITE), cn.colors.house);
+        }
+        if let Some(text) = e.text.as_ref().filter(|_| mt.ui.reading == Some(i)) {
+            mt.said.push(text.clone());
+            let text: Vec<String> = text.lines().map(|l| cowsay::wrap(l, CHAT_WRAP * 2)).collect();
+            let bubble = gen_bubble_ascii(&text.join("\n"), mt.bubble_style, BubbleKind::Say);
+            mt.print_bubble(bubble, x, art.w, y, 0, asset_color(cn, e.asset));
+        }
+    }
+
+    // Draw seasonal decorations next to the first tree or the house
+    for (d, art) in &cn.decorations {
+        let anchor = match d.anchor {
+            Anchor::Tree => mt.world.map.entities.iter().find(|e| e.asset == Asset::Tree).map(|e| (e.world_x(mt.renderer.w), e.asset)),
+            Anchor::House => mt.world.map.find(Role::House).map(|e| (e.world_x(mt.renderer.w), e.asset)),
+        };
+        let Some((x, asset)) = anchor else { continue; };
+        let x = x + cn.art(asset).w / 2 + d.dx;
+        mt.print_ascii(art, x, mt.rest_y(art, x), d.color);
+    }
+
+    // Draw the art about to be stamped in the editor
+    if let Some(e) = &mt.editor {
+        let art = cn.art(e.asset());
+        mt.print_ascii(art, mt.world.x, mt.rest_y(art, mt.world.x), cn.colors.preview);
+    }
+
+    // Draw the snowmen built by visitors
+    for x in mt.shared.snowmen.clone() {
+        mt.print_ascii(&cn.asc_snowman, x, mt.rest_y(&cn.asc_snowman, x), cn.colors.snow[0]);
+    }
+
+    // Draw the sled on top of the hill
+    let sled_start = (wf * sled::SLED_X_VW) as i32;
+    mt.print_ascii(&cn.asc_sled, sled_start, mt.rest_y(&cn.asc_sled, sled_start), cn.colors.house);
+
+    // Draw title at the center of the screen, with a rainbow shimmering across it
+    let shimmer = Gradient { shift: -mt.flicker_time() * TITLE_SHIMMER_SPEED, ..Gradient::new(&cn.colors.title, Direction::Columns) };
+    let (title, title_x) = (mt.camera.zoomed(&cn.asc_title), mt.screen_x((mt.renderer.w - cn.asc_title.w) / 2));
+    mt.renderer.put_ascii_gradient(&title, title_x, (mt.renderer.h - cn.asc_title.h) / 2, shimmer);
+    let soon = Gradient::new(&cn.colors.soon, Direction::Lines);
+    let (soon_art, soon_x) = (mt.camera.zoomed(&cn.asc_soon), mt.screen_x((1.7 * wf) as i32));
+    mt.renderer.put_ascii_gradient(&soon_art, soon_x, (mt.renderer.h - cn.asc_soon.h) / 2, soon);
+
+    // Draw the NPCs, with what they are busy with by their schedules next to them
+    for (i, n) in npc::NPCS.iter().enumerate() {
+        let art = &cn.npc_arts[i];
+        let x = mt.npc_x(i);
+        let y = mt.rest_y(art, x);
+        shadow::draw(mt, x, art.w, cn.colors.shadow);
+        let hit = mt.world.fight.as_ref().is_some_and(|f| f.npc == i && f.flashing(false));
+        mt.print_ascii(art, x, y, if hit { snowball::COLOR_FLASH } else { n.color });
+        if let Some(label) = mt.npc_activity(i).and_then(npc::Activity::label) {
+            mt.renderer.put_str(mt.screen_x(x + art.w), y - 1, label, n.color);
+        }
+
+        // Show what the NPC says in a cutscene
+        if let Some((Actor::Npc(npc), text)) = mt.cutscene.as_ref().and_then(|c| c.bubble.clone()) {
+            if npc == i {
+                let bubble = gen_bubble_ascii(&text, mt.bubble_style, BubbleKind::Say);
+                mt.print_bubble(bubble, x, art.w, y, 0, n.color);
+            }
+        }
+
+        // Show the current line of the conversation above the NPC
+        if let State::Talking { npc, node } = mt.state {
+            if npc != i { continue; }
+            let mut text = cn.line(i, node).to_string();
+            let node = &n.nodes[node];
+            for (j, c) in node.choices.iter().enumerate() {
+                text.push_str(&format!("\n[{}] {}", j + 1, c.label));
+            }
+            let bubble = gen_bubble_ascii(&text, mt.bubble_style, node.kind);
+            mt.print_bubble(bubble, x, art.w, y, 0, node.kind.color(n.color));
+        }
+    }
+
+    // Draw the garden plots, grown by real days since they were planted
+    let plots = mt.world.map.plots(mt.world.tiles.w());
+    let now = mt.now_ms();
+    garden::draw(mt, &plots, now / 1000, cn.colors.tree);
+
+    // Draw the fish and the coins lying around on screen
+    let scroll = mt.get_scroll();
+    for (kind, art, color) in [(Pickup::Fish, &cn.asc_fish, cn.colors.fish), (Pickup::Coin, &cn.asc_coin, cn.colors.coin)] {
+        for x in mt.world.inventory.between(kind, scroll, scroll + mt.camera.view_w()) {
+            mt.print_ascii(art, x, mt.rest_y(art, x), color);
+        }
+    }
+
+    // Draw the other visitors' cats with their names and what they said, and the crowd of a stress test
+    let mut others = mt.visitors.as_ref().map(|v| v.others.clone()).unwrap_or_default();
+    others.extend(mt.stress.iter().flat_map(|s| s.crowd.clone()));
+    let mut other_rects = Vec::new();
+    for o in others {
+        let color = presence::PLAYER_COLORS[o.color % presence::PLAYER_COLORS.len()];
+        let x = o.shown_x.round() as i32;
+        let y = mt.rest_y(&cn.asc_cat, x);
+        mt.print_ascii_smooth(&cn.asc_cat, o.shown_x, y, color, o.vx >= 0.0);
+        let name_x = mt.screen_x(x + cn.asc_cat.w / 2) - o.name.chars().count() as i32 / 2;
+        mt.renderer.put_str(name_x, y - 1, &o.name, color);
+        other_rects.push(Rect { x: mt.screen_x(x), y: y - 1, w: cn.asc_cat.w / mt.camera.zoom, h: cn.asc_cat.h + 1 });
+        let said = o.message.filter(|(_, at)| now.saturating_sub(*at) < MESSAGE_DURATION.as_millis() as u64);
+        if let Some((msg, _)) = said {
+            let bubble = gen_bubble_ascii(&msg, mt.bubble_style, BubbleKind::Say);
+            mt.print_bubble(bubble, x, cn.asc_cat.w, y - 1, 5, color);
+        }
+        if let Some((e, at)) = o.emote {
+            let age = now.saturating_sub(at) as f32 / 1000.0;
+            emote::draw(mt, e, age, x, cn.asc_cat.w, y - 1, color);
+        }
+    }
+
+    // Show the host's announcements at the top of the screen
+    let announcement = mt.admin.as_ref().and_then(|a| a.orders.announcement.clone())
+        .filter(|(_, at)| now.saturating_sub(*at) < ANNOUNCEMENT_DURATION.as_millis() as u64);
+    if let Some((msg, _)) = announcement {
+        let bubble = gen_bubble_ascii(&cowsay::wrap(&format!("Host: {}", msg), CHAT_WRAP * 2), mt.bubble_style, BubbleKind::Shout);
+        let x = mt.get_scroll() + (mt.renderer.w - bubble.w) / 2 * mt.camera.zoom;
+        let y = HUD_HEIGHT + bubble.h;
+        mt.print_bubble(bubble, x, 0, y, 0, cn.colors.announcement);
+    }
+
+    // Cracks spread through the ice of a pond where the cat stays in the middle too long
+    pond::draw_cracks(mt, cn.asc_cat.w, cn.colors.ice);
+
+    // Draw the cat standing on the ground, with its face showing how it feels, sitting on the seat of
+    // a bench, or skating on a frozen pond, wearing what it bought in the shop
+    let draw_x = mt.world.draw_x;
+    let seat = bench(mt, cn).filter(|_| mt.world.sitting).map(|(x, art)| mt.rest_y(art, x) + 1);
+    let cat = match mt.world.pose {
+        _ if seat.is_some() => &cn.asc_cat_sitting,
+        Pose::Sit => &cn.asc_cat_sitting,
+        Pose::Loaf => &cn.asc_cat_loaf,
+        Pose::Sleep => &cn.asc_cat_sleeping,
+        Pose::Stand if mt.skating(&cn.asc_cat) => &cn.asc_cat_skating,
+        Pose::Stand => cn.cat_sprites[mt.emotions.current() as usize].frame(mt.time),
+    };
+    let cat_y = seat.map_or_else(|| mt.rest_y(cat, draw_x.round() as i32), |y| y - cat.h);
+    if seat.is_none() { shadow::draw(mt, draw_x.round() as i32, cat.w, cn.colors.shadow); }
+    let hit = mt.world.fight.as_ref().is_some_and(|f| f.flashing(true));
+    let color = if hit { snowball::COLOR_FLASH } else { secrets::cat_color(mt, cn) };
+    let shivering = mt.cozy && mt.warmth.cold() && mt.world.pose != Pose::Sleep && !mt.reduce_motion;
+    let shiver = if shivering && (mt.time * warmth::SHIVER_RATE) as i32 % 2 == 0 { 1.0 } else { 0.0 };
+    let right = mt.world.x as f32 > draw_x;
+    mt.print_ascii_smooth(cat, draw_x + shiver, cat_y, color, right);
+    let cell = mt.smooth_cell(draw_x + shiver, right);
+    shop::draw(mt, cat, cell, cat_y);
+    if mt.world.pose == Pose::Sleep {
+        mt.renderer.put_str(mt.screen_x(draw_x.round() as i32 + cat.w), cat_y - 1, pose::SNORE, cn.colors.cat);
+    }
+    if let Some((e, at)) = mt.ui.emote {
+        let age = now.saturating_sub(at) as f32 / 1000.0;
+        emote::draw(mt, e, age, draw_x.round() as i32, cat.w, cat_y, cn.colors.cat);
+    }
+    if let Some(fight) = mt.world.fight.take() {
+        fight.draw(mt, npc::NPCS[fight.npc].name, cn.colors.cat);
+        mt.world.fight = Some(fight);
+    }
+
+    // A blizzard hides what is far from the cat, then the snow falls in front of the scene, below the
+    // HUD and the bubbles
+    let blizzard = mt.blizzard();
+    if blizzard > 0.0 {
+        let clear = mt.renderer.w as f32;
+        let radius = clear + (blizzard::VISIBILITY - clear) * blizzard;
+        let (cx, cy) = (mt.screen_x(draw_x.round() as i32 + cat.w / 2), cat_y + cat.h / 2);
+        mt.renderer.fog(cx, cy, radius / mt.camera.zoom as f32);
+    }
+    mt.draw_snow(true);
+
+    secrets::draw(mt);
+
+    // Leave out the HUD and the bubbles in photo mode
+    if mt.ui.photo.is_some() {
+        draw_photo_mode(mt, cn);
+        return;
+    }
+
+    // Draw the compass, the warmth meter in cozy mode, the affection meter and the fish and coin counts
+    // in the top right corner
+    let inv = &mt.world.inventory;
+    let hud = format!("{}  {} x{}  {} x{}", mt.affection.hearts(), inventory::FISH_ART, inv.fish, inventory::COIN_ART, inv.coins);
+    let hud = if mt.cozy { format!("{}  {}", mt.warmth.meter(), hud) } else { hud };
+    let compass = mt.compass.hud(mt.world.x + cn.asc_cat.w / 2, &landmarks(mt, cn));
+    let hud = if compass.is_empty() { hud } else { format!("{}  {}", compass, hud) };
+    mt.renderer.put_str(mt.renderer.w - hud.chars().count() as i32 - 1, 0, &hud, cn.colors.cat);
+
+    // Warn of a blizzard about to hit in the middle of the top line
+    if mt.world.blizzard.coming(mt.time) && mt.snow_enabled && mt.world.weather.kind == Precipitation::Snow {
+        mt.renderer.put_str((mt.renderer.w - BLIZZARD_WARNING.chars().count() as i32) / 2, 0, BLIZZARD_WARNING, cn.colors.announcement);
+    }
+    if mt.minimap.shown {
+        draw_minimap(mt, cn, mt.renderer.w - hud.chars().count() as i32 - 3);
+    }
+
+    let chat = |msg: &str, mt: &mut Mutes| {
+        mt.said.push(msg.to_string());
+
+        // Draw the chat bubble
+        let bubble = gen_bubble_ascii(msg, mt.bubble_style, BubbleKind::Say);
+        mt.print_bubble(bubble, mt.world.draw_x.round() as i32, cat.w, cat_y, 5, cn.colors.bubble);
+    };
+
+    // Show the map editor's keys on the bottom line
+    if let Some(e) = &mt.editor {
+        let line = format!(" Editing {}  [tab] {}  [enter] stamp  [x] remove  [i] ice  [t] line  [s] save",
+            e.path.display(), e.asset().name());
+        let line = format!("{:<1$}", line, mt.renderer.w as usize);
+        mt.renderer.put_str(0, mt.renderer.h - 1, &line, cn.colors.preview);
+    }
+
+    let now = mt.now_ms();
+    let message = mt.message.clone().filter(|(_, at)| now.saturating_sub(*at) < MESSAGE_DURATION.as_millis() as u64);
+    let phase = mt.world.clock.phase();
+    let cutscene = mt.cutscene.as_ref().map(|c| c.bubble.clone());
+    if let Some(bubble) = cutscene {
+        // Only the cat's lines of the cutscene show above it
+        if let Some((Actor::Cat, text)) = bubble {
+            chat(&text, mt);
+        }
+    }
+    else if matches!(mt.state, State::Talking { .. }) {
+        // The NPC is talking, while the cat says its last reply
+        if let Some((msg, _)) = message {
+            chat(&msg, mt);
+        }
+    }
+    else if mt.state == State::Credits {
+        draw_credits(mt, cn);
+    }
+    else if mt.state == State::Writing {
+        // Draw the message being typed on the bottom line
+        widget::text_input(mt, "Sign the guestbook: ", &mt.ui.input.clone(), "[enter] sign  [esc] cancel");
+    }
+    else if mt.state == State::Chatting {
+        // Show what is being typed in the cat's bubble as well as on the bottom line
+        widget::text_input(mt, "Say: ", &mt.ui.input.clone(), "[enter] say  [esc] cancel");
+        let text = cowsay::wrap(&format!("{}_", mt.ui.input), CHAT_WRAP);
+        chat(&text, mt);
+    }
+    else if mt.state == State::Emoting {
+        chat(&emote::picker(), mt);
+    }
+    else if mt.state == State::Shopping {
+        // The shop, under what the cat said about the last item picked
+        let menu = shop::menu(&mt.save, mt.world.inventory.coins);
+        let text = match message { Some((msg, _)) => format!("{}\n\n{}", msg, menu), None => menu };
+        chat(&text, mt);
+    }
+    else if mt.state == State::Labeling {
+        widget::text_input(mt, "Line: ", &mt.ui.input.clone(), "[enter] set  [esc] cancel");
+    }
+    else if mt.state == State::Commanding {
+        widget::text_input(mt, ":", &mt.ui.input.clone(), "[enter] run  [esc] cancel");
+        if let Some((msg, _)) = message {
+            chat(&msg, mt);
+        }
+    }
+    else if let Some((msg, _)) = message {
+        chat(&msg, mt);
+    }
+    else if mt.state == State::Welcome {
+        let greeting = cn.events.iter().find_map(|e| e.greeting);
+        let line = greeting.unwrap_or_else(|| mt.phrases.line(Place::Welcome, phase));
+        chat(line, mt);
+    }
+    else {
+        // Check position, if the cat is at something with a line of dialogue in the map...
+        if mt.world.pose == Pose::Sleep {
+            // Sleeping cats don't talk
+        }
+        else if mt.world.sitting {
+            let line = mt.phrases.line(Place::Bench, phase);
+            chat(line, mt);
+        }
+
+        // Else: if the cat is at a signpost it isn't reading yet...
+        else if mt.ui.reading.is_none() && near_entity(mt, cn).is_some_and(|i| mt.world.map.entities[i].role == Role::Signpost) {
+            chat("A signpost!\nPress [e] to read it.", mt);
+        }
+
+        // Else: if the cat is at a bulletin board...
+        else if near_entity(mt, cn).is_some_and(|i| mt.world.map.entities[i].role == Role::Bulletin) {
+            chat("A bulletin board!\nPress [e] to read it.", mt);
+        }
+
+        // Else: if the cat is at something with a line of dialogue in the map...
+        else if let Some(line) = near_entity(mt, cn).and_then(|i| mt.world.map.line(&mt.world.map.entities[i])).map(str::to_string) {
+            chat(&line, mt);
+        }
+
+        // Else: if the cat is at the leaderboard...
+        else if let Some(lb) = mt.leaderboard.as_ref().filter(|_| near(mt, cn, Role::Leaderboard)) {
+            let mut msg = String::new();
+            for (board, title, unit) in [(Board::Sled, "Sledding", "m"), (Board::Fish, "Fishing", " fish"), (Board::Snowball, "Snowball fights", " wins")] {
+                msg.push_str(title);
+                let top = lb.top(board);
+                if top.is_empty() { msg.push_str("\n  No scores yet"); }
+                for (i, (name, score)) in top.iter().enumerate() {
+                    msg.push_str(&format!("\n  {}. {} - {}{}", i + 1, name, score, unit));
+                }
+                msg.push('\n');
+            }
+            chat(msg.trim_end(), mt);
+        }
+
+        // Else: if the cat is near the house...
+        else if near(mt, cn, Role::House) {
+            let place = if npc::NPCS.iter().all(|n| mt.save.met.contains(n.name)) { Place::HouseAllMet } else { Place::House };
+            let line = mt.phrases.line(place, phase);
+            chat(line, mt);
+        }
+
+        // Else: If the cat is at the edge...
+        else if mt.world.x == 0 {
+            let line = mt.phrases.line(Place::Cliff, phase);
+            chat(line, mt);
+        }
+
+        // Cat is in the middle of nowhere
+        if (1.5 * wf..1.8 * wf).contains(&xf) {
+            let line = mt.phrases.line(Place::Soon, phase);
+            chat(line, mt);
+        }
+
+        // Cat found the sled
+        if near_sled(mt, cn) {
+            let line = mt.phrases.line(Place::Sled, phase);
+            chat(line, mt);
+        }
+
+        // Cat found the website sign
+        if near(mt, cn, Role::Website) {
+            let msg = if mt.renderer.hyperlinks { "My human's website!\nClick the sign to visit." } else { "My human's website!\nVisit hydev.org <3" };
+            chat(msg, mt);
+        }
+
+        // Cat found the guestbook
+        if near_sign(mt, cn) {
+            let recent = mt.guestbook.recent();
+            let msg = if recent.is_empty() { "Nobody signed the guestbook\nyet. Press [e] to be first!".to_string() }
+                else { format!("{}\nPress [e] to sign it too.", recent.join("\n")) };
+            chat(&msg, mt);
+        }
+
+        // Cat found a friend, unless they are busy throwing snowballs at each other
+        if let Some(i) = near_npc(mt, cn).filter(|_| mt.world.fight.is_none()) {
+            let name = npc::NPCS[i].name;
+            let msg = if mt.npc_asleep(i) { format!("{} is asleep.\nBetter not wake them.", name) }
+                else { format!("It's {}! Press [e]\nto say hi.", name) };
+            chat(&msg, mt);
+        }
+        // if (wf * X_BOUND_VW - 3.0..wf * X_BOUND_VW + 3.0).contains(&xf) {
+        //     chat("", mt);
+        // }
+    }
+
+    // Lay out the bubbles so that they don't cover each other, the title or the cats
+    let z = mt.camera.zoom;
+    let cat_rect = Rect { x: mt.screen_x(mt.world.draw_x.round() as i32), y: cat_y, w: cat.w / z, h: cat.h };
+    let title = Rect { x: title_x, y: (mt.renderer.h - cn.asc_title.h) / 2, w: cn.asc_title.w / z, h: cn.asc_title.h };
+    let soon = Rect { x: soon_x, y: (mt.renderer.h - cn.asc_soon.h) / 2, w: cn.asc_soon.w / z, h: cn.asc_soon.h };
+    other_rects.extend([title, soon, cat_rect]);
+    mt.draw_bubbles(&other_rects);
+}
+
+/// Save the scene drawn so far when a photo was asked for, leaving out the bubbles, and show the keys
+/// of photo mode on the bottom line
+fn draw_photo_mode(mt: &mut Mutes, cn: &Consts) {
+    mt.bubbles.clear();
+    let Some(photo) = &mut mt.ui.photo else { return; };
+    if std::mem::take(&mut photo.shoot) {
+        match Photo::save(&mt.renderer.plain()) {
+            Ok(path) => {
+                log::info!("Saved a photo to {}", path.display());
+                mt.ui.toasts.push(ToastKind::Info, format!("Saved {}", path.display()));
+            }
+            Err(e) => {
+                log::warn!("Failed to save the photo: {}", e);
+                mt.ui.toasts.push(ToastKind::Error, "Couldn't save the photo");
+            }
+        }
+    }
+    let line = format!("{} x{}", photo::HINT, mt.time_scale);
+    let line = format!("{:<1$}", line, mt.renderer.w as usize);
+    mt.renderer.put_str(0, mt.renderer.h - 1, &line, cn.colors.preview);
+}
+
+/// Draw the minimap at the start of the top line, w columns wide, marking the ice, the trees, the house,
+/// the other visitors and the cat at their columns
+fn draw_minimap(mt: &mut Mutes, cn: &Consts, w: i32) {
+    let map = mt.minimap;
+    for col in 0..w {
+        let ice = map.cells(col, w).any(|x| matches!(mt.world.tiles.ground(x), Ground::Ice | Ground::Pond));
+        let (glyph, color) = if ice { (minimap::ICE, cn.colors.ice) } else { (minimap::GROUND, cn.colors.border) };
+        mt.renderer.put_str(col, 0, &glyph.to_string(), color);
+    }
+
+    let mut marks = Vec::new();
+    for e in &mt.world.map.entities {
+        let center = e.world_x(mt.renderer.w) + cn.art(e.asset).w / 2;
+        match (e.role, e.asset) {
+            (Role::House, _) => marks.push((center, minimap::HOUSE, cn.colors.house)),
+            (_, Asset::Tree) => marks.push((center, minimap::TREE, cn.colors.tree)),
+            _ => (),
+        }
+    }
+    marks.sort_by_key(|(_, glyph, _)| *glyph == minimap::HOUSE);
+    for o in mt.visitors.as_ref().map_or(&[][..], |v| &v.others[..]) {
+        let color = presence::PLAYER_COLORS[o.color % presence::PLAYER_COLORS.len()];
+        marks.push((o.shown_x.round() as i32 + cn.asc_cat.w / 2, minimap::VISITOR, color));
+    }
+    marks.push((mt.world.x + cn.asc_cat.w / 2, minimap::CAT, cn.colors.cat));
+
+    for (x, glyph, color) in marks {
+        if let Some(col) = map.column(x, w) {
+            mt.renderer.put_str(col, 0, &glyph.to_string(), color);
+        }
+    }
+}
+
+/// Draw the credits in a box in the middle of the screen, with clickable links to the art sources
+fn draw_credits(mt: &mut Mutes, cn: &Consts) {
+    let mut lines = vec!["Credits".to_string(), String::new()];
+    lines.extend(cn.credits());
+    lines.push(String::new());
+    lines.push("[c] close".to_string());
+
+    let bubble = gen_bubble_ascii(&lines.join("\n"), mt.bubble_style, BubbleKind::Say);
+    let (bx, by) = (((mt.renderer.w - bubble.w) / 2).max(0), ((mt.renderer.h - bubble.h) / 2).max(HUD_HEIGHT));
+    mt.renderer.put_ascii(&bubble, bx, by, cn.colors.bubble);
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((start, end, url)) = term::find_url(line) {
+            mt.renderer.put_link(bx + 2 + line[..start].chars().count() as i32, by + 1 + i as i32, &line[start..end], &url, cn.colors.house);
+        }
+    }
+}
+
+/// Links and the people who made the game, at the end of the end credits
+const CONTRIBUTORS: &[&str] = &["Azalea (hykilpikonna)"];
+const LINKS: &[&str] = &["https://hydev.org", "https://github.com/hykilpikonna/tngame"];
+
+/// Draw the end credits scrolled up from below the screen to above it by a share from 0 to 1, with
+/// clickable links
+fn draw_end_credits(mt: &mut Mutes, cn: &Consts, rolled: f32) {
+    let mut lines = vec!["tngame".to_string(), String::new(), "Art".to_string()];
+    lines.extend(cn.credits());
+    lines.extend([String::new(), "Made by".to_string()]);
+    lines.extend(CONTRIBUTORS.iter().map(|c| c.to_string()));
+    lines.push(String::new());
+    lines.extend(LINKS.iter().map(|l| l.to_string()));
+    lines.extend([String::new(), "Thanks for visiting <3".to_string()]);
+
+    let top = mt.renderer.h - (rolled * (mt.renderer.h + lines.len() as i32) as f32) as i32;
+    for (i, line) in lines.iter().enumerate() {
+        let (x, y) = ((mt.renderer.w - line.chars().count() as i32) / 2, top + i as i32);
+        if !(0..mt.renderer.h).contains(&y) { continue; }
+        mt.renderer.put_str(x, y, line, cn.colors.cat);
+        if let Some((start, end, url)) = term::find_url(line) {
+            mt.renderer.put_link(x + line[..start].chars().count() as i32, y, &line[start..end], &url, cn.colors.house);
+        }
+    }
+}
+
+/// Places the compass points to: the entities of the map that do something, the NPCs and the sled
+fn landmarks(mt: &Mutes, cn: &Consts) -> Vec<Landmark> {
+    let mut landmarks = Vec::new();
+    for e in &mt.world.map.entities {
+        let name = match e.role {
+            Role::House => "House",
+            Role::Guestbook => "Guestbook",
+            Role::Website => "Website",
+            Role::Leaderboard if mt.leaderboard.is_some() => "Leaderboard",
+            Role::Leaderboard | Role::Prop | Role::Signpost | Role::Bulletin => continue,
+        };
+        landmarks.push(Landmark { name, x: e.world_x(mt.renderer.w), w: cn.art(e.asset).w });
+    }
+    for (i, n) in npc::NPCS.iter().enumerate() {
+        landmarks.push(Landmark { name: n.name, x: mt.npc_x(i), w: cn.npc_arts[i].w });
+    }
+    landmarks.push(Landmark { name: "Sled", x: (mt.renderer.w as f32 * sled::SLED_X_VW) as i32, w: cn.asc_sled.w });
+    landmarks
+}
+
+/// World x and art of the first entity with a role in the map
+fn entity<'a>(mt: &Mutes, cn: &'a Consts, role: Role) -> Option<(i32, &'a AsciiArt)> {
+    mt.world.map.find(role).map(|e| (e.world_x(mt.renderer.w), cn.art(e.asset)))
+}
+
+/// Whether the cat is standing at the first entity with a role
+fn near(mt: &Mutes, cn: &Consts, role: Role) -> bool {
+    entity(mt, cn, role).is_some_and(|(x, art)| mt.world.x + cn.asc_cat.w > x && mt.world.x < x + art.w)
+}
+
+/// Whether the cat is standing at the guestbook signpost
+fn near_sign(mt: &Mutes, cn: &Consts) -> bool {
+    near(mt, cn, Role::Guestbook)
+}
+
+/// Index of the entity of the map that the cat is standing at
+fn near_entity(mt: &Mutes, cn: &Consts) -> Option<usize> {
+    mt.world.map.entity_at(mt.world.x + cn.asc_cat.w / 2, mt.renderer.w, |a| cn.art(a).w)
+}
+
+/// Color an asset placed by a map is drawn in
+fn asset_color(cn: &Consts, asset: Asset) -> &'static str {
+    match asset {
+        Asset::Tree => cn.colors.tree,
+        Asset::Snowman => cn.colors.snow[0],
+        Asset::Fish => cn.colors.fish,
+        Asset::Campfire => color::code(cn.colors.light),
+        Asset::House | Asset::Sign | Asset::Board | Asset::Website | Asset::Sled | Asset::Lamp | Asset::Bench | Asset::Signpost | Asset::Bulletin => cn.colors.house,
+    }
+}
+
+/// Open the text of a bulletin board of the map in the reader, which is the built-in text unless the
+/// board names a file
+fn open_bulletin(mt: &mut Mutes, i: usize) {
+    let text = match &mt.world.map.entities[i].file {
+        Some(file) => match fs::read_to_string(file) {
+            Ok(text) => text,
+            Err(e) => {
+                log::warn!("Failed to read the bulletin board {}: {}", file, e);
+                mt.ui.toasts.push(ToastKind::Error, "Couldn't read the bulletin board");
+                return;
+            }
+        },
+        None => reader::DEFAULT.to_string(),
+    };
+    mt.ui.reader = Some(Reader::new("Bulletin board", &text));
+}
+
+/// Whether the cat is close enough to a campfire or the house to warm up
+fn by_heat(mt: &Mutes, cn: &Consts) -> bool {
+    let (start, end) = (mt.world.x - warmth::HEAT_RANGE, mt.world.x + cn.asc_cat.w + warmth::HEAT_RANGE);
+    mt.world.map.entities.iter()
+        .filter(|e| e.role == Role::House || e.asset == Asset::Campfire)
+        .any(|e| {
+            let x = e.world_x(mt.renderer.w);
+            x < end && x + cn.art(e.asset).w > start
+        })
+}
+
+/// World x and art of the bench the cat is standing at
+fn bench<'a>(mt: &Mutes, cn: &'a Consts) -> Option<(i32, &'a AsciiArt)> {
+    let e = &mt.world.map.entities[near_entity(mt, cn)?];
+    (e.asset == Asset::Bench).then(|| (e.world_x(mt.renderer.w), cn.art(e.asset)))
+}
+
+/// Index of the NPC the cat is standing next to
+fn near_npc(mt: &Mutes, cn: &Consts) -> Option<usize> {
+    (0..npc::NPCS.len()).find(|&i| {
+        let x = mt.npc_x(i);
+        mt.world.x + cn.asc_cat.w > x && mt.world.x < x + cn.npc_arts[i].w
+    })
+}
+
+/// Handle a key in the map editor, returning whether it was one of the editor's keys
+fn edit_key(mt: &mut Mutes, cn: &Consts, key: Key) -> bool {
+    let Some(editor) = &mut mt.editor else { return false; };
+    match key {
+        Key::Tab => editor.next_asset(),
+        Key::Enter => {
+            let asset = editor.asset();
+            mt.world.map.entities.push(Entity::prop(asset, mt.world.x));
+        },
+        Key::Char('x') => match near_entity(mt, cn) {
+            Some(i) => { mt.world.map.entities.remove(i); },
+            None => mt.say("Nothing to remove here."),
+        },
+        // Toggle ice under the cat
+        Key::Char('i') => {
+            let ground = match mt.world.tiles.tile(mt.world.x + cn.asc_cat.w / 2).ground {
+                Ground::Ice | Ground::Pond => Ground::Grass,
+                Ground::Grass => Ground::Ice,
+            };
+            for x in mt.world.x..mt.world.x + cn.asc_cat.w {
+                mt.world.tiles.set_ground(x, ground);
+                mt.world.map.set_ground(x, ground);
+            }
+        },
+        Key::Char('t') => {
+            match near_entity(mt, cn) {
+                Some(i) => {
+                    mt.ui.input = mt.world.map.line(&mt.world.map.entities[i]).unwrap_or_default().replace('\n', " ");
+                    mt.state = State::Labeling;
+                },
+                None => mt.say("Stand at something\nto give it a line."),
+            }
+        },
+        Key::Char('s') => {
+            let path = editor.path.clone();
+            match mt.world.map.save(&path) {
+                Ok(()) => mt.say(&format!("Saved the map to\n{}", path.display())),
+                Err(e) => {
+                    log::warn!("Failed to save the map {}: {}", path.display(), e);
+                    mt.say("Couldn't save the map...");
+                }
+            }
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Carry out a command typed after ":"
+fn run_command(mt: &mut Mutes, cn: &Consts, cmd: Command) {
+    match cmd {
+        Command::Teleport(x) => match mt.teleport(x) {
+            Some(x) => mt.say(&format!("Teleported to {}", x)),
+            None => mt.say(&format!("I can't get to {}", x)),
+        },
+        Command::Weather(weather, density) => {
+            mt.set_weather(cn, weather, density);
+            mt.say(&format!("Weather: {:?}", weather.kind).to_lowercase());
+        }
+        Command::Time(hours) => {
+            mt.world.clock.set(hours);
+            mt.say(&format!("Time: {:02}:{:02}", hours as u32, (hours.fract() * 60.0) as u32));
+        }
+        Command::Seed(seed) => {
+            utils::seed_rng(seed);
+            mt.say(&format!("Seed: {} (the terrain stays)", seed));
+        }
+        Command::Go(destination) => {
+            let (x, name) = match destination {
+                Destination::X(x) => (x, x.to_string()),
+                Destination::Npc(i) => (mt.npc_x(i) - cn.asc_cat.w - 1, npc::NPCS[i].name.to_string()),
+            };
+            if mt.go_to(x) { mt.say(&format!("Walking to {}", name)); }
+            else { mt.say(&format!("I can't get to {}", name)); }
+        }
+        Command::Blizzard => {
+            let time = mt.time;
+            mt.world.blizzard.start(time);
+            mt.say("Brr... the wind is\npicking up.");
+        }
+        Command::Fireworks(n) => {
+            for _ in 0..n {
+                events::launch_rocket(mt);
+            }
+        }
+    }
+}
+
+/// Short description of where the cat is and what it is doing, used in the window title
+fn location(mt: &Mutes, cn: &Consts) -> String {
+    match mt.state {
+        State::Sledding => "sledding down the hill".to_string(),
+        State::Talking { npc, .. } => format!("talking to {}", npc::NPCS[npc].name),
+        State::Writing => "signing the guestbook".to_string(),
+        State::Chatting => "chatting".to_string(),
+        State::Commanding => "typing a command".to_string(),
+        State::Credits => "reading the credits".to_string(),
+        State::Shopping => "shopping in the house".to_string(),
+        _ => {
+            if let Some(npc) = near_npc(mt, cn) { format!("with {}", npc::NPCS[npc].name) }
+            else if near_sign(mt, cn) { "at the guestbook".to_string() }
+            else if near_sled(mt, cn) { "on top of the sled hill".to_string() }
+            else if near(mt, cn, Role::House) { "near the house".to_string() }
+            else if near_entity(mt, cn).is_some_and(|i| mt.world.map.entities[i].asset == Asset::Tree) { "under a tree".to_string() }
+            else if mt.world.x == 0 { "at the cliff".to_string() }
+            else { "out in the snow".to_string() }
+        }
+    }
+}
+
+/// Sentences describing the current frame for narration: where the cat is, what it says, and what
+/// the NPC it talks to says with the replies it can pick
+fn narration(mt: &Mutes, cn: &Consts) -> Vec<String> {
+    let mut sentences = vec![format!("You are {}.", location(mt, cn))];
+    sentences.extend(mt.said.iter().map(|s| format!("The cat says: {}", narration::sentence(s))));
+    if let State::Talking { npc, node } = mt.state {
+        let (line, npc, node) = (cn.line(npc, node), &npc::NPCS[npc], &npc::NPCS[npc].nodes[node]);
+        let replies: Vec<_> = node.choices.iter().enumerate().map(|(i, c)| format!("[{}] {}", i + 1, c.label)).collect();
+        sentences.push(format!("{} says: {} Replies: {} [esc] Leave.", npc.name, narration::sentence(line), replies.join(" ")));
+    }
+    sentences
+}
+
+/// Update the cat's emotion from what is going on around it
+fn update_emotions(mt: &mut Mutes, cn: &Consts) {
+    let house_center = entity(mt, cn, Role::House).map(|(x, art)| x + art.w / 2);
+    let ctx = Context {
+        mood: mt.affection.mood(),
+        night: mt.world.clock.is_night(),
+        near_warmth: house_center.is_some_and(|c| (mt.world.x + cn.asc_cat.w / 2 - c).abs() < WARMTH_RADIUS),
+        idle: mt.time - mt.last_input,
+    };
+    let time = mt.time;
+    mt.emotions.update(time, &ctx);
+}
+
+/// Whether the cat is close enough to the sled to hop on
+fn near_sled(mt: &Mutes, cn: &Consts) -> bool {
+    let sled_start = (mt.renderer.w as f32 * sled::SLED_X_VW) as i32;
+    mt.world.x + cn.asc_cat.w > sled_start && mt.world.x < sled_start + cn.asc_sled.w
+}
+
+/// Update the world by the time passed since the last frame and draw it into the buffer
+fn update_frame(mt: &mut Mutes, cn: &Consts, now: Instant) {
+    // Calculate the delta time, which stays zero while paused so that resuming doesn't jump ahead,
+    // except for frames stepped through in debug mode which take exactly one frame
+    let step = mt.paused && mt.step;
+    let fixed = mt.recorder.is_some() || mt.playback.is_some();
+    let dt = if (mt.paused || !mt.focused) && !step { 0.0 }
+        else if step || fixed { FRAME_TIME.as_secs_f32() }
+        else { (now - mt.last_update).as_secs_f32() };
+    let dt = dt * mt.time_scale;
+    let before = step.then(|| debug::Snapshot::take(mt));
+
+    // Update scenes
+    mt.last_update = now;
+    mt.time += dt;
+    mt.world.clock.update(dt);
+    mt.world.blizzard.update(mt.time);
+    if mt.blizzard() > 0.0 {
+        mt.grow_snow(mt.blizzard_density(), cn);
+    }
+    mt.affection.update(dt);
+    mt.update_warmth(cn, dt);
+    mt.update_npcs(dt);
+    mt.update_fight(cn, dt);
+    if mt.state == State::Sledding {
+        mt.update_snow(dt);
+        mt.draw_snow(false);
+        mt.update_sled(dt);
+        mt.surfaces.end_frame();
+        if let Some(sled) = mt.world.sled.take() {
+            sled.draw(mt, &cn.colors);
+            mt.world.sled = Some(sled);
+        }
+        mt.draw_snow(true);
+    }
+    else {
+        let clock = mt.world.clock;
+        clock.draw_sky(mt, &cn.asc_sun, &cn.asc_moon);
+        mt.draw_grass(cn);
+        mt.update_walk(&cn.asc_cat, dt);
+        mt.update_move_to(dt);
+        mt.update_slide(&cn.asc_cat, dt);
+        pond::update(mt, cn.asc_cat.w, dt);
+        mt.update_draw_x(dt);
+        let scroll = mt.get_scroll();
+        mt.world.tiles.unload(scroll, mt.camera.view_w());
+        mt.minimap.explore(scroll, mt.camera.view_w());
+        let landmarks = landmarks(mt, cn);
+        // Landmarks are found once the cutscene playing is over, and can play their own story moments.
+        // Bumping into the end of the world rolls the end credits.
+        let at_end = mt.world.x > 0 && mt.world.tiles.tile(mt.world.x + cn.asc_cat.w).solid;
+        if at_end && mt.cutscenes && mt.cutscene.is_none() && !mt.save.seen.contains(cutscene::END.name) {
+            mt.play(&cutscene::END);
+        }
+        if mt.cutscene.is_none() {
+            for name in mt.compass.discover(mt.world.x, cn.asc_cat.w, &landmarks) {
+                let story = cutscene::STORIES.iter().find(|(landmark, _)| *landmark == name).map(|(_, s)| s);
+                if let Some(story) = story.filter(|s| mt.cutscenes && mt.cutscene.is_none() && !mt.save.seen.contains(s.name)) {
+                    mt.play(story);
+                }
+            }
+        }
+        if let Some(mut c) = mt.cutscene.take() {
+            if !c.update(mt, cn) { mt.cutscene = Some(c); }
+        }
+        let snowfall = if mt.snow_enabled && mt.world.weather.kind == Precipitation::Snow { mt.world.weather.intensity * mt.snow_density } else { 0.0 };
+        let time = mt.time;
+        mt.shared.update(dt, snowfall);
+        mt.shared.autosave(time);
+        mt.autosave();
+        let (time, x, chat, emote, player) = (mt.time, mt.world.x, mt.ui.chat.clone(), mt.ui.emote, mt.player.clone());
+        if let Some(v) = &mut mt.visitors {
+            let joined = v.sync(time, &player, x, chat, emote);
+            v.predict(dt);
+            for name in joined {
+                mt.ui.toasts.push(ToastKind::Info, format!("{} joined", name));
+            }
+        }
+        if let Some(weather) = mt.admin.as_mut().and_then(|a| a.poll(time)) {
+            mt.world.weather = weather;
+        }
+        // The leaderboard is reloaded every few seconds while the cat looks at it, to show other visitors' scores
+        if mt.leaderboard.is_some() && time - mt.leaderboard_loaded > LEADERBOARD_RELOAD && near(mt, cn, Role::Leaderboard) {
+            mt.leaderboard = Some(Leaderboard::load());
+            mt.leaderboard_loaded = time;
+        }
+        let now = mt.now_ms();
+        if let Some(s) = &mut mt.stress {
+            s.update(x, dt, now);
+        }
+        let (start, end) = (mt.world.x, mt.world.x + cn.asc_cat.w);
+        if mt.world.inventory.pick_up(Pickup::Fish, start, end) {
+            mt.stats.fish_caught += 1;
+            let caught = mt.stats.fish_caught;
+            mt.record_score(Board::Fish, caught);
+            let time = mt.time;
+            mt.emotions.surprise(time);
+            let msg = format!("A fish! I have {} now.\nPress [f] to eat one.", mt.world.inventory.fish);
+            mt.say(&msg);
+        }
+        if mt.world.inventory.pick_up(Pickup::Coin, start, end) {
+            let msg = format!("A coin! I have {} now.\nThe house has a shop...", mt.world.inventory.coins);
+            mt.say(&msg);
+        }
+        mt.update_snow(dt);
+        mt.draw_snow(false);
+        mt.update_smoke(cn, dt);
+        if cn.events.iter().any(|e| e.fireworks) && !mt.reduce_motion {
+            events::launch_fireworks(mt, dt);
+        }
+        events::update_fireworks(mt, dt);
+        mt.world.effects.update(dt);
+        let effects = std::mem::take(&mut mt.world.effects);
+        effects.draw(mt, false);
+        mt.world.effects = effects;
+        update_emotions(mt, cn);
+        draw_ascii_frame(mt, cn);
+        mt.phrases.end_frame();
+        mt.surfaces.end_frame();
+        let (time, scroll, zoom) = (mt.time, mt.get_scroll(), mt.camera.zoom);
+        mt.settled.update(&mt.surfaces, &mut mt.renderer, time, scroll, zoom);
+        light::draw(mt, cn);
+        if let Some(c) = &mt.cutscene {
+            let (faded, rolled) = (c.faded(mt.time), c.rolled(mt.time));
+            mt.renderer.dissolve(faded);
+            if let Some(rolled) = rolled {
+                draw_end_credits(mt, cn, rolled);
+            }
+        }
+    }
+
+    if let Some(reader) = mt.ui.reader.take() {
+        reader.draw(mt, cn.colors.cat, cn.colors.border);
+        mt.ui.reader = Some(reader);
+    }
+
+    let now = mt.now_ms();
+    mt.ui.toasts.draw(&mut mt.renderer, HUD_HEIGHT, now);
+
+    // Report what changed in a stepped frame
+    if let Some(before) = before {
+        mt.step = false;
+        mt.step_log = debug::Snapshot::take(mt).diff(&before);
+        log::info!("Stepped a frame: {}", mt.step_log.join(", "));
+    }
+
+    if mt.paused {
+        let x = (mt.renderer.w - PAUSED.chars().count() as i32) / 2;
+        mt.renderer.put_str(x, 1, PAUSED, cn.colors.cat);
+        for (i, line) in mt.step_log.clone().iter().enumerate() {
+            mt.renderer.put_str(0, 2 + i as i32, line, cn.colors.cat);
+        }
+        if let Some(menu) = mt.ui.menu.take() {
+            menu.draw(mt);
+            mt.ui.menu = Some(menu);
+        }
+    }
+
+    mt.frame += 1;
+}
+
+/// Menu shown while paused, with the settings as they are
+fn pause_menu(mt: &Mutes) -> Menu {
+    let (min, max) = SNOW_TUNING_RANGE;
+    let slider = |label, value| Widget::Slider { label, value, min, max, step: SNOW_TUNING_STEP };
+    let theme = Theme::NAMES.iter().position(|&n| n == mt.renderer.theme.name()).unwrap_or(0);
+    Menu::new("Paused", vec![
+        Widget::Button("Resume"),
+        Widget::Toggle("Snow", mt.snow_enabled),
+        slider("Snow density", mt.snow_density),
+        slider("Snow speed", mt.snow_speed),
+        Widget::Toggle("Reduce motion", mt.reduce_motion),
+        Widget::Toggle("Cozy mode", mt.cozy),
+        Widget::Choice { label: "Theme", options: Theme::NAMES, selected: theme },
+        Widget::Button("Quit"),
+    ])
+}
+
+/// Menu offering to pick up a visit that ended suddenly from where it was autosaved
+fn restore_menu() -> Menu {
+    Menu::new("Your last visit ended suddenly", vec![Widget::Button("Restore"), Widget::Button("Start over")])
+}
+
+/// Apply what was changed or activated in the pause menu or the restore menu
+fn pause_menu_action(mt: &mut Mutes, cn: &Consts, action: Action) {
+    let Some(menu) = mt.ui.menu.take() else { return; };
+    match action {
+        Action::Activated(i) => match menu.items[i].label() {
+            "Resume" => mt.paused = false,
+            "Restore" => {
+                if let Some(a) = mt.recovered.take() {
+                    a.restore(mt);
+                }
+                mt.paused = false;
+            }
+            "Start over" => {
+                mt.recovered = None;
+                mt.paused = false;
+            }
+            // Roll the end credits before leaving, unless they can't play right now
+            "Quit" if mt.cutscenes && mt.state != State::Sledding => {
+                mt.paused = false;
+                mt.play(&cutscene::GOODBYE);
+            }
+            "Quit" => mt.should_exit = true,
+            _ => (),
+        },
+        Action::Changed(i) => match menu.items[i].label() {
+            "Snow" => mt.snow_enabled = menu.toggle(i),
+            "Snow density" => {
+                mt.tune_snow_density(menu.value(i) - mt.snow_density, cn);
+            }
+            "Snow speed" => mt.tune_snow_speed(menu.value(i) - mt.snow_speed),
+            "Reduce motion" => mt.reduce_motion = menu.toggle(i),
+            "Cozy mode" => mt.cozy = menu.toggle(i),
+            "Theme" => mt.renderer.theme = Theme::parse(Theme::NAMES[menu.selected(i)]).unwrap_or_default(),
+            _ => (),
+        },
+    }
+    if mt.paused {
+        mt.ui.menu = Some(menu);
+    }
+}
+
+/// Handle a key pressed by the player, on a keyboard or a controller that may report key releases
+fn handle_key(mt: &mut Mutes, cn: &Consts, event: KeyEvent, releases: bool) {
+    mt.woken = Instant::now();
+
+    // Record every event, releases and shift included, for replays to walk and stop the same way
+    let (frame, time) = (mt.frame, mt.time);
+    if let Some(r) = &mut mt.recorder {
+        r.record(&Record { frame, time, event, releases });
+    }
+
+    // Stand still while the terminal is in the background, since the keys held may be released there
+    if matches!(event.key, Key::FocusIn | Key::FocusOut) {
+        mt.focused = event.key == Key::FocusIn;
+        mt.world.movement = Movement::new();
+        return;
+    }
+
+    // With key release events, the cat walks for as long as the key is held, and runs while shift is
+    // held. Without them, shift shows up as upper case letters.
+    let dir = match event.key {
+        Key::Char('a' | 'A') | Key::Left => -1,
+        Key::Char('d' | 'D') | Key::Right => 1,
+        _ => 0,
+    };
+    if event.key == Key::Shift {
+        mt.world.movement.shift = event.kind != Kind::Release;
+        return;
+    }
+    if event.kind == Kind::Release {
+        mt.world.movement.release(dir);
+        return;
+    }
+
+    if event.key == Key::KittySupported {
+        mt.kitty = true;
+        return;
+    }
+    mt.last_input = mt.time;
+
+    // Cutscenes are watched, and skipped on space, enter or escape
+    if mt.cutscene.is_some() && !mt.paused {
+        match event.key {
+            Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+            Key::Char(' ') | Key::Enter | Key::Esc => mt.skip_cutscene(cn),
+            _ => (),
+        }
+        return;
+    }
+
+    // Pause and resume on space, except when it is typed into a message
+    if event.key == Key::Char(' ') && !matches!(mt.state, State::Writing | State::Chatting | State::Commanding | State::Labeling) {
+        mt.paused = !mt.paused;
+        mt.step_log.clear();
+        mt.ui.menu = (mt.paused && !mt.debug).then(|| pause_menu(mt));
+        return;
+    }
+
+    // Run time slower or faster on < and > in debug and photo mode
+    let typing = matches!(mt.state, State::Writing | State::Chatting | State::Commanding | State::Labeling);
+    if matches!(event.key, Key::Char('<' | '>')) && (mt.debug || mt.ui.photo.is_some()) && !typing {
+        mt.scale_time(if event.key == Key::Char('>') { 1 } else { -1 });
+        return;
+    }
+    if mt.paused {
+        match event.key {
+            Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+            // Step a single frame on . in debug mode
+            Key::Char('.') if mt.debug => mt.step = true,
+            Key::Esc => {
+                mt.paused = false;
+                mt.ui.menu = None;
+            }
+            key => {
+                let action = mt.ui.menu.as_mut().and_then(|m| m.key(key));
+                if let Some(action) = action {
+                    pause_menu_action(mt, cn, action);
+                }
+            }
+        }
+        return;
+    }
+
+    // In photo mode, the arrows pan the camera and enter saves the scene
+    if mt.ui.photo.is_some() {
+        photo::handle_key(mt, event.key);
+        return;
+    }
+
+    // While reading a bulletin board, the arrows flip the pages
+    if mt.ui.reader.is_some() {
+        reader::handle_key(mt, event.key, dir);
+        return;
+    }
+
+    // Secret codes typed while walking around set off easter eggs, and the keys still do what they do
+    if matches!(mt.state, State::Welcome | State::Exploring) {
+        if let Some(egg) = mt.secrets.feed(event.key) {
+            secrets::unlock(mt, egg);
+        }
+    }
+
+    // While sledding, the keys steer the sled instead of moving the cat
+    if mt.world.sled.is_some() {
+        sled::handle_key(mt, event.key);
+        return;
+    }
+
+    // In a snowball fight, the cat still walks around to dodge
+    if mt.world.fight.is_some() && snowball::handle_key(mt, cn, event.key) {
+        return;
+    }
+
+    // While talking, the number keys pick a reply
+    if let State::Talking { npc, node } = mt.state {
+        npc::handle_key(mt, npc, node, event.key);
+        return;
+    }
+
+    // While picking an emote, the number keys show one above the cat
+    if mt.state == State::Emoting {
+        emote::handle_key(mt, event.key);
+        return;
+    }
+
+    // While shopping, the number keys buy an item
+    if mt.state == State::Shopping {
+        shop::handle_key(mt, event.key);
+        return;
+    }
+
+    // While writing, the keys type the guestbook message or what the cat says
+    if matches!(mt.state, State::Writing | State::Chatting | State::Commanding | State::Labeling) {
+        ui::type_key(mt, cn, event.key);
+        return;
+    }
+
+    // The map editor takes over the keys for placing things, while the cat walks around as its cursor
+    if mt.editor.is_some() && edit_key(mt, cn, event.key) {
+        return;
+    }
+
+    // Type a command on : when cheats are on
+    if event.key == Key::Char(':') && mt.cheats && mt.state != State::Sledding {
+        mt.ui.input.clear();
+        mt.state = State::Commanding;
+        return;
+    }
+
+    // Take pictures of the scene in photo mode on o, starting from where the camera is
+    if event.key == Key::Char('o') && matches!(mt.state, State::Welcome | State::Exploring) {
+        mt.ui.photo = Some(Photo::new(mt.get_scroll()));
+        return;
+    }
+
+    // Make the cat say something on t
+    if event.key == Key::Char('t') {
+        mt.ui.input.clear();
+        mt.state = State::Chatting;
+        return;
+    }
+
+    // Open the quick-pick of emotes on v
+    if event.key == Key::Char('v') && matches!(mt.state, State::Welcome | State::Exploring) {
+        mt.state = State::Emoting;
+        return;
+    }
+
+    // Toggle the credits on c
+    if event.key == Key::Char('c') {
+        mt.state = if mt.state == State::Credits { State::Exploring } else { State::Credits };
+        return;
+    }
+
+    // Open the guestbook on e or enter
+    let interact = matches!(event.key, Key::Char('e') | Key::Enter);
+    if interact && near_sign(mt, cn) {
+        mt.ui.input.clear();
+        mt.state = State::Writing;
+        return;
+    }
+
+    // Talk to the NPC next to the cat on e or enter
+    if let Some(npc) = near_npc(mt, cn).filter(|_| interact) {
+        if mt.npc_asleep(npc) {
+            mt.say("Shh... they're asleep.");
+        } else {
+            let met = mt.save.met.contains(npc::NPCS[npc].name);
+            mt.state = State::Talking { npc, node: npc::NPCS[npc].start(met) };
+        }
+        return;
+    }
+
+    // Read the signpost on e or enter, or stop reading it
+    if let Some(i) = near_entity(mt, cn).filter(|&i| interact && mt.world.map.entities[i].role == Role::Signpost) {
+        mt.ui.reading = if mt.ui.reading == Some(i) { None } else { Some(i) };
+        mt.mark_read(i);
+        return;
+    }
+
+    // Read the bulletin board on e or enter
+    if let Some(i) = near_entity(mt, cn).filter(|&i| interact && mt.world.map.entities[i].role == Role::Bulletin) {
+        open_bulletin(mt, i);
+        mt.mark_read(i);
+        return;
+    }
+
+    // Sit down on the bench or get up again on e or enter
+    if interact && (mt.world.sitting || bench(mt, cn).is_some()) {
+        mt.world.sitting = !mt.world.sitting;
+        if let Some((x, art)) = bench(mt, cn).filter(|_| mt.world.sitting) {
+            mt.world.x = x + (art.w - cn.asc_cat_sitting.w) / 2;
+            mt.world.move_to = None;
+            mt.world.slide_v = 0.0;
+        }
+        return;
+    }
+
+    // Hop on the sled on e or enter
+    if interact && near_sled(mt, cn) {
+        mt.start_sledding();
+        return;
+    }
+
+    // Plant a seed in the garden plot the cat stands at, or pick its flower, on e or enter
+    if let Some(plot) = mt.world.map.plot_at(mt.world.x + cn.asc_cat.w / 2, mt.world.tiles.w()).filter(|_| interact) {
+        let now = mt.now_ms() / 1000;
+        let (msg, coins) = garden::tend(&mut mt.save, plot, now);
+        mt.world.inventory.coins += coins;
+        mt.write_save();
+        mt.say(&msg);
+        return;
+    }
+
+    // Go shopping in the house on e or enter
+    if interact && near(mt, cn, Role::House) {
+        mt.state = State::Shopping;
+        return;
+    }
+
+    // Switch on the key
+    match event.key {
+        // exit on q or ctrl+c or esc
+        Key::Char('q') | Key::CtrlC | Key::Esc => mt.should_exit = true,
+        // Pet the cat on p, and feed it a fish on f
+        Key::Char('p') => {
+            let time = mt.time;
+            let msg = mt.affection.pet(time);
+            mt.say(msg);
+        },
+        Key::Char('f') => {
+            if mt.world.inventory.fish == 0 {
+                mt.say("No fish left...\nMaybe I can find some?");
+            } else {
+                mt.world.inventory.fish -= 1;
+                let msg = mt.affection.feed();
+                mt.say(msg);
+            }
+        },
+        // Move left on a or left arrow, and right on d or right arrow. When releases are reported,
+        // step once right away and keep walking until the key is released, ignoring the repeats sent
+        // while it is held.
+        _ if dir != 0 => {
+            mt.world.move_to = None;
+            if !releases {
+                mt.world.movement.shift = event.shift || matches!(event.key, Key::Char('A' | 'D'));
+                let cells = mt.world.movement.step(dir);
+                mt.walk(&cn.asc_cat, cells);
+            } else if event.kind == Kind::Press {
+                mt.world.movement.shift = event.shift;
+                let cells = mt.world.movement.hold(dir);
+                mt.walk(&cn.asc_cat, cells);
+            }
+        },
+        // Call up a blizzard or a gentle flurry with + and -, and change how fast it falls with [ and ]
+        Key::Char('+' | '=') | Key::Char('-' | '_') => {
+            let delta = if matches!(event.key, Key::Char('+' | '=')) { SNOW_TUNING_STEP } else { -SNOW_TUNING_STEP };
+            mt.tune_snow_density(delta, cn);
+        },
+        Key::Char(']') => mt.tune_snow_speed(SNOW_TUNING_STEP),
+        Key::Char('[') => mt.tune_snow_speed(-SNOW_TUNING_STEP),
+        // Zoom out to see more of the world on z, and back in again
+        Key::Char('z') => {
+            mt.camera.zoom = if mt.camera.zoom == 1 { ZOOM_OUT } else { 1 };
+            mt.say(if mt.camera.zoom == 1 { "Back up close!" } else { "The world is big..." });
+        },
+        // Toggle the minimap on m
+        Key::Char('m') => {
+            mt.minimap.shown = !mt.minimap.shown;
+            mt.say(if mt.minimap.shown { "Where am I again?" } else { "I know my way around." });
+        },
+        // Toggle the snow or rain on w
+        Key::Char('w') => {
+            mt.snow_enabled = !mt.snow_enabled;
+            mt.say(if mt.snow_enabled { "The snow is back!" } else { "The sky cleared up." });
+        },
+        // Build a snowman on b
+        Key::Char('b') => {
+            let x = mt.world.x + (cn.asc_cat.w - cn.asc_snowman.w) / 2;
+            if mt.shared.build_snowman(x) {
+                mt.say(if leaderboard::server_mode() { "I built a snowman!\nEveryone can see it." } else { "I built a snowman!\nIt'll be here next time." });
+                if let Err(e) = mt.shared.save() {
+                    log::warn!("Failed to save the shared world: {}", e);
+                    mt.ui.toasts.push(ToastKind::Error, "Couldn't save the snowman");
+                }
+            } else {
+                mt.say("There's a snowman\nhere already!");
+            }
+        },
+        // Sit on h, loaf on l and sleep on n, or stand up again on the same key. Falling asleep after
+        // dusk sleeps through the night, fading out until the morning.
+        Key::Char(c @ ('h' | 'l' | 'n')) => {
+            let Some(pose) = mt.world.pose.toggle(c) else { return; };
+            mt.world.pose = pose;
+            mt.world.move_to = None;
+            mt.world.slide_v = 0.0;
+            if pose == Pose::Sleep && Pose::sleeps_through(mt.world.clock.phase()) {
+                if mt.cutscenes {
+                    mt.play(&cutscene::SLEEP);
+                } else {
+                    mt.wake(pose::WAKE_HOUR);
+                    mt.say("What a good sleep!\nGood morning!");
+                }
+            }
+        },
+        // Toggle running on r
+        Key::Char('r') => {
+            mt.world.movement.toggled = !mt.world.movement.toggled;
+            mt.say(if mt.world.movement.toggled { "Zoom zoom!" } else { "Slowing down..." });
+        },
+        _ => (),
+    }
+}
@@ -0,0 +1,65 @@
+use crate::clock::Phase;
+use crate::map::Asset;
+use crate::{utils, Consts, Mutes};
+
+/// Flickering of the flames, in changes per second, and a seed for each light so they don't flicker
+/// together
+const FLICKER_SPEED: f32 = 6.0;
+const FLICKER_SEED: u32 = 0xf1e;
+
+/// A light given off by the art of an asset, at a column and line of the art
+struct Source {
+    dx: i32,
+    dy: i32,
+    /// Columns the light reaches, and how much of its color it adds right at it
+    radius: f32,
+    strength: f32,
+    /// Share of the strength that comes and goes as the light flickers
+    flicker: f32,
+    /// Whether the light drops by that much now and then, like a worn out bulb, instead of wavering
+    sputter: bool,
+}
+
+/// The two windows of the house, glowing with the fireplace inside
+const WINDOWS: &[Source] = &[
+    Source { dx: 6, dy: 4, radius: 7.0, strength: 0.35, flicker: 0.1, sputter: false },
+    Source { dx: 13, dy: 4, radius: 7.0, strength: 0.35, flicker: 0.1, sputter: false },
+];
+const LAMP: &[Source] = &[Source { dx: 1, dy: 1, radius: 10.0, strength: 0.4, flicker: 0.7, sputter: true }];
+const CAMPFIRE: &[Source] = &[Source { dx: 2, dy: 1, radius: 14.0, strength: 0.5, flicker: 0.4, sputter: false }];
+
+/// Share of the time a sputtering light is down
+const SPUTTER: f32 = 0.15;
+
+/// Lights an asset gives off
+fn sources(asset: Asset) -> &'static [Source] {
+    match asset {
+        Asset::House => WINDOWS,
+        Asset::Lamp => LAMP,
+        Asset::Campfire => CAMPFIRE,
+        _ => &[],
+    }
+}
+
+/// Light up the cells around the windows, the lamps and the campfires once the scene is drawn, fully
+/// at night and half as much at dusk and dawn
+pub fn draw(mt: &mut Mutes, cn: &Consts) {
+    let dark = match mt.world.clock.phase() {
+        Phase::Night => 1.0,
+        Phase::Dawn | Phase::Dusk => 0.5,
+        Phase::Day => return,
+    };
+    let (time, zoom) = (mt.flicker_time(), mt.camera.zoom);
+    for (i, e) in mt.world.map.entities.iter().enumerate() {
+        let art = cn.art(e.asset);
+        let x = e.world_x(mt.renderer.w);
+        let y = mt.rest_y(art, x) - e.y;
+        for (j, s) in sources(e.asset).iter().enumerate() {
+            let seed = FLICKER_SEED.wrapping_add((i * 8 + j) as u32);
+            let n = utils::noise(seed, time * FLICKER_SPEED);
+            let flicker = if !s.sputter { 1.0 - s.flicker * n } else if n < SPUTTER { 1.0 - s.flicker } else { 1.0 };
+            let (sx, sy) = (mt.screen_x(x + s.dx), y + s.dy / zoom);
+            mt.renderer.light(sx, sy, s.radius / zoom as f32, cn.colors.light, s.strength * flicker * dark);
+        }
+    }
+}
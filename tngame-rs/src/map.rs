@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tilemap::Ground;
+
+/// The world as it is built in, which --map replaces
+pub const DEFAULT: &str = include_str!("../maps/world.toml");
+
+/// Art that can be placed in the world
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Asset {
+    Tree,
+    House,
+    Sign,
+    Board,
+    Website,
+    Snowman,
+    Sled,
+    Fish,
+    Lamp,
+    Campfire,
+    Bench,
+    Signpost,
+    Bulletin,
+}
+
+impl Asset {
+    /// Assets in the order the editor cycles through them
+    pub const ALL: [Asset; 13] = [
+        Asset::Tree, Asset::House, Asset::Sign, Asset::Board, Asset::Website, Asset::Snowman, Asset::Sled, Asset::Fish,
+        Asset::Lamp, Asset::Campfire, Asset::Bench, Asset::Signpost, Asset::Bulletin,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Asset::Tree => "tree",
+            Asset::House => "house",
+            Asset::Sign => "sign",
+            Asset::Board => "board",
+            Asset::Website => "website",
+            Asset::Snowman => "snowman",
+            Asset::Sled => "sled",
+            Asset::Fish => "fish",
+            Asset::Lamp => "lamp",
+            Asset::Campfire => "campfire",
+            Asset::Bench => "bench",
+            Asset::Signpost => "signpost",
+            Asset::Bulletin => "bulletin",
+        }
+    }
+}
+
+/// What an entity does in the world besides being drawn
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Only drawn, and says its dialogue when the cat is at it
+    #[default]
+    Prop,
+    /// Where the cat lives, with smoke rising from the chimney and warmth around it
+    House,
+    /// Signpost opening the guestbook
+    Guestbook,
+    /// Signpost linking to the website
+    Website,
+    /// Board showing the top scores in server mode
+    Leaderboard,
+    /// Signpost showing its text when the cat reads it
+    Signpost,
+    /// Bulletin board opening a longer text to read page by page
+    Bulletin,
+}
+
+/// A piece of art placed in the world, resting on the ground
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Entity {
+    #[serde(rename = "type", default)]
+    pub role: Role,
+    pub asset: Asset,
+    /// World x of the left edge, as a share of the screen width plus a number of cells, since the
+    /// world is laid out for the screen it is shown on
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub x_vw: f64,
+    #[serde(default)]
+    pub x: i32,
+    /// Rows above the ground the art is lifted by
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub y: i32,
+    /// Key of the line in the dialogue table the cat says at the entity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialogue: Option<String>,
+    /// Text written on a signpost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// File a bulletin board shows, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl Entity {
+    /// Key telling the entity apart from the others by its asset and position, kept in the save
+    pub fn key(&self) -> String {
+        format!("{}@{}vw{:+}", self.asset.name(), self.x_vw, self.x)
+    }
+}
+
+fn is_default<T: Default + PartialEq>(v: &T) -> bool {
+    *v == T::default()
+}
+
+impl Entity {
+    /// Prop of an asset at a world x
+    pub fn prop(asset: Asset, x: i32) -> Self {
+        Self { role: Role::Prop, asset, x_vw: 0.0, x, y: 0, dialogue: None, text: None, file: None }
+    }
+
+    /// World x of the left edge on a screen w cells wide
+    pub fn world_x(&self, w: i32) -> i32 {
+        (w as f64 * self.x_vw) as i32 + self.x
+    }
+}
+
+/// Ground of a tile changed from what the terrain generates
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct TileEdit {
+    pub x: i32,
+    pub ground: Ground,
+}
+
+/// A frozen pond, w cells wide
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Pond {
+    /// World x of the left edge, like the x of an entity
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub x_vw: f64,
+    #[serde(default)]
+    pub x: i32,
+    pub w: i32,
+}
+
+impl Pond {
+    /// World x of the left edge on a screen w cells wide
+    pub fn world_x(&self, w: i32) -> i32 {
+        (w as f64 * self.x_vw) as i32 + self.x
+    }
+}
+
+/// Columns a garden plot is wide, and columns from the left edge of one plot to the next
+pub const PLOT_W: i32 = 3;
+const PLOT_SPACING: i32 = 4;
+
+/// A row of garden plots the cat plants seeds in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Garden {
+    /// World x of the left edge, like the x of an entity
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub x_vw: f64,
+    #[serde(default)]
+    pub x: i32,
+    pub plots: i32,
+}
+
+/// Everything placed in the world, in a TOML file with an [[entity]] table for each piece of art, a
+/// [[tile]] table for each changed tile, a [[pond]] table for each frozen pond, a [[garden]] table for
+/// each row of garden plots, and the lines the entities say in the [dialogue] table
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Map {
+    #[serde(default, rename = "entity")]
+    pub entities: Vec<Entity>,
+    #[serde(default, rename = "tile")]
+    pub tiles: Vec<TileEdit>,
+    #[serde(default, rename = "pond", skip_serializing_if = "Vec::is_empty")]
+    pub ponds: Vec<Pond>,
+    #[serde(default, rename = "garden", skip_serializing_if = "Vec::is_empty")]
+    pub gardens: Vec<Garden>,
+    #[serde(default)]
+    pub dialogue: BTreeMap<String, String>,
+}
+
+impl Map {
+    /// The built-in world
+    pub fn default_world() -> Self {
+        toml::from_str(DEFAULT).expect("The built-in map is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| anyhow!("Failed to read the map {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| anyhow!("Failed to parse the map {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Index of the last placed entity covering world x on a screen w cells wide, given the width of
+    /// each asset's art
+    pub fn entity_at(&self, x: i32, w: i32, width: impl Fn(Asset) -> i32) -> Option<usize> {
+        self.entities.iter().rposition(|e| (e.world_x(w)..e.world_x(w) + width(e.asset)).contains(&x))
+    }
+
+    /// Left edge and width of the pond covering world x on a screen w cells wide
+    pub fn pond_at(&self, x: i32, w: i32) -> Option<(i32, i32)> {
+        self.ponds.iter().map(|p| (p.world_x(w), p.w)).find(|&(start, pw)| (start..start + pw).contains(&x))
+    }
+
+    /// World x of the left edge of every garden plot on a screen w cells wide, numbered in the order
+    /// of the gardens
+    pub fn plots(&self, w: i32) -> Vec<i32> {
+        self.gardens.iter()
+            .flat_map(|g| (0..g.plots).map(move |i| (w as f64 * g.x_vw) as i32 + g.x + i * PLOT_SPACING))
+            .collect()
+    }
+
+    /// Number of the garden plot covering world x on a screen w cells wide
+    pub fn plot_at(&self, x: i32, w: i32) -> Option<usize> {
+        self.plots(w).iter().position(|&px| (px..px + PLOT_W).contains(&x))
+    }
+
+    /// The first entity with a role
+    pub fn find(&self, role: Role) -> Option<&Entity> {
+        self.entities.iter().find(|e| e.role == role)
+    }
+
+    /// Line of dialogue an entity says, if it has one
+    pub fn line(&self, entity: &Entity) -> Option<&str> {
+        entity.dialogue.as_ref().and_then(|k| self.dialogue.get(k)).map(String::as_str)
+    }
+
+    /// Give an entity a line of dialogue, or take its line away, keyed by its asset and position
+    pub fn set_line(&mut self, i: usize, line: Option<String>) {
+        let e = &mut self.entities[i];
+        let key = e.dialogue.clone().unwrap_or_else(|| format!("{}-{}", e.asset.name(), e.x));
+        match line {
+            Some(line) => {
+                e.dialogue = Some(key.clone());
+                self.dialogue.insert(key, line);
+            }
+            None => {
+                e.dialogue = None;
+                self.dialogue.remove(&key);
+            }
+        }
+    }
+
+    /// Change the ground of a tile, replacing an earlier change of the same tile
+    pub fn set_ground(&mut self, x: i32, ground: Ground) {
+        self.tiles.retain(|t| t.x != x);
+        self.tiles.push(TileEdit { x, ground });
+    }
+}
+
+/// State of the map editor, where the cat is the cursor
+pub struct Editor {
+    /// File the map is saved to
+    pub path: PathBuf,
+    /// Index of the asset stamped next, in Asset::ALL
+    pub asset: usize,
+}
+
+impl Editor {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, asset: 0 }
+    }
+
+    pub fn asset(&self) -> Asset {
+        Asset::ALL[self.asset]
+    }
+
+    pub fn next_asset(&mut self) {
+        self.asset = (self.asset + 1) % Asset::ALL.len();
+    }
+}
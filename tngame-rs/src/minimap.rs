@@ -0,0 +1,42 @@
+use std::ops::Range;
+
+/// Minimap glyphs, drawn over each other in this order
+pub const GROUND: char = '-';
+pub const ICE: char = '=';
+pub const TREE: char = '^';
+pub const HOUSE: char = 'H';
+pub const VISITOR: char = 'o';
+pub const CAT: char = '@';
+
+/// A one-line map of the part of the world the cat has seen, squeezed into the width of the screen
+#[derive(Clone, Copy)]
+pub struct Minimap {
+    pub shown: bool,
+    /// World x of the left and right end of what has been on screen so far
+    explored: (i32, i32),
+}
+
+impl Minimap {
+    pub fn new(scroll: i32, w: i32) -> Self {
+        Self { shown: false, explored: (scroll, scroll + w - 1) }
+    }
+
+    /// Widen the explored range to the part of the world on screen
+    pub fn explore(&mut self, scroll: i32, w: i32) {
+        self.explored = (self.explored.0.min(scroll), self.explored.1.max(scroll + w - 1));
+    }
+
+    /// Column of a world x on a minimap w columns wide, if it has been explored
+    pub fn column(&self, x: i32, w: i32) -> Option<i32> {
+        let (lo, hi) = self.explored;
+        if !(lo..=hi).contains(&x) || w <= 0 { return None; }
+        Some(((x - lo) as i64 * w as i64 / (hi - lo + 1) as i64) as i32)
+    }
+
+    /// World x covered by a column of a minimap w columns wide
+    pub fn cells(&self, col: i32, w: i32) -> Range<i32> {
+        let (lo, hi) = self.explored;
+        let at = |c: i32| lo + ((c as i64 * (hi - lo + 1) as i64) / w.max(1) as i64) as i32;
+        at(col)..at(col + 1).max(at(col) + 1)
+    }
+}
@@ -0,0 +1,80 @@
+use std::env;
+
+/// Movement constants
+const STEP_RATE: f32 = 20.0; // Steps per second while a walking key is held, about as fast as key repeat
+const RUN_MULTIPLIER: f32 = 3.0; // Running moves this many times as far per step
+
+/// How the cat walks, either a step per keypress or at a steady velocity while a key is held
+pub struct Movement {
+    /// Cells moved per step, configured with TN_WALK_SPEED
+    pub speed: f32,
+    /// Share of the speed the cat walks at, lower while it is cold
+    pub pace_share: f32,
+    /// Whether running was toggled on
+    pub toggled: bool,
+    /// Whether shift is held
+    pub shift: bool,
+    held: i32,
+    acc: f32,
+}
+
+impl Movement {
+    pub fn new() -> Self {
+        let speed = env::var("TN_WALK_SPEED").ok()
+            .and_then(|s| s.parse::<f32>().ok())
+            .filter(|s| *s > 0.0)
+            .unwrap_or(1.0);
+        Self { speed, pace_share: 1.0, toggled: false, shift: false, held: 0, acc: 0.0 }
+    }
+
+    pub fn running(&self) -> bool {
+        self.toggled || self.shift
+    }
+
+    /// Cells moved per step
+    fn step_size(&self) -> f32 {
+        let speed = self.speed * self.pace_share;
+        if self.running() { speed * RUN_MULTIPLIER } else { speed }
+    }
+
+    /// Steps per second the cat takes when walking on its own, like along a path
+    pub fn pace(&self) -> f32 {
+        self.speed * STEP_RATE
+    }
+
+    /// Velocity in cells per second, which is zero unless a walking key is held
+    pub fn velocity(&self) -> f32 {
+        self.held as f32 * self.step_size() * STEP_RATE
+    }
+
+    /// Take a single step for a keypress, returning the whole cells to move
+    pub fn step(&mut self, dir: i32) -> i32 {
+        self.advance(dir as f32 * self.step_size())
+    }
+
+    /// Start walking while a key is held, taking the first step right away
+    pub fn hold(&mut self, dir: i32) -> i32 {
+        self.held = dir;
+        self.acc = 0.0;
+        self.step(dir)
+    }
+
+    /// Stop walking when the held key is released
+    pub fn release(&mut self, dir: i32) {
+        if dir == self.held { self.held = 0; }
+    }
+
+    /// Advance by the velocity for one tick, returning the whole cells to move
+    pub fn update(&mut self, dt: f32) -> i32 {
+        if self.held == 0 { return 0; }
+        self.advance(self.velocity() * dt)
+    }
+
+    /// Accumulate a distance, keeping the fraction of a cell for later
+    fn advance(&mut self, distance: f32) -> i32 {
+        self.acc += distance;
+        let cells = self.acc.trunc();
+        self.acc -= cells;
+        cells as i32
+    }
+}
@@ -0,0 +1,22 @@
+/// Describes what happens in plain sentences for screen readers, instead of drawing the screen. Every
+/// frame the game lists what is going on, and only the sentences that weren't true a frame ago are
+/// told, so standing still is quiet.
+#[derive(Default)]
+pub struct Narrator {
+    /// Sentences describing the last frame
+    last: Vec<String>,
+}
+
+impl Narrator {
+    /// Sentences of a frame that are new since the last frame
+    pub fn narrate(&mut self, now: Vec<String>) -> Vec<String> {
+        let new = now.iter().filter(|s| !self.last.contains(s)).cloned().collect();
+        self.last = now;
+        new
+    }
+}
+
+/// Text of a speech bubble read as one sentence
+pub fn sentence(text: &str) -> String {
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ")
+}
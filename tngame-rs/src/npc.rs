@@ -0,0 +1,294 @@
+use crate::cowsay::BubbleKind;
+use crate::input::Key;
+use crate::{Mutes, State};
+
+/// A line of an NPC's conversation, with the replies the cat can pick
+pub struct Node {
+    pub text: &'static str,
+    pub kind: BubbleKind,
+    pub choices: &'static [Choice],
+}
+
+/// A reply leading to another node of the conversation, or ending it
+pub struct Choice {
+    pub label: &'static str,
+    pub next: Option<usize>,
+}
+
+/// What an NPC is up to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Activity {
+    Idle,
+    Hunt,
+    Fish,
+    Sleep,
+}
+
+impl Activity {
+    /// What is shown next to an NPC busy with the activity
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Activity::Idle => None,
+            Activity::Hunt => Some("*sniff sniff*"),
+            Activity::Fish => Some("~o"),
+            Activity::Sleep => Some("Zzz..."),
+        }
+    }
+}
+
+/// A part of an NPC's day, from an hour until the next slot of its schedule starts
+pub struct Slot {
+    pub from: f32,
+    pub activity: Activity,
+    pub x_vw: f32, // World x it spends the slot at as a percentage of the screen width
+}
+
+/// A friendly animal living in the world
+pub struct Npc {
+    pub name: &'static str,
+    pub schedule: &'static [Slot], // Sorted by hour, the last slot going on past midnight
+    pub art: &'static str,
+    pub color: &'static str,
+    pub first: usize, // Node starting the first conversation
+    pub returning: usize, // Node starting later conversations
+    pub fight: Option<usize>, // Node challenging the cat to a snowball fight, which starts when the conversation ends there
+    pub nodes: &'static [Node],
+}
+
+impl Npc {
+    /// Node to start a conversation with, depending on whether the cat talked to this NPC before
+    pub fn start(&self, met: bool) -> usize {
+        if met { self.returning } else { self.first }
+    }
+
+    /// Slot of the schedule at an hour of the day
+    pub fn slot(&self, hours: f32) -> &Slot {
+        self.schedule.iter().rev().find(|s| s.from <= hours).unwrap_or(&self.schedule[self.schedule.len() - 1])
+    }
+}
+
+/// Cells per second an NPC walks at to where its schedule takes it
+pub const WALK_SPEED: f32 = 6.0;
+
+const END: &[Choice] = &[Choice { label: "Bye!", next: None }];
+
+pub const NPCS: &[Npc] = &[
+    Npc {
+        name: "Fox",
+        // Out in the trees before the hill hunting at dusk, and asleep by the house at night
+        schedule: &[
+            Slot { from: 5.0, activity: Activity::Idle, x_vw: 1.2 },
+            Slot { from: 19.0, activity: Activity::Hunt, x_vw: 2.08 },
+            Slot { from: 21.0, activity: Activity::Sleep, x_vw: 0.88 },
+        ],
+        art: r#"
+ /\   /\
+ \ \_/ /
+ ( o.o )
+  > ^ <~"#,
+        color: "\x1b[38;2;255;160;90m",
+        first: 0,
+        returning: 4,
+        fight: Some(6),
+        nodes: &[
+            // 0
+            Node {
+                text: "Oh! A cat! I haven't seen\nanyone out here in ages.",
+                kind: BubbleKind::Say,
+                choices: &[
+                    Choice { label: "Hi! I'm new here.", next: Some(1) },
+                    Choice { label: "Were you waiting for me?", next: Some(2) },
+                ],
+            },
+            // 1
+            Node {
+                text: "Welcome! I'm Fox. I keep an\neye on the woods around here.",
+                kind: BubbleKind::Say,
+                choices: &[Choice { label: "Are there others?", next: Some(3) }],
+            },
+            // 2
+            Node {
+                text: "Hehe, maybe. It gets lonely\nwhen the snow piles up.",
+                kind: BubbleKind::Whisper,
+                choices: &[Choice { label: "Are there others?", next: Some(3) }],
+            },
+            // 3
+            Node {
+                text: "Penguin lives further east.\nThey'd love to meet you!",
+                kind: BubbleKind::Say,
+                choices: END,
+            },
+            // 4
+            Node {
+                text: "You're back! The woods are\nquiet today.",
+                kind: BubbleKind::Say,
+                choices: &[
+                    Choice { label: "What are you up to?", next: Some(5) },
+                    Choice { label: "Snowball fight?", next: Some(6) },
+                    Choice { label: "Just saying hi!", next: None },
+                ],
+            },
+            // 5
+            Node {
+                text: "Counting snowflakes. I lost\ncount at three hundred.",
+                kind: BubbleKind::Think,
+                choices: END,
+            },
+            // 6
+            Node {
+                text: "You're on! First one to\nland five hits wins.",
+                kind: BubbleKind::Shout,
+                choices: &[Choice { label: "Bring it on!", next: None }],
+            },
+        ],
+    },
+    Npc {
+        name: "Penguin",
+        // Fishing through a hole in the frozen pond in the morning, and asleep by the house at night
+        schedule: &[
+            Slot { from: 5.0, activity: Activity::Fish, x_vw: 1.75 },
+            Slot { from: 11.0, activity: Activity::Idle, x_vw: 1.4 },
+            Slot { from: 21.0, activity: Activity::Sleep, x_vw: 0.98 },
+        ],
+        art: r#"
+   _
+ ('v')
+//-=-\\
+(\_=_/)
+ ^^ ^^"#,
+        color: "\x1b[38;2;200;220;255m",
+        first: 0,
+        returning: 3,
+        fight: None,
+        nodes: &[
+            // 0
+            Node {
+                text: "Brr! Isn't this weather\nwonderful?",
+                kind: BubbleKind::Shout,
+                choices: &[
+                    Choice { label: "It's a bit cold...", next: Some(1) },
+                    Choice { label: "The best!", next: Some(2) },
+                ],
+            },
+            // 1
+            Node {
+                text: "Cold? This is a heatwave\nwhere I come from!",
+                kind: BubbleKind::Say,
+                choices: &[Choice { label: "Where are you from?", next: Some(2) }],
+            },
+            // 2
+            Node {
+                text: "Far south. I came to see\nthe snowy world with Fox.",
+                kind: BubbleKind::Say,
+                choices: END,
+            },
+            // 3
+            Node {
+                text: "Hello again, friend!\nSlid on any ice lately?",
+                kind: BubbleKind::Say,
+                choices: &[
+                    Choice { label: "Yes, whee!", next: Some(4) },
+                    Choice { label: "Not yet.", next: None },
+                ],
+            },
+            // 4
+            Node {
+                text: "Belly-sliding is the only\nway to travel, I say.",
+                kind: BubbleKind::Shout,
+                choices: END,
+            },
+        ],
+    },
+];
+
+/// Handle a key while talking to an NPC at a node of its conversation, where the number keys pick a reply
+pub fn handle_key(mt: &mut Mutes, npc: usize, node: usize, key: Key) {
+    let choices = NPCS[npc].nodes[node].choices;
+    match key {
+        Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+        Key::Esc => mt.end_talking(npc),
+        Key::Char(c) => {
+            let pick = c.to_digit(10).and_then(|i| choices.get((i as usize).wrapping_sub(1)));
+            if let Some(c) = pick {
+                mt.say(c.label);
+                match c.next {
+                    Some(next) => mt.state = State::Talking { npc, node: next },
+                    None => {
+                        mt.end_talking(npc);
+                        if NPCS[npc].fight == Some(node) { mt.start_fight(npc); }
+                    }
+                }
+            }
+        },
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    fn talking(npc: usize, node: usize) -> Mutes {
+        let mut mt = Mutes::detached(assets::load_with(None, &[]), (80, 24));
+        mt.state = State::Talking { npc, node };
+        mt
+    }
+
+    #[test]
+    fn conversations_only_lead_to_nodes_that_exist() {
+        for npc in NPCS {
+            let len = npc.nodes.len();
+            assert!(npc.first < len && npc.returning < len && npc.fight.is_none_or(|f| f < len), "{}", npc.name);
+            for node in npc.nodes {
+                assert!(!node.choices.is_empty(), "{} has a node without replies", npc.name);
+                assert!(node.choices.iter().all(|c| c.next.is_none_or(|n| n < len)), "{}", npc.name);
+            }
+        }
+    }
+
+    #[test]
+    fn returning_visitors_start_elsewhere() {
+        assert_eq!(NPCS[0].start(false), 0);
+        assert_eq!(NPCS[0].start(true), 4);
+    }
+
+    #[test]
+    fn schedules_go_on_past_midnight() {
+        assert_eq!(NPCS[0].slot(12.0).activity, Activity::Idle);
+        assert_eq!(NPCS[0].slot(20.0).activity, Activity::Hunt);
+        assert_eq!(NPCS[0].slot(23.0).activity, Activity::Sleep);
+        assert_eq!(NPCS[0].slot(3.0).activity, Activity::Sleep);
+    }
+
+    #[test]
+    fn talking_to_the_end_meets_the_npc() {
+        let mut mt = talking(0, 0);
+        handle_key(&mut mt, 0, 0, Key::Char('1'));
+        assert_eq!(mt.state, State::Talking { npc: 0, node: 1 });
+        // Keys that aren't a reply leave the conversation where it is
+        handle_key(&mut mt, 0, 1, Key::Char('9'));
+        assert_eq!(mt.state, State::Talking { npc: 0, node: 1 });
+        handle_key(&mut mt, 0, 1, Key::Char('1'));
+        handle_key(&mut mt, 0, 3, Key::Char('1'));
+        assert_eq!(mt.state, State::Exploring);
+        assert!(mt.save.met.contains("Fox"));
+        assert!(mt.world.fight.is_none());
+    }
+
+    #[test]
+    fn walking_away_still_meets_the_npc() {
+        let mut mt = talking(1, 0);
+        handle_key(&mut mt, 1, 0, Key::Esc);
+        assert_eq!(mt.state, State::Exploring);
+        assert!(mt.save.met.contains("Penguin"));
+    }
+
+    #[test]
+    fn accepting_a_challenge_starts_a_fight() {
+        let mut mt = talking(0, 4);
+        handle_key(&mut mt, 0, 4, Key::Char('2'));
+        handle_key(&mut mt, 0, 6, Key::Char('1'));
+        assert!(mt.world.fight.is_some());
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::color::{self, Rgb};
+use crate::theme::Theme;
+
+/// Colors of the snow flakes, one picked for each flake
+const SNOW: &[Rgb] = &[[246, 170, 183], [255, 255, 255], [85, 205, 253]];
+
+/// Colors of the chimney smoke, fading as it dissipates
+const SMOKE: &[Rgb] = &[[190, 190, 200], [120, 120, 135]];
+
+/// From the cat's color to pink, top to bottom
+const SOON: &[Rgb] = &[[255, 231, 151], [246, 170, 183]];
+
+const GRASS_CHARS: &str = ".,;";
+
+/// Colors the scene is drawn in, as interned escape codes that pixels can keep referring to, and the
+/// characters the grass grows in
+pub struct Palette {
+    pub cat: &'static str,
+    pub tree: &'static str,
+    pub house: &'static str,
+    pub border: &'static str,
+    pub grass: &'static str,
+    pub fish: &'static str,
+    pub coin: &'static str,
+    pub ice: &'static str,
+    /// Art about to be stamped in the map editor, and the hints on the bottom line
+    pub preview: &'static str,
+    /// Bubble of what the cat says
+    pub bubble: &'static str,
+    pub announcement: &'static str,
+    pub shadow: &'static str,
+    /// Warm light of the windows, the lamps and the campfire at night, and the color of the flames
+    pub light: Rgb,
+    /// The first snow color is also the color of snow on the ground and of the snowman
+    pub snow: Vec<&'static str>,
+    pub smoke: Vec<&'static str>,
+    /// Rainbow shimmering across the title, and the gradient of the coming soon sign
+    pub title: Vec<Rgb>,
+    pub soon: Vec<Rgb>,
+    pub grass_chars: Vec<char>,
+    /// Theme the scene is shown in unless another one is picked
+    pub theme: Option<Theme>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            cat: color::code([255, 231, 151]),
+            tree: color::code([204, 255, 88]),
+            house: color::code([251, 194, 110]),
+            border: color::code([120, 130, 150]),
+            grass: color::code([181, 203, 194]),
+            fish: color::code([255, 170, 120]),
+            coin: color::code([255, 215, 90]),
+            ice: color::code([170, 230, 255]),
+            preview: color::code([120, 130, 150]),
+            bubble: color::code([255, 231, 151]),
+            announcement: color::code([255, 215, 120]),
+            shadow: color::code([90, 100, 125]),
+            light: [255, 180, 90],
+            snow: codes(SNOW),
+            smoke: codes(SMOKE),
+            title: color::RAINBOW.to_vec(),
+            soon: SOON.to_vec(),
+            grass_chars: GRASS_CHARS.chars().collect(),
+            theme: None,
+        }
+    }
+}
+
+/// The colors file, where every color is a hex value like "#ffe797", or a list of them for the ones
+/// that have several
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct File {
+    cat: Option<String>,
+    tree: Option<String>,
+    house: Option<String>,
+    border: Option<String>,
+    grass: Option<String>,
+    fish: Option<String>,
+    coin: Option<String>,
+    ice: Option<String>,
+    preview: Option<String>,
+    bubble: Option<String>,
+    announcement: Option<String>,
+    shadow: Option<String>,
+    light: Option<String>,
+    snow: Option<Vec<String>>,
+    smoke: Option<Vec<String>>,
+    title: Option<Vec<String>>,
+    soon: Option<Vec<String>>,
+    grass_chars: Option<String>,
+    theme: Option<String>,
+}
+
+impl Palette {
+    /// The built-in colors with the ones set in the text of a colors file replaced
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: File = toml::from_str(text)?;
+        let mut p = Self::default();
+        let one = |value: Option<String>, into: &mut &'static str| -> Result<()> {
+            if let Some(v) = value { *into = color::code(hex(&v)?); }
+            Ok(())
+        };
+        one(file.cat, &mut p.cat)?;
+        one(file.tree, &mut p.tree)?;
+        one(file.house, &mut p.house)?;
+        one(file.border, &mut p.border)?;
+        one(file.grass, &mut p.grass)?;
+        one(file.fish, &mut p.fish)?;
+        one(file.coin, &mut p.coin)?;
+        one(file.ice, &mut p.ice)?;
+        one(file.preview, &mut p.preview)?;
+        one(file.bubble, &mut p.bubble)?;
+        one(file.announcement, &mut p.announcement)?;
+        one(file.shadow, &mut p.shadow)?;
+        if let Some(v) = file.light { p.light = hex(&v)?; }
+        if let Some(v) = file.snow { p.snow = codes(&list(&v)?); }
+        if let Some(v) = file.smoke { p.smoke = codes(&list(&v)?); }
+        if let Some(v) = file.title { p.title = list(&v)?; }
+        if let Some(v) = file.soon { p.soon = list(&v)?; }
+        if let Some(v) = file.grass_chars {
+            if v.is_empty() { return Err(anyhow!("The grass needs at least one character")); }
+            p.grass_chars = v.chars().collect();
+        }
+        if let Some(v) = file.theme { p.theme = Some(Theme::parse(&v)?); }
+        Ok(p)
+    }
+}
+
+/// Color of a hex value like "#ffe797"
+fn hex(value: &str) -> Result<Rgb> {
+    let digits = value.strip_prefix('#').unwrap_or(value);
+    let channel = |i: usize| digits.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (digits.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok([r, g, b]),
+        _ => Err(anyhow!("Bad color: {} (expected a hex value like #ffe797)", value)),
+    }
+}
+
+/// Colors of a list of hex values, which needs at least one
+fn list(values: &[String]) -> Result<Vec<Rgb>> {
+    if values.is_empty() { return Err(anyhow!("A list of colors needs at least one color")); }
+    values.iter().map(|v| hex(v)).collect()
+}
+
+fn codes(colors: &[Rgb]) -> Vec<&'static str> {
+    colors.iter().map(|&c| color::code(c)).collect()
+}
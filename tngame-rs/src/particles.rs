@@ -0,0 +1,283 @@
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::render::Pixel;
+use crate::{utils, Mutes};
+
+/// A point that moves on its own, like a snow flake or a spark
+#[derive(Clone)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// Downwards acceleration in pixels per second squared
+    pub gravity: f32,
+    /// Seconds the particle has been alive, and after how many it disappears
+    pub age: f32,
+    pub life: f32,
+    /// Colors and characters the particle is drawn with over its lifetime, from the first to the last
+    pub colors: Arc<[&'static str]>,
+    pub glyphs: &'static [char],
+    /// Whether the particle is drawn in front of the scene rather than behind it
+    pub front: bool,
+}
+
+impl Particle {
+    /// A particle that lives until it is removed, drawn with its first color and character
+    pub fn endless(x: f32, y: f32, vx: f32, vy: f32, colors: impl Into<Arc<[&'static str]>>, glyphs: &'static [char]) -> Self {
+        Self { x, y, vx, vy, gravity: 0.0, age: 0.0, life: f32::INFINITY, colors: colors.into(), glyphs, front: false }
+    }
+
+    /// Move the particle and let it age
+    pub fn step(&mut self, dt: f32) {
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+        self.vy += self.gravity * dt;
+        self.age += dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < self.life
+    }
+
+    /// Item of a sequence spread evenly over the lifetime of the particle
+    fn stage<T: Copy>(&self, items: &[T]) -> T {
+        let progress = if self.life.is_finite() { self.age / self.life } else { 0.0 };
+        items[((progress * items.len() as f32) as usize).min(items.len() - 1)]
+    }
+
+    /// Color to draw the particle with at its age
+    pub fn color(&self) -> &'static str {
+        self.stage(&self.colors)
+    }
+
+    /// Character to draw the particle with at its age
+    pub fn glyph(&self) -> char {
+        self.stage(self.glyphs)
+    }
+}
+
+/// How the particles of an emitter start moving
+#[derive(Clone, Copy)]
+pub enum Velocity {
+    /// Uniformly random in the ranges of x and y velocities
+    Uniform { vx: (f32, f32), vy: (f32, f32) },
+    /// Outwards in all directions at the same speed, squashed vertically since pixels are about twice
+    /// as tall as they are wide
+    Radial { speed: f32 },
+}
+
+impl Velocity {
+    /// Velocity of particle i of n spawned together
+    pub fn sample(self, rng: &mut impl Rng, i: usize, n: usize) -> (f32, f32) {
+        match self {
+            Velocity::Uniform { vx, vy } => (rng.gen_range(vx.0..=vx.1), rng.gen_range(vy.0..=vy.1)),
+            Velocity::Radial { speed } => {
+                let angle = i as f32 / n as f32 * TAU;
+                (angle.cos() * speed, angle.sin() * speed / 2.0)
+            }
+        }
+    }
+}
+
+/// Spawns particles in an area, continuously at a rate or all at once in bursts
+#[derive(Clone)]
+pub struct Emitter {
+    /// Top left corner and size of the area particles spawn in
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    /// Particles per second, zero for emitters that only burst
+    pub rate: f32,
+    pub velocity: Velocity,
+    pub gravity: f32,
+    pub life: f32,
+    /// Colors over the lifetime of a particle, one of which is picked at random for every particle
+    pub palettes: Vec<Arc<[&'static str]>>,
+    pub glyphs: &'static [char],
+    /// Whether the emitter is spawning
+    pub on: bool,
+    /// Fraction of a particle waiting to be spawned
+    pending: f32,
+}
+
+impl Emitter {
+    pub fn new(x: f32, y: f32, velocity: Velocity, life: f32, palettes: &[&[&'static str]], glyphs: &'static [char]) -> Self {
+        let palettes = palettes.iter().map(|&p| p.into()).collect();
+        Self { x, y, w: 0.0, h: 0.0, rate: 0.0, velocity, gravity: 0.0, life, palettes, glyphs, on: true, pending: 0.0 }
+    }
+
+    /// Spawn particle i of n spawned together
+    fn spawn(&self, i: usize, n: usize) -> Particle {
+        let mut rng = utils::rng();
+        let x = self.x + rng.gen_range(0.0..=self.w);
+        let y = self.y + rng.gen_range(0.0..=self.h);
+        let (vx, vy) = self.velocity.sample(&mut *rng, i, n);
+        let colors = self.palettes[rng.gen_range(0..self.palettes.len())].clone();
+        Particle { x, y, vx, vy, gravity: self.gravity, age: 0.0, life: self.life, colors, glyphs: self.glyphs, front: false }
+    }
+}
+
+/// Most particles a system holds at once. Emitters and bursts spawn no more than there is room for,
+/// so a huge screen can't make a system grow past what can be moved every frame.
+const MAX_PARTICLES: usize = 2000;
+
+/// Particles and the emitters spawning them. Positions are in world space, or in screen space for
+/// systems drawn without scrolling.
+#[derive(Default)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+    pub emitters: Vec<Emitter>,
+    /// Range of world x on screen, for systems in world space. Particles outside of it only age
+    /// instead of moving, aren't drawn, and emitters outside of it don't spawn.
+    pub window: Option<(f32, f32)>,
+}
+
+impl ParticleSystem {
+    /// Spawn new particles from the emitters that are on, then move the particles and remove the dead
+    /// ones, which are returned so effects can chain, like rockets bursting into sparks
+    pub fn update(&mut self, dt: f32) -> Vec<Particle> {
+        let window = self.window;
+        let visible = |x: f32| window.is_none_or(|(start, end)| (start..=end).contains(&x));
+        for e in &mut self.emitters {
+            if !e.on || !visible(e.x) { continue; }
+            e.pending += e.rate * dt;
+            let n = e.pending as usize;
+            e.pending -= n as f32;
+            let room = MAX_PARTICLES.saturating_sub(self.particles.len());
+            self.particles.extend((0..n.min(room)).map(|i| e.spawn(i, n)));
+        }
+        for p in &mut self.particles {
+            if visible(p.x) { p.step(dt); } else { p.age += dt; }
+        }
+        self.particles.extract_if(.., |p| !p.alive()).collect()
+    }
+
+    /// Spawn n particles of an emitter at once
+    pub fn burst(&mut self, emitter: &Emitter, n: usize) {
+        let room = MAX_PARTICLES.saturating_sub(self.particles.len());
+        self.particles.extend((0..n.min(room)).map(|i| emitter.spawn(i, n)));
+    }
+
+    /// Draw the particles, projected onto the screen like the rest of the world for systems in world space
+    pub fn draw(&self, mt: &mut Mutes, world: bool) {
+        let window = self.window;
+        for p in self.particles.iter().filter(|p| window.is_none_or(|(start, end)| (start..=end).contains(&p.x))) {
+            let x = if world { mt.screen_x(p.x.round() as i32) } else { p.x.round() as i32 };
+            mt.renderer.put(x, p.y.round() as i32, Pixel { color: p.color(), char: p.glyph(), dim: false });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: &[&str] = &["a", "b", "c"];
+    const GLYPHS: &[char] = &['*'];
+
+    fn emitter(rate: f32, life: f32) -> Emitter {
+        let mut e = Emitter::new(10.0, 5.0, Velocity::Uniform { vx: (0.0, 0.0), vy: (1.0, 1.0) }, life, &[COLORS], GLYPHS);
+        e.rate = rate;
+        e
+    }
+
+    fn system(e: Emitter) -> ParticleSystem {
+        ParticleSystem { emitters: vec![e], ..ParticleSystem::default() }
+    }
+
+    #[test]
+    fn emits_at_the_rate_over_time() {
+        let mut s = system(emitter(10.0, f32::INFINITY));
+        for _ in 0..4 {
+            s.update(0.25);
+        }
+        assert_eq!(s.particles.len(), 10);
+    }
+
+    #[test]
+    fn keeps_fractions_of_particles_for_later() {
+        let mut s = system(emitter(3.0, f32::INFINITY));
+        for _ in 0..3 {
+            s.update(0.1);
+        }
+        assert_eq!(s.particles.len(), 0);
+        s.update(0.15);
+        assert_eq!(s.particles.len(), 1);
+    }
+
+    #[test]
+    fn stops_emitting_when_off() {
+        let mut e = emitter(10.0, f32::INFINITY);
+        e.on = false;
+        let mut s = system(e);
+        s.update(1.0);
+        assert!(s.particles.is_empty());
+    }
+
+    #[test]
+    fn returns_particles_once_their_life_is_over() {
+        let mut s = ParticleSystem::default();
+        s.burst(&emitter(0.0, 1.0), 3);
+        assert!(s.update(0.6).is_empty());
+        assert_eq!(s.particles[0].color(), "b");
+        let dead = s.update(0.6);
+        assert_eq!(dead.len(), 3);
+        assert!(s.particles.is_empty());
+    }
+
+    #[test]
+    fn gravity_speeds_up_falling() {
+        let mut p = Particle::endless(0.0, 0.0, 2.0, 0.0, COLORS, GLYPHS);
+        p.gravity = 10.0;
+        for _ in 0..10 {
+            p.step(0.1);
+        }
+        assert!((p.vy - 10.0).abs() < 1e-4);
+        assert!((p.x - 2.0).abs() < 1e-4);
+        // Position moves with the speed before each step adds to it: 10 * 0.1² * (0 + 1 + ... + 9)
+        assert!((p.y - 4.5).abs() < 1e-4, "{}", p.y);
+        assert_eq!(p.color(), "a");
+    }
+
+    #[test]
+    fn only_ages_particles_off_the_window() {
+        let mut s = ParticleSystem { window: Some((0.0, 100.0)), ..ParticleSystem::default() };
+        s.particles.push(Particle::endless(50.0, 0.0, 1.0, 1.0, COLORS, GLYPHS));
+        s.particles.push(Particle::endless(500.0, 0.0, 1.0, 1.0, COLORS, GLYPHS));
+        s.update(1.0);
+        assert_eq!((s.particles[0].x, s.particles[0].y), (51.0, 1.0));
+        assert_eq!((s.particles[1].x, s.particles[1].y), (500.0, 0.0));
+        assert_eq!(s.particles[1].age, 1.0);
+    }
+
+    #[test]
+    fn emitters_off_the_window_stay_quiet() {
+        let mut e = emitter(10.0, f32::INFINITY);
+        e.x = 500.0;
+        let mut s = ParticleSystem { window: Some((0.0, 100.0)), ..system(e) };
+        s.update(1.0);
+        assert!(s.particles.is_empty());
+    }
+
+    #[test]
+    fn caps_the_number_of_particles() {
+        let mut s = ParticleSystem::default();
+        s.burst(&emitter(0.0, 1.0), MAX_PARTICLES + 100);
+        assert_eq!(s.particles.len(), MAX_PARTICLES);
+        s.burst(&emitter(0.0, 1.0), 10);
+        assert_eq!(s.particles.len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn radial_bursts_spread_all_around() {
+        let mut rng = utils::rng();
+        let v: Vec<_> = (0..4).map(|i| Velocity::Radial { speed: 2.0 }.sample(&mut *rng, i, 4)).collect();
+        let close = |(x, y): (f32, f32), (ex, ey): (f32, f32)| (x - ex).abs() < 1e-5 && (y - ey).abs() < 1e-5;
+        assert!(close(v[0], (2.0, 0.0)) && close(v[1], (0.0, 1.0)) && close(v[2], (-2.0, 0.0)) && close(v[3], (0.0, -1.0)), "{:?}", v);
+    }
+}
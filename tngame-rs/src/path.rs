@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+/// Path constants
+const CLIMB_COST: f32 = 1.0; // Extra steps of time it takes to climb up one pixel of hill
+
+/// Cells on the way from one world x to another, each with the steps of time it takes to walk onto it,
+/// or None when a solid cell blocks the way. The world is a single line, so there is only one way to
+/// go, and solid cells can't be walked around. Climbing up the hills takes longer than walking on flat
+/// ground, judging from the ground height at every x.
+pub fn find(from: i32, to: i32, height: impl Fn(i32) -> i32, solid: impl Fn(i32) -> bool) -> Option<VecDeque<(i32, f32)>> {
+    let dir = (to - from).signum();
+    let mut path = VecDeque::new();
+    let mut x = from;
+    while x != to {
+        let next = x + dir;
+        if solid(next) { return None; }
+        let climb = (height(next) - height(x)).max(0);
+        path.push_back((next, 1.0 + climb as f32 * CLIMB_COST));
+        x = next;
+    }
+    Some(path)
+}
+
+/// A behavior walking an entity to a world x along a path, one cell at a time
+pub struct MoveTo {
+    pub target: i32,
+    path: VecDeque<(i32, f32)>,
+    /// Steps of time walked towards the next cell
+    acc: f32,
+}
+
+impl MoveTo {
+    /// Plan the way from one world x to another, or None when it is blocked
+    pub fn new(from: i32, to: i32, height: impl Fn(i32) -> i32, solid: impl Fn(i32) -> bool) -> Option<Self> {
+        let path = find(from, to, height, solid)?;
+        Some(Self { target: to, path, acc: 0.0 })
+    }
+
+    /// Walk for a tick at a speed in steps per second, returning the cells to move by
+    pub fn update(&mut self, from: i32, dt: f32, speed: f32) -> i32 {
+        self.acc += dt * speed;
+        let mut x = from;
+        while let Some(&(next, cost)) = self.path.front() {
+            if self.acc < cost { break; }
+            self.acc -= cost;
+            x = next;
+            self.path.pop_front();
+        }
+        x - from
+    }
+
+    pub fn done(&self) -> bool {
+        self.path.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat(_: i32) -> i32 {
+        0
+    }
+
+    fn open(_: i32) -> bool {
+        false
+    }
+
+    #[test]
+    fn walks_cell_by_cell_either_way() {
+        let path: Vec<_> = find(3, 6, flat, open).unwrap().into();
+        assert_eq!(path, vec![(4, 1.0), (5, 1.0), (6, 1.0)]);
+        let back: Vec<_> = find(6, 4, flat, open).unwrap().into_iter().map(|(x, _)| x).collect();
+        assert_eq!(back, vec![5, 4]);
+        assert!(find(2, 2, flat, open).unwrap().is_empty());
+    }
+
+    #[test]
+    fn climbing_takes_longer_than_walking_down() {
+        let hill = |x: i32| x.clamp(0, 2);
+        let up: Vec<_> = find(0, 4, hill, open).unwrap().into_iter().map(|(_, cost)| cost).collect();
+        assert_eq!(up, vec![1.0 + CLIMB_COST, 1.0 + CLIMB_COST, 1.0, 1.0]);
+        assert!(find(4, 0, hill, open).unwrap().iter().all(|&(_, cost)| cost == 1.0));
+    }
+
+    #[test]
+    fn solid_cells_block_the_way() {
+        assert!(find(0, 10, flat, |x| x == 5).is_none());
+        assert!(find(0, 4, flat, |x| x == 5).is_some());
+        assert!(MoveTo::new(0, 10, flat, |x| x == 5).is_none());
+    }
+
+    #[test]
+    fn moves_as_far_as_the_time_allows() {
+        let mut m = MoveTo::new(0, 5, |x| if x >= 2 { 1 } else { 0 }, open).unwrap();
+        assert_eq!(m.update(0, 0.5, 1.0), 0);
+        assert_eq!(m.update(0, 0.5, 1.0), 1);
+        // The climb onto x 2 costs two steps
+        assert_eq!(m.update(1, 1.0, 1.0), 0);
+        assert_eq!(m.update(1, 1.0, 1.0), 1);
+        assert_eq!(m.update(2, 10.0, 1.0), 3);
+        assert!(m.done());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+
+use crate::input::Key;
+use crate::{presence, save, Mutes};
+
+/// World cells the camera moves on each arrow press, times the zoom
+pub const PAN_STEP: i32 = 4;
+
+/// Keys shown on the bottom line in photo mode before the time scale. The line isn't part of the photos.
+pub const HINT: &str = " Photo mode  [arrows] pan  [enter] save  [o] leave  [<>] time";
+
+/// Photo mode, where the HUD and bubbles are hidden and the camera is let go of the cat to pan around
+/// the scene freely, so it can be saved as a picture
+pub struct Photo {
+    /// World x of the left edge of the screen
+    pub scroll: i32,
+    /// Whether to save the next frame drawn
+    pub shoot: bool,
+}
+
+impl Photo {
+    /// Photo mode starting from where the camera is
+    pub fn new(scroll: i32) -> Self {
+        Self { scroll, shoot: false }
+    }
+
+    /// Move the camera by a number of world cells, not past the left edge of the world
+    pub fn pan(&mut self, cells: i32) {
+        self.scroll = (self.scroll + cells).max(0);
+    }
+
+    /// Folder the photos are saved in, which can be overridden with TN_PHOTOS
+    fn dir() -> Option<PathBuf> {
+        save::data_path("TN_PHOTOS", "photos")
+    }
+
+    /// Save a frame drawn as plain lines of colored text, which look like the scene again when printed
+    /// with cat
+    pub fn save(frame: &str) -> Result<PathBuf> {
+        let dir = Self::dir().ok_or_else(|| anyhow!("No folder for the photos"))?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("tngame-{}.txt", presence::now_ms()));
+        fs::write(&path, frame)?;
+        Ok(path)
+    }
+}
+
+/// Handle a key in photo mode, where the arrows pan the camera and enter saves the scene
+pub fn handle_key(mt: &mut Mutes, key: Key) {
+    let zoom = mt.camera.zoom;
+    let Some(photo) = &mut mt.ui.photo else { return; };
+    match key {
+        Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+        Key::Char('o') | Key::Esc => mt.ui.photo = None,
+        Key::Left => photo.pan(-PAN_STEP * zoom),
+        Key::Right => photo.pan(PAN_STEP * zoom),
+        Key::Enter => photo.shoot = true,
+        _ => (),
+    }
+}
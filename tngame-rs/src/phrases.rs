@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::clock::Phase;
+use crate::utils;
+
+/// A line the cat may say, the times of day it fits, or any time when empty, and how likely it is
+/// picked over the other lines that fit
+pub struct Phrase {
+    pub text: &'static str,
+    pub phases: &'static [Phase],
+    pub weight: u32,
+}
+
+const fn any(text: &'static str, weight: u32) -> Phrase {
+    Phrase { text, phases: &[], weight }
+}
+
+const fn at(phases: &'static [Phase], text: &'static str, weight: u32) -> Phrase {
+    Phrase { text, phases, weight }
+}
+
+const NIGHT: &[Phase] = &[Phase::Night];
+const DAWN: &[Phase] = &[Phase::Dawn];
+const DAY: &[Phase] = &[Phase::Dawn, Phase::Day];
+const DUSK: &[Phase] = &[Phase::Dusk];
+
+// The lines of each place
+const WELCOME: &[Phrase] = &[
+    at(DAY, "Welcome to my\nsnowy world!", 4),
+    at(DAWN, "Good morning!\nThe sun is up.", 2),
+    at(DAY, "What a bright\nsnowy day!", 2),
+    at(DUSK, "The sun is setting.\nWelcome anyway!", 3),
+    at(NIGHT, "It's late, everyone\nis asleep... shh!", 4),
+    at(NIGHT, "Brr, the nights\nare cold here.", 2),
+];
+
+const HOUSE: &[Phrase] = &[
+    any("I wonder what\nmy friends are doing.", 4),
+    at(DAY, "Home sweet home.\nFriends are nearby!", 2),
+    at(NIGHT, "The fire is still\nwarm inside.", 2),
+];
+
+const HOUSE_ALL_MET: &[Phrase] = &[
+    at(DAY, "Fox and Penguin are\nout in the snow today!", 4),
+    at(DUSK, "Fox and Penguin will\nbe home soon.", 2),
+    at(NIGHT, "Everyone is home,\nsnug and warm.", 3),
+    at(NIGHT, "Fox and Penguin are\nout in the snow today!", 1),
+];
+
+const CLIFF: &[Phrase] = &[
+    any("The cliff looks steep.\nBut I can't fly...", 4),
+    any("It's a long way down.\nBetter not look.", 2),
+    at(NIGHT, "The cliff is scarier\nin the dark...", 2),
+];
+
+const SOON: &[Phrase] = &[
+    any("What is this?", 4),
+    any("Coming soon...?\nWhat's coming?", 2),
+    at(NIGHT, "It glows a little\nat night.", 1),
+];
+
+const SLED: &[Phrase] = &[
+    any("A sled! Press [e]\nto ride down the hill.", 4),
+    at(DAY, "Perfect sledding weather!\nPress [e] to ride.", 2),
+    at(NIGHT, "Sledding in the dark?\nPress [e] if you dare.", 2),
+];
+
+const BENCH: &[Phrase] = &[
+    any("What a nice spot\nto rest my paws.", 4),
+    at(DAY, "Watching the snow\nfall is so relaxing.", 2),
+    at(DUSK, "The sky is turning\npink. Pretty...", 2),
+    at(NIGHT, "The lamp keeps me\ncompany at night.", 3),
+];
+
+/// Places the cat says something about when it is there
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Place {
+    /// Where the cat starts, before it moved
+    Welcome,
+    /// The house, before and after the cat met everyone
+    House,
+    HouseAllMet,
+    /// The cliff at the left edge of the world
+    Cliff,
+    /// The strange sign in the middle of nowhere
+    Soon,
+    /// The sled on top of the hill
+    Sled,
+    /// Sitting on a bench
+    Bench,
+}
+
+impl Place {
+    /// The lines the cat says at the place
+    fn pool(self) -> &'static [Phrase] {
+        match self {
+            Place::Welcome => WELCOME,
+            Place::House => HOUSE,
+            Place::HouseAllMet => HOUSE_ALL_MET,
+            Place::Cliff => CLIFF,
+            Place::Soon => SOON,
+            Place::Sled => SLED,
+            Place::Bench => BENCH,
+        }
+    }
+}
+
+/// Picks the lines the cat says at places, keeping a line for as long as the cat stays there so that
+/// coming back picks another one
+#[derive(Default)]
+pub struct Phrases {
+    picked: HashMap<Place, &'static str>,
+    shown: Vec<Place>,
+}
+
+impl Phrases {
+    /// The line the cat says at a place at a time of day
+    pub fn line(&mut self, place: Place, phase: Phase) -> &'static str {
+        self.shown.push(place);
+        let pool = place.pool();
+        let fits = |p: &Phrase| p.phases.is_empty() || p.phases.contains(&phase);
+        if let Some(text) = self.picked.get(&place).filter(|t| pool.iter().any(|p| p.text == **t && fits(p))) {
+            return text;
+        }
+        let fitting: Vec<&Phrase> = pool.iter().filter(|p| fits(p)).collect();
+        let total: u32 = fitting.iter().map(|p| p.weight).sum();
+        let mut roll = utils::rng().gen_range(0..total.max(1));
+        let text = fitting.iter().find(|p| {
+            if roll < p.weight { return true; }
+            roll -= p.weight;
+            false
+        }).map_or(pool[0].text, |p| p.text);
+        self.picked.insert(place, text);
+        text
+    }
+
+    /// Forget the lines of the places the cat wasn't at in this frame
+    pub fn end_frame(&mut self) {
+        let shown = std::mem::take(&mut self.shown);
+        self.picked.retain(|place, _| shown.contains(place));
+    }
+}
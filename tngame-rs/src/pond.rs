@@ -0,0 +1,47 @@
+use crate::render::Pixel;
+use crate::{utils, Mutes};
+
+/// Skating constants, gliding much further than on an ice patch
+pub const PUSH: f32 = 8.0; // Velocity added to the cat by a keypress on the pond in pixels per second
+pub const MAX_SPEED: f32 = 40.0; // Maximum skating velocity in pixels per second
+pub const FRICTION: f32 = 0.7; // Fraction of the skating velocity that remains after one second
+
+/// Character of the frozen pond surface
+pub const CHAR: char = '-';
+
+/// Seconds the cat can stay in the middle of a pond before the ice starts to crack, and seconds for
+/// the cracks to spread one more cell
+const CRACK_AFTER: f32 = 3.0;
+const CRACK_SPREAD: f32 = 0.6;
+
+/// Cells the cracks spread at most to each side of the cat
+const CRACK_REACH: i32 = 8;
+const CRACK_CHARS: &[char] = &['/', '\\', 'x', 'v'];
+
+/// Count the time the cat stays in the middle third of a pond, starting over once it leaves
+pub fn update(mt: &mut Mutes, cat_w: i32, dt: f32) {
+    let x = mt.world.x + cat_w / 2;
+    let middle = mt.world.map.pond_at(x, mt.world.tiles.w())
+        .is_some_and(|(start, w)| (start + w / 3..start + w - w / 3).contains(&x));
+    mt.world.linger = if middle { mt.world.linger + dt } else { 0.0 };
+}
+
+/// Draw the cracks spreading through the ice around the cat after it lingers in the middle of a
+/// pond. They only show, the ice never breaks.
+pub fn draw_cracks(mt: &mut Mutes, cat_w: i32, color: &'static str) {
+    if mt.world.linger < CRACK_AFTER { return; }
+    let reach = (((mt.world.linger - CRACK_AFTER) / CRACK_SPREAD) as i32 + 1).min(CRACK_REACH);
+
+    let center = mt.world.x + cat_w / 2;
+    let dim = mt.world.clock.is_night();
+    for x in center - reach..=center + reach {
+        // Only some cells crack, the same ones each time so the cracks don't flicker
+        let h = utils::hash(x as u32);
+        if h.is_multiple_of(3) || mt.world.map.pond_at(x, mt.world.tiles.w()).is_none() { continue; }
+
+        let (sx, tile) = (mt.screen_x(x), mt.world.tiles.tile(x));
+        let char = CRACK_CHARS[(h / 3) as usize % CRACK_CHARS.len()];
+        let y = mt.renderer.h - 1 - tile.height;
+        mt.renderer.put(sx, y, Pixel { color, char, dim });
+    }
+}
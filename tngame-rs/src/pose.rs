@@ -0,0 +1,36 @@
+use crate::clock::Phase;
+
+/// Hour the cat wakes up at after sleeping through the night, when cutscenes are off and the sleep
+/// cutscene can't jump there
+pub const WAKE_HOUR: f32 = 7.0;
+
+/// Shown next to the cat while it sleeps
+pub const SNORE: &str = "Zzz...";
+
+/// How the cat is resting, until it walks off
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pose {
+    Stand,
+    Sit,
+    // Paws tucked in like a loaf of bread
+    Loaf,
+    Sleep,
+}
+
+impl Pose {
+    /// The pose taken on a key, or standing up again when the cat is already in it
+    pub fn toggle(self, key: char) -> Option<Pose> {
+        let pose = match key {
+            'h' => Pose::Sit,
+            'l' => Pose::Loaf,
+            'n' => Pose::Sleep,
+            _ => return None,
+        };
+        Some(if self == pose { Pose::Stand } else { pose })
+    }
+
+    /// Whether falling asleep in a part of the day sleeps through to the morning, rather than a nap
+    pub fn sleeps_through(phase: Phase) -> bool {
+        matches!(phase, Phase::Dusk | Phase::Night)
+    }
+}
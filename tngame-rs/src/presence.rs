@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::emote::Emote;
+use crate::utils::{SystemTime, UNIX_EPOCH};
+use crate::{save, utils};
+
+/// Presence constants
+const SYNC_INTERVAL: f32 = 0.25; // Seconds between sharing this cat and reading the others
+const STALE_MS: u64 = 10_000; // Cats whose session hasn't shared anything for this long have left
+const FLOOD_LIMIT: usize = 3; // Chat messages allowed per flood window
+const FLOOD_WINDOW: f32 = 10.0; // Seconds of the flood window
+
+/// Prediction constants
+const MAX_EXTRAPOLATE: f32 = 0.5; // Seconds other cats keep walking with their last velocity without news
+const RECONCILE_RATE: f32 = 10.0; // How quickly a predicted cat is pulled to its shared position, per second
+const RECONCILE_SNAP: f32 = 8.0; // Distance in cells above which a predicted cat jumps to its shared position
+
+/// Colors of other visitors' cats, picked by hashing their session
+pub const PLAYER_COLORS: [&str; 5] = [
+    "\x1b[38;2;255;180;120m",
+    "\x1b[38;2;150;210;255m",
+    "\x1b[38;2;190;255;160m",
+    "\x1b[38;2;240;160;255m",
+    "\x1b[38;2;255;240;140m",
+];
+
+/// What a session shares about its cat with the other sessions of a server
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Presence {
+    pub name: String,
+    pub x: i32,
+    pub color: usize,
+    /// The last chat message, and the unix time in milliseconds it was said at
+    pub message: Option<(String, u64)>,
+    /// The last emote, and the unix time in milliseconds it was shown at
+    #[serde(default)]
+    pub emote: Option<(Emote, u64)>,
+    /// Cells per second the cat was walking at when it was shared
+    #[serde(default)]
+    pub vx: f32,
+    updated: u64,
+    /// Where the cat is drawn, predicted from its last shared position and velocity
+    #[serde(skip)]
+    pub shown_x: f32,
+    #[serde(skip)]
+    id: String,
+}
+
+impl Presence {
+    /// A cat that only this session walks around, drawn like the other visitors' cats
+    pub fn local(name: String, x: f32, color: usize, vx: f32, message: Option<(String, u64)>) -> Self {
+        Self { name, x: x.round() as i32, color, message, emote: None, vx, updated: now_ms(), shown_x: x, id: String::new() }
+    }
+}
+
+/// Current unix time in milliseconds
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+/// The cats of other visitors in server mode. Every session shares its cat in a file of a common
+/// folder, like the leaderboard, and reads the others' files every few frames.
+pub struct Visitors {
+    dir: PathBuf,
+    id: String,
+    pub color: usize,
+    pub others: Vec<Presence>,
+    last_sync: f32,
+    last_x: i32,
+    sent: VecDeque<f32>,
+}
+
+impl Visitors {
+    /// Location of the shared folder, which can be overridden with TN_PRESENCE
+    fn dir() -> Option<PathBuf> {
+        save::data_path("TN_PRESENCE", "presence")
+    }
+
+    pub fn open() -> Option<Self> {
+        let id = format!("{}-{}", std::process::id(), now_ms());
+        let color = utils::hash(id.bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32))) as usize % PLAYER_COLORS.len();
+        Some(Self { dir: Self::dir()?, id, color, others: Vec::new(), last_sync: f32::MIN, last_x: 0, sent: VecDeque::new() })
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(format!("{}.json", self.id))
+    }
+
+    /// Share this cat and read the others every few frames, returning the names of the visitors who
+    /// just joined
+    pub fn sync(&mut self, time: f32, name: &str, x: i32, message: Option<(String, u64)>, emote: Option<(Emote, u64)>) -> Vec<String> {
+        if time - self.last_sync < SYNC_INTERVAL { return Vec::new(); }
+        let vx = if self.last_sync == f32::MIN { 0.0 } else { (x - self.last_x) as f32 / (time - self.last_sync) };
+        self.last_sync = time;
+        self.last_x = x;
+
+        let me = Presence { name: name.to_string(), x, color: self.color, message, emote, vx, updated: now_ms(), shown_x: 0.0, id: self.id.clone() };
+        if let Err(e) = self.write(&me) {
+            log::warn!("Failed to share the cat: {}", e);
+        }
+
+        let now = now_ms();
+        let own = self.path();
+        let others: Vec<Presence> = fs::read_dir(&self.dir).into_iter().flatten().flatten()
+            .map(|e| e.path())
+            .filter(|p| *p != own && p.extension().is_some_and(|e| e == "json"))
+            .filter_map(|p| {
+                let mut o = serde_json::from_str::<Presence>(&fs::read_to_string(&p).ok()?).ok()?;
+                o.id = p.file_stem()?.to_string_lossy().into_owned();
+                Some(o)
+            })
+            .filter(|p| now.saturating_sub(p.updated) < STALE_MS)
+            .map(|mut o| {
+                // Keep drawing known cats where they were predicted to be, new ones where they are
+                o.shown_x = self.others.iter().find(|old| old.id == o.id).map_or(o.x as f32, |old| old.shown_x);
+                o
+            })
+            .collect();
+        let joined = others.iter().filter(|o| !self.others.iter().any(|old| old.id == o.id)).map(|o| o.name.clone()).collect();
+        self.others = others;
+        joined
+    }
+
+    /// Keep the other cats walking between updates, since they are only shared a few times a second,
+    /// and pull them towards where their sessions said they are
+    pub fn predict(&mut self, dt: f32) {
+        let now = now_ms();
+        for o in &mut self.others {
+            let age = now.saturating_sub(o.updated) as f32 / 1000.0;
+            if age < MAX_EXTRAPOLATE {
+                o.shown_x += o.vx * dt;
+            }
+            let target = o.x as f32 + o.vx * age.min(MAX_EXTRAPOLATE);
+            let error = target - o.shown_x;
+            if error.abs() > RECONCILE_SNAP {
+                o.shown_x = target;
+            } else {
+                o.shown_x += error * (1.0 - (-RECONCILE_RATE * dt).exp());
+            }
+        }
+    }
+
+    /// Write this cat's file in one rename, so that other sessions never read half of it
+    fn write(&self, me: &Presence) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!("{}.tmp", self.id));
+        fs::write(&tmp, serde_json::to_string(me)?)?;
+        fs::rename(tmp, self.path())?;
+        Ok(())
+    }
+
+    /// Whether another chat message can be sent without flooding the others
+    pub fn allow_message(&mut self, time: f32) -> bool {
+        while self.sent.front().is_some_and(|t| time - t > FLOOD_WINDOW) {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= FLOOD_LIMIT { return false; }
+        self.sent.push_back(time);
+        true
+    }
+
+    /// Remove this cat from the shared folder when the visitor leaves
+    pub fn leave(&self) {
+        let _ = fs::remove_file(self.path());
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+/// Time a frame may take to be delivered before the connection counts as slow, and under which it
+/// counts as fast again
+const SLOW: Duration = Duration::from_millis(40);
+const FAST: Duration = Duration::from_millis(10);
+
+/// Weight of the newest frame in the smoothed delivery time
+const SMOOTHING: f32 = 0.1;
+
+/// Frames to wait after changing the quality before changing it again, so the delivery time can settle
+const SETTLE_FRAMES: u32 = 40;
+
+/// Share of the particles drawn and how many frame times each frame is shown for, at each level from
+/// full quality down
+const LEVELS: &[(f32, u32)] = &[(1.0, 1), (0.5, 2), (0.25, 3)];
+
+/// Draws less when frames take too long to reach the visitor, like over a slow SSH connection, and
+/// more again once they arrive in time
+#[derive(Default)]
+pub struct Quality {
+    level: usize,
+    /// Smoothed time in seconds writing a frame took
+    delivery: f32,
+    /// Frames since the level last changed
+    settled: u32,
+}
+
+impl Quality {
+    /// Record how long writing a frame took, returning the new level if it changed
+    pub fn observe(&mut self, took: Duration) -> Option<usize> {
+        self.delivery += (took.as_secs_f32() - self.delivery) * SMOOTHING;
+        self.settled += 1;
+        if self.settled < SETTLE_FRAMES { return None; }
+
+        let level = if self.delivery > SLOW.as_secs_f32() { (self.level + 1).min(LEVELS.len() - 1) }
+            else if self.delivery < FAST.as_secs_f32() { self.level.saturating_sub(1) }
+            else { self.level };
+        if level == self.level { return None; }
+        self.level = level;
+        self.settled = 0;
+        Some(level)
+    }
+
+    /// Share of the particles to draw
+    pub fn particles(&self) -> f32 {
+        LEVELS[self.level].0
+    }
+
+    /// Time between frames at this level
+    pub fn frame_time(&self, frame: Duration) -> Duration {
+        frame * LEVELS[self.level].1
+    }
+}
@@ -0,0 +1,91 @@
+use crate::input::Key;
+use crate::{cowsay, Mutes};
+
+/// Text of the bulletin boards that don't name a file of their own
+pub const DEFAULT: &str = include_str!("../maps/bulletin.txt");
+
+/// A line of the text that starts a new page
+const PAGE_BREAK: &str = "---";
+
+/// Cells between the frame and the text on every side
+const PADDING: i32 = 2;
+
+/// A text read page by page on the whole screen, like the notes pinned to a bulletin board. The text
+/// is split into pages that fit the screen when it is drawn, so that resizing the terminal keeps it
+/// readable.
+pub struct Reader {
+    title: String,
+    text: String,
+    pub page: usize,
+}
+
+impl Reader {
+    pub fn new(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.trim_matches('\n').to_string(), page: 0 }
+    }
+
+    /// The lines of every page, wrapped to w columns with at most h lines on a page
+    fn pages(&self, w: i32, h: i32) -> Vec<Vec<String>> {
+        let (w, h) = (w.max(1) as usize, h.max(1) as usize);
+        let mut pages = Vec::new();
+        for part in self.text.split(&format!("\n{}\n", PAGE_BREAK)) {
+            let lines: Vec<String> = part.lines().flat_map(|l| cowsay::wrap(l, w).lines().map(str::to_string).collect::<Vec<_>>()).collect();
+            pages.extend(lines.chunks(h).map(<[String]>::to_vec));
+        }
+        if pages.is_empty() { pages.push(Vec::new()); }
+        pages
+    }
+
+    /// Columns and lines of text a page has on a screen w by h cells, inside the frame and the padding
+    /// and above the line of hints
+    fn page_size(w: i32, h: i32) -> (i32, i32) {
+        (w - 2 - PADDING * 2, h - 3 - PADDING)
+    }
+
+    /// Turn to the next page, or the one before when dir is negative, on a screen w by h cells
+    pub fn flip(&mut self, dir: i32, w: i32, h: i32) {
+        let (pw, ph) = Self::page_size(w, h);
+        let last = self.pages(pw, ph).len() - 1;
+        self.page = self.page.saturating_add_signed(dir as isize).min(last);
+    }
+
+    /// Draw the page being read over the whole screen, in a frame with the title at the top and the
+    /// page number at the bottom
+    pub fn draw(&self, mt: &mut Mutes, color: &'static str, frame: &'static str) {
+        let (w, h) = (mt.renderer.w, mt.renderer.h);
+        let (pw, ph) = Self::page_size(w, h);
+        let pages = self.pages(pw, ph);
+        let page = self.page.min(pages.len() - 1);
+
+        mt.renderer.clear();
+        let [tl, tr, bl, br, hor, ver] = mt.bubble_style.chars();
+        let rule: String = std::iter::repeat_n(hor, (w - 2).max(0) as usize).collect();
+        mt.renderer.put_str(0, 0, &format!("{}{}{}", tl, rule, tr), frame);
+        mt.renderer.put_str(0, h - 1, &format!("{}{}{}", bl, rule, br), frame);
+        for y in 1..h - 1 {
+            mt.renderer.put_str(0, y, &ver.to_string(), frame);
+            mt.renderer.put_str(w - 1, y, &ver.to_string(), frame);
+        }
+        let title = format!(" {} ", self.title);
+        mt.renderer.put_str((w - title.chars().count() as i32) / 2, 0, &title, color);
+
+        for (i, line) in pages[page].iter().enumerate() {
+            mt.renderer.put_str(1 + PADDING, 1 + PADDING / 2 + i as i32, line, color);
+        }
+
+        let hints = format!("[←] [→] page {}/{}  [esc] close", page + 1, pages.len());
+        mt.renderer.put_str((w - hints.chars().count() as i32) / 2, h - 2, &hints, frame);
+    }
+}
+
+/// Handle a key while reading, where walking left or right flips the pages
+pub fn handle_key(mt: &mut Mutes, key: Key, dir: i32) {
+    let (w, h) = (mt.renderer.w, mt.renderer.h);
+    let Some(reader) = &mut mt.ui.reader else { return; };
+    match key {
+        Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+        Key::Char('e') | Key::Enter | Key::Esc => mt.ui.reader = None,
+        _ if dir != 0 => reader.flip(dir, w, h),
+        _ => (),
+    }
+}
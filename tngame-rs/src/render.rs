@@ -0,0 +1,358 @@
+use anyhow::Result;
+
+use crate::color::{self, Gradient, Rgb};
+use crate::cowsay::BubbleStyle;
+use crate::layout::{self, Rect};
+use crate::term::{self, Colors};
+use crate::theme::{self, Theme};
+use crate::{AsciiArt, RESET};
+
+/// A clickable OSC 8 hyperlink over a span of text on the screen
+struct Link {
+    x: i32,
+    y: i32,
+    len: i32,
+    url: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Pixel {
+    pub color: &'static str,
+    pub char: char,
+    /// Whether the pixel is drawn faint, like shadows. The high contrast theme draws it like any other.
+    pub dim: bool,
+}
+
+/// Escape codes making the text faint, and making it normal again
+const DIM: &str = "\x1b[2m";
+const UNDIM: &str = "\x1b[22m";
+
+/// The screen the game is drawn on: a buffer of pixels in screen space, and how it is turned into
+/// escape codes for the terminal
+pub struct Renderer {
+    pub w: i32,
+    pub h: i32,
+    buf: Vec<Vec<Option<Pixel>>>,
+    // Terminal position of the top left corner of the buffer, and the border around it when letterboxed
+    origin: (i32, i32),
+    border: String,
+    // Escape codes clearing the screen before every frame
+    clear: String,
+    // Whether background colors are left out so the terminal's background shows through
+    pub transparent_bg: bool,
+
+    // Colors the terminal can show, and the theme the game's colors are shown in
+    pub colors: Colors,
+    pub theme: Theme,
+
+    // Hyperlinks drawn in the current frame, if the terminal supports them
+    pub hyperlinks: bool,
+    links: Vec<Link>,
+}
+
+impl Renderer {
+    /// Screen of a terminal size. Very large terminals draw the world in a box of a fixed size, with a
+    /// border of a color around it.
+    pub fn new(width: i32, height: i32, border: &str) -> Self {
+        let clear = term::clear_screen(height);
+        let letterbox = layout::letterbox(width, height);
+        let border = letterbox.map_or(String::new(), |r| draw_border(r, border));
+        let (w, h) = letterbox.map_or((width, height), |r| (r.w, r.h));
+        Self {
+            w,
+            h,
+            buf: vec![vec![None; w as usize]; h as usize],
+            origin: letterbox.map_or((0, 0), |r| (r.x, r.y)),
+            border,
+            clear,
+            transparent_bg: false,
+            colors: Colors::from_env(),
+            theme: Theme::from_env(),
+            hyperlinks: term::supports_hyperlinks(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Set a pixel at screen x and y, ignoring positions outside the screen
+    pub fn put(&mut self, x: i32, y: i32, pixel: Pixel) {
+        if 0 <= x && x < self.w && 0 <= y && y < self.h {
+            self.buf[y as usize][x as usize] = Some(pixel);
+        }
+    }
+
+    /// Draw a single line of text at screen x and y
+    pub fn put_str(&mut self, x: i32, y: i32, text: &str, color: &'static str) {
+        for (i, c) in text.chars().enumerate() {
+            self.put(x + i as i32, y, Pixel { color, char: c, dim: false });
+        }
+    }
+
+    /// Draw a single line of text at screen x and y that links to a url on supporting terminals
+    pub fn put_link(&mut self, x: i32, y: i32, text: &str, url: &str, color: &'static str) {
+        self.put_str(x, y, text, color);
+        if !self.hyperlinks || !(0..self.h).contains(&y) { return; }
+
+        // Only link the part of the text that is on screen
+        let start = x.max(0);
+        let end = (x + text.chars().count() as i32).min(self.w);
+        if start < end {
+            self.links.push(Link { x: start, y, len: end - start, url: url.to_string() });
+        }
+    }
+
+    /// Draw an ascii art at screen x and y
+    pub fn put_ascii(&mut self, art: &AsciiArt, x: i32, y: i32, color: &'static str) {
+        self.put_ascii_with(art, x, y, |_, _| color);
+    }
+
+    /// Draw an ascii art at screen x and y in a gradient
+    pub fn put_ascii_gradient(&mut self, art: &AsciiArt, x: i32, y: i32, gradient: Gradient) {
+        self.put_ascii_with(art, x, y, |j, i| gradient.color(j, i, art.w, art.h));
+    }
+
+    /// Draw an ascii art at screen x and y, coloring every character by its column and line in the art
+    pub fn put_ascii_with(&mut self, art: &AsciiArt, x: i32, y: i32, color: impl Fn(i32, i32) -> &'static str) {
+        // If the ascii art is out of bounds, don't draw it
+        if (x + art.w) < 0 || x > self.w || (y + art.h) < 0 || y > self.h {
+            return;
+        }
+
+        // Loop through all lines in the ascii art
+        for (i, line) in art.art.lines().enumerate() {
+            let first_non_space = line.chars().position(|c| c != ' ').unwrap_or(0);
+            // Loop through all characters in the line
+            for (j, c) in line.chars().enumerate() {
+                if j < first_non_space { continue; }
+                // Draw the character in the buffer
+                self.put(x + j as i32, y + i as i32, Pixel { color: color(j as i32, i as i32), char: c, dim: false });
+            }
+        }
+    }
+
+    /// Draw the buffer as plain lines of colored text, without moving the cursor, so it can be printed
+    /// anywhere text goes
+    pub fn draw_plain(&mut self) -> String {
+        let out = self.plain();
+        self.clear();
+        out
+    }
+
+    /// Blank out everything drawn so far in this frame
+    pub fn clear(&mut self) {
+        for row in &mut self.buf {
+            row.fill(None);
+        }
+        self.links.clear();
+    }
+
+    /// The buffer drawn so far as plain lines of colored text, leaving it as it is
+    pub fn plain(&self) -> String {
+        let mut out = String::with_capacity((self.w * self.h) as usize);
+        for row in &self.buf {
+            let (mut last_color, mut last_dim) = ("", false);
+            let mut line = String::new();
+            let mut spaces = 0;
+            for p in row {
+                match p {
+                    None => spaces += 1,
+                    Some(p) => {
+                        line.extend(std::iter::repeat_n(' ', spaces));
+                        spaces = 0;
+                        if p.color != last_color {
+                            line.push_str(&self.colors.convert(&self.theme.apply(p.color)));
+                            last_color = p.color;
+                        }
+                        if p.dim != last_dim {
+                            line.push_str(dim_code(self.theme, p.dim));
+                            last_dim = p.dim;
+                        }
+                        line.push(p.char);
+                    }
+                }
+            }
+            out.push_str(&line);
+            if !last_color.is_empty() { out.push_str(RESET); }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Blank out a share of the cells from 0 to 1, scattered over the screen and the same ones every
+    /// frame, so that the screen dissolves into the background and back
+    pub fn dissolve(&mut self, amount: f32) {
+        if amount <= 0.0 { return; }
+        for (y, row) in self.buf.iter_mut().enumerate() {
+            for (x, p) in row.iter_mut().enumerate() {
+                let mut h = (x as u32).wrapping_mul(0x9e37_79b1) ^ (y as u32).wrapping_mul(0x85eb_ca77);
+                h ^= h >> 15;
+                h = h.wrapping_mul(0x2c1b_3c6d);
+                h ^= h >> 12;
+                if (h % 1000) as f32 / 1000.0 < amount {
+                    *p = None;
+                }
+            }
+        }
+    }
+
+    /// Brighten the pixels around screen x and y with a light of a color, by strength right at it and
+    /// fading out to nothing radius columns away. Cells are about twice as tall as they are wide, so
+    /// rows count twice.
+    pub fn light(&mut self, x: i32, y: i32, radius: f32, light: Rgb, strength: f32) {
+        let r = radius.ceil() as i32;
+        for py in (y - r / 2).max(0)..(y + r / 2 + 1).min(self.h) {
+            for px in (x - r).max(0)..(x + r + 1).min(self.w) {
+                let Some(p) = &mut self.buf[py as usize][px as usize] else { continue; };
+                let Some(c) = color::parse(p.color) else { continue; };
+                let (dx, dy) = ((px - x) as f32, ((py - y) * 2) as f32);
+                let falloff = 1.0 - (dx * dx + dy * dy).sqrt() / radius;
+                if falloff <= 0.0 { continue; }
+                p.color = color::code(color::brighten(c, light, strength * falloff * falloff));
+            }
+        }
+    }
+
+    /// Fade out the pixels far from screen x and y as if hidden by thick snow: faint past radius
+    /// columns away and gone past twice as far. Rows count twice like for lights.
+    pub fn fog(&mut self, x: i32, y: i32, radius: f32) {
+        for (py, row) in self.buf.iter_mut().enumerate() {
+            for (px, p) in row.iter_mut().enumerate() {
+                let (dx, dy) = ((px as i32 - x) as f32, ((py as i32 - y) * 2) as f32);
+                let dist = (dx * dx + dy * dy).sqrt();
+                if dist > radius * 2.0 { *p = None; }
+                else if dist > radius { if let Some(p) = p { p.dim = true; } }
+            }
+        }
+    }
+
+    /// Pixels drawn so far in this frame, by row
+    #[cfg_attr(not(feature = "ratatui"), allow(dead_code))]
+    pub fn pixels(&self) -> &[Vec<Option<Pixel>>] {
+        &self.buf
+    }
+
+    /// Move everything in the buffer by an offset, dropping what moves off the screen
+    pub fn shift_buf(&mut self, dx: i32, dy: i32) {
+        let mut shifted = vec![vec![None; self.w as usize]; self.h as usize];
+        for (y, row) in self.buf.iter_mut().enumerate() {
+            for (x, p) in row.iter_mut().enumerate() {
+                let (sx, sy) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.w).contains(&sx) && (0..self.h).contains(&sy) {
+                    shifted[sy as usize][sx as usize] = p.take();
+                }
+            }
+        }
+        self.buf = shifted;
+        for l in &mut self.links {
+            (l.x, l.y) = (l.x + dx, l.y + dy);
+        }
+    }
+
+    /// Draw the buffer to the screen, moved by the offset of the screen shake
+    pub fn draw_buf(&mut self, (dx, dy): (i32, i32)) -> Result<String> {
+        // Project the buffer with the offset of the screen shake
+        if (dx, dy) != (0, 0) {
+            self.shift_buf(dx, dy);
+        }
+
+        // Create a buffer string
+        let mut buf_str = String::with_capacity((self.w * self.h) as usize);
+
+        // Keep the last color, and whether the last pixel was faint
+        let mut last_color: &str = "";
+        let mut last_dim = false;
+
+        // Keep the current cursor, which starts outside the buffer when it is letterboxed
+        let mut cursor = if self.origin == (0, 0) { (0, 0) } else { (usize::MAX, usize::MAX) };
+
+        // No optimization method: clear the screen, in black for the high contrast theme
+        if self.theme == Theme::HighContrast && !self.transparent_bg {
+            buf_str.push_str(theme::BLACK_BACKGROUND);
+        }
+        buf_str.push_str(&self.clear);
+
+        // Loop through all pixels in the buffer
+        for y in 0..self.h as usize {
+            let mut link_end = None;
+            for x in 0..self.w as usize {
+                // Get the pixel
+                let ppr = &mut self.buf[y][x];
+
+                // If the current pixel isn't empty
+                if let Some(p) = ppr {
+                    if cursor != (x, y) {
+                        if cursor.1 == y && x - cursor.0 < 8 {
+                            // If the cursor is on the same line and with x distance less than 8, use spaces
+                            for _ in 0..(x - cursor.0) {
+                                buf_str.push(' ');
+                            }
+                        } else {
+                            // Jump to the pixel position
+                            buf_str.push_str(&goto((x as i32 + self.origin.0) as u16 + 1, (y as i32 + self.origin.1) as u16 + 1));
+                        }
+                    };
+                    cursor = (x + 1, y);
+
+                    if p.color != last_color {
+                        // Set the color
+                        let code = self.colors.convert(&self.theme.apply(p.color));
+                        buf_str.push_str(&if self.transparent_bg { term::without_background(&code) } else { code });
+                        last_color = p.color;
+                    }
+                    if p.dim != last_dim {
+                        buf_str.push_str(dim_code(self.theme, p.dim));
+                        last_dim = p.dim;
+                    }
+
+                    // Open a hyperlink starting at this pixel
+                    if let Some(l) = self.links.iter().find(|l| (l.x, l.y) == (x as i32, y as i32)) {
+                        buf_str.push_str(&format!("\x1b]8;;{}\x1b\\", l.url));
+                        link_end = Some(x as i32 + l.len - 1);
+                    }
+
+                    // Draw the pixel
+                    buf_str.push(p.char);
+
+                    // Close the hyperlink after its last pixel
+                    if link_end == Some(x as i32) {
+                        buf_str.push_str("\x1b]8;;\x1b\\");
+                        link_end = None;
+                    }
+
+                    // Clear the pixel
+                    *ppr = None;
+                }
+            }
+        }
+
+        // Reset the color
+        buf_str.push_str(&self.border);
+        buf_str.push_str(RESET);
+        self.links.clear();
+
+        Ok(buf_str)
+    }
+}
+
+/// Escape code making the following text faint or normal again, which the high contrast theme leaves out
+fn dim_code(theme: Theme, dim: bool) -> &'static str {
+    if theme == Theme::HighContrast { return ""; }
+    if dim { DIM } else { UNDIM }
+}
+
+/// Escape code moving the cursor to a column and a row, counted from 1
+fn goto(x: u16, y: u16) -> String {
+    format!("\x1b[{};{}H", y, x)
+}
+
+/// Escape codes drawing a border around a letterboxed rectangle of the terminal
+fn draw_border(r: Rect, color: &str) -> String {
+    let [tl, tr, bl, br, h, v] = BubbleStyle::Rounded.chars();
+    let horizontal: String = std::iter::repeat_n(h, r.w as usize).collect();
+    let mut s = String::from(color);
+    s.push_str(&format!("{}{}{}{}", goto(r.x as u16, r.y as u16), tl, horizontal, tr));
+    for y in r.y..r.y + r.h {
+        s.push_str(&format!("{}{}{}{}", goto(r.x as u16, y as u16 + 1), v, goto((r.x + r.w) as u16 + 1, y as u16 + 1), v));
+    }
+    s.push_str(&format!("{}{}{}{}", goto(r.x as u16, (r.y + r.h) as u16 + 1), bl, horizontal, br));
+    s
+}
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::input::KeyEvent;
+use crate::toast;
+
+/// First line of an input recording, with what it takes to replay it the same way
+#[derive(Serialize, Deserialize)]
+pub struct Header {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    /// Code of the world the recording was made in, when it wasn't the built-in one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world: Option<String>,
+    /// Unix time in milliseconds the recording started at, which the wall-clock time is counted from
+    /// by frames while recording and replaying
+    #[serde(default)]
+    pub started: u64,
+    /// Time of day in hours and the date of the seasonal events when the recording started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// A key event handled right before a frame
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub frame: u64,
+    pub time: f32,
+    pub event: KeyEvent,
+    pub releases: bool,
+}
+
+/// Writes the handled key events to a file as json lines
+pub struct Recorder {
+    pub started: u64,
+    file: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, header: &Header) -> Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        Ok(Self { started: header.started, file })
+    }
+
+    /// Write a record, flushing it right away so that a crash still leaves a complete recording
+    pub fn record(&mut self, record: &Record) {
+        let written = serde_json::to_string(record).map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(self.file, "{}", line)?))
+            .and_then(|_| Ok(self.file.flush()?));
+        if let Err(e) = written {
+            log::warn!("Failed to record the input: {}", e);
+            toast::report("Couldn't record the input");
+        }
+    }
+}
+
+/// Key events read from a recording, waiting for the frame they were handled at
+pub struct Playback {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    pub world: Option<String>,
+    pub started: u64,
+    pub hours: Option<f32>,
+    pub date: Option<String>,
+    records: VecDeque<Record>,
+}
+
+impl Playback {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: Header = serde_json::from_str(&lines.next().ok_or_else(|| anyhow!("Empty recording"))??)?;
+        let records = lines.map(|l| Ok(serde_json::from_str(&l?)?)).collect::<Result<_>>()?;
+        Ok(Self {
+            seed: header.seed, width: header.width, height: header.height, world: header.world,
+            started: header.started, hours: header.hours, date: header.date, records,
+        })
+    }
+
+    /// Take the records due at a frame
+    pub fn due(&mut self, frame: u64) -> Vec<Record> {
+        let n = self.records.iter().take_while(|r| r.frame <= frame).count();
+        self.records.drain(..n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, Kind};
+
+    #[test]
+    fn playback_reads_back_what_was_recorded() {
+        let path = std::env::temp_dir().join(format!("tngame-replay-{}.jsonl", std::process::id()));
+        let header = Header {
+            seed: 42, width: 80, height: 24, world: Some("3F9KQ27M".to_string()),
+            started: 1_700_000_000_000, hours: Some(18.5), date: Some("12-24".to_string()),
+        };
+        let mut shifted = KeyEvent::new(Key::Char('D'), Kind::Press);
+        shifted.shift = true;
+        let events = [
+            (3, KeyEvent::new(Key::Right, Kind::Press), true),
+            (3, KeyEvent::new(Key::Right, Kind::Repeat), true),
+            (9, KeyEvent::new(Key::Right, Kind::Release), true),
+            (20, shifted, false),
+        ];
+        let mut recorder = Recorder::create(&path, &header).unwrap();
+        for (frame, event, releases) in events {
+            recorder.record(&Record { frame, time: frame as f32 / 20.0, event, releases });
+        }
+        drop(recorder);
+
+        let mut playback = Playback::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((playback.seed, playback.width, playback.height), (42, 80, 24));
+        assert_eq!(playback.world.as_deref(), Some("3F9KQ27M"));
+        assert_eq!((playback.started, playback.hours, playback.date.as_deref()), (1_700_000_000_000, Some(18.5), Some("12-24")));
+
+        let mut replayed = Vec::new();
+        for frame in 0..30 {
+            for r in playback.due(frame) {
+                assert_eq!(r.frame, frame);
+                replayed.push((r.frame, r.event, r.releases));
+            }
+        }
+        assert_eq!(replayed, events);
+    }
+
+    #[test]
+    fn old_headers_start_the_clock_at_zero() {
+        let header: Header = serde_json::from_str(r#"{"seed": 1, "width": 80, "height": 24}"#).unwrap();
+        assert_eq!((header.started, header.hours, header.date), (0, None, None));
+    }
+
+    #[test]
+    fn rejects_empty_recordings() {
+        let path = std::env::temp_dir().join(format!("tngame-replay-empty-{}.jsonl", std::process::id()));
+        File::create(&path).unwrap();
+        let loaded = Playback::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+}
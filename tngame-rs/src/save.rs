@@ -0,0 +1,173 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::toast;
+
+/// Version of the save format, raised with a migration whenever the format changes in a way that old
+/// saves wouldn't load into
+const VERSION: u32 = 1;
+
+/// Migrations of the save from each version to the next, starting from saves that have no version
+const MIGRATIONS: [fn(&mut Value); VERSION as usize] = [
+    // Saves from before versioning only hold the NPCs met, which load as they are
+    |_| (),
+];
+
+/// Progress that is kept between visits
+#[derive(Serialize, Deserialize)]
+pub struct Save {
+    /// Version of the format the save is in
+    #[serde(default)]
+    pub version: u32,
+    /// Names of the NPCs the cat has talked to
+    #[serde(default)]
+    pub met: BTreeSet<String>,
+    /// Names of the cutscenes already watched
+    #[serde(default)]
+    pub seen: BTreeSet<String>,
+    /// Names of the items bought in the shop, and of the ones the cat is wearing
+    #[serde(default)]
+    pub owned: BTreeSet<String>,
+    #[serde(default)]
+    pub wearing: BTreeSet<String>,
+    /// Unix seconds each garden plot was planted at, or none while it is empty
+    #[serde(default)]
+    pub garden: Vec<Option<u64>>,
+    /// Keys of the signposts and bulletin boards already read
+    #[serde(default)]
+    pub read: BTreeSet<String>,
+    /// Unix seconds the last visit started at, to tell what grew in the garden since
+    #[serde(default)]
+    pub visited: u64,
+}
+
+impl Default for Save {
+    fn default() -> Self {
+        Self { version: VERSION, met: BTreeSet::new(), seen: BTreeSet::new(), owned: BTreeSet::new(), wearing: BTreeSet::new(), garden: Vec::new(), read: BTreeSet::new(), visited: 0 }
+    }
+}
+
+/// Location of a data file, which is the path in the environment variable if set, or a file in the
+/// tngame folder of the user's data directory
+pub fn data_path(var: &str, file: &str) -> Option<PathBuf> {
+    if let Ok(path) = env::var(var) {
+        return Some(PathBuf::from(path));
+    }
+    let data = env::var("XDG_DATA_HOME").map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok()?;
+    Some(data.join("tngame").join(file))
+}
+
+impl Save {
+    /// Location of the save file, which can be overridden with TN_SAVE
+    pub fn path() -> Option<PathBuf> {
+        data_path("TN_SAVE", "save.json")
+    }
+
+    /// Load the save file, starting fresh if it doesn't exist or can't be read. A save that can't be
+    /// read is kept next to it, so starting fresh doesn't overwrite the progress in it.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else { return Self::default(); };
+        match fs::read_to_string(&path) {
+            Ok(json) => Self::parse(&json).inspect(|save| if save.newer() {
+                log::warn!("The save file {} is from a newer version of the game, so it won't be written", path.display());
+                toast::report("The save is from a newer tngame, so progress isn't saved");
+            }).unwrap_or_else(|e| {
+                log::warn!("Failed to parse the save file {}: {}", path.display(), e);
+                toast::report("Couldn't read the save file, starting fresh");
+                let backup = path.with_extension("json.bak");
+                if let Err(e) = fs::copy(&path, &backup) {
+                    log::warn!("Failed to keep the save file in {}: {}", backup.display(), e);
+                }
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse a save of any version, migrating it to the current one. A save from a newer version keeps
+    /// its version, since what this version doesn't know of in it is dropped.
+    fn parse(json: &str) -> Result<Self> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+        for migrate in MIGRATIONS.iter().skip(version) {
+            migrate(&mut value);
+        }
+        let mut save: Self = serde_json::from_value(value)?;
+        save.version = save.version.max(VERSION);
+        Ok(save)
+    }
+
+    /// Whether the save is from a newer version of the game, which writing it would lose data of
+    pub fn newer(&self) -> bool {
+        self.version > VERSION
+    }
+
+    pub fn write(&self) -> Result<()> {
+        self.write_to(&Self::path().ok_or_else(|| anyhow!("No location for the save file"))?)
+    }
+
+    /// Write the save in one rename, so a crash halfway through leaves the last one intact. A save from
+    /// a newer version is never written, so that its data isn't lost.
+    fn write_to(&self, path: &Path) -> Result<()> {
+        if self.newer() { bail!("The save file is from a newer version of the game"); }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_saves_migrate() {
+        let save = Save::parse(r#"{"met": ["Fox"]}"#).unwrap();
+        assert_eq!(save.version, VERSION);
+        assert!(save.met.contains("Fox"));
+        assert!(save.seen.is_empty() && save.garden.is_empty());
+    }
+
+    #[test]
+    fn current_saves_round_trip() {
+        let mut save = Save::default();
+        save.met.insert("Penguin".to_string());
+        save.owned.insert("scarf".to_string());
+        save.wearing.insert("scarf".to_string());
+        save.garden = vec![Some(1_700_000_000), None];
+        save.visited = 1_700_000_500;
+
+        let path = std::env::temp_dir().join(format!("tngame-save-{}.json", std::process::id()));
+        save.write_to(&path).unwrap();
+        let loaded = Save::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&save).unwrap());
+    }
+
+    #[test]
+    fn newer_saves_are_left_alone() {
+        let json = format!(r#"{{"version": {}, "met": ["Fox"], "pets": 12}}"#, VERSION + 1);
+        let path = std::env::temp_dir().join(format!("tngame-newer-save-{}.json", std::process::id()));
+        fs::write(&path, &json).unwrap();
+
+        let save = Save::parse(&json).unwrap();
+        assert!(save.newer());
+        assert!(save.met.contains("Fox"));
+        assert!(save.write_to(&path).is_err());
+        let kept = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(kept, json);
+    }
+}
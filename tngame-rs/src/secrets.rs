@@ -0,0 +1,143 @@
+use crate::color::{self, Direction, Gradient};
+use crate::input::Key;
+use crate::toast::Kind as ToastKind;
+use crate::{AsciiArt, Consts, Mutes, HUD_HEIGHT};
+
+/// UFO constants
+const UFO_ART: &str = r#"
+   ___
+ _/ o \_
+(_______)
+ ' ' ' '"#;
+const COLOR_UFO: &str = "\x1b[38;2;150;255;170m";
+const UFO_TIME: f32 = 8.0; // Seconds the UFO takes to fly across the screen
+
+/// Times per second the rainbow cat goes through the rainbow
+const NYAN_SPEED: f32 = 0.5;
+
+/// Easter eggs hidden behind secret codes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Egg {
+    Ufo,
+    Nyan,
+}
+
+impl Egg {
+    pub const ALL: [Egg; 2] = [Egg::Ufo, Egg::Nyan];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Egg::Ufo => "UFO",
+            Egg::Nyan => "Nyan cat",
+        }
+    }
+}
+
+/// The codes and the easter eggs they unlock
+const CODES: &[(&[Key], Egg)] = &[
+    (&[Key::Up, Key::Up, Key::Down, Key::Down, Key::Left, Key::Right, Key::Left, Key::Right, Key::Char('b'), Key::Char('a')], Egg::Ufo),
+    (&[Key::Char('n'), Key::Char('y'), Key::Char('a'), Key::Char('n')], Egg::Nyan),
+];
+
+/// Remembers the last keys pressed to notice the secret codes typed in them
+#[derive(Default)]
+pub struct Secrets {
+    keys: Vec<Key>,
+}
+
+impl Secrets {
+    /// Add a pressed key, returning the easter egg whose code it completes
+    pub fn feed(&mut self, key: Key) -> Option<Egg> {
+        let longest = CODES.iter().map(|(code, _)| code.len()).max().unwrap_or(0);
+        self.keys.push(key);
+        if self.keys.len() > longest {
+            self.keys.remove(0);
+        }
+        let egg = CODES.iter().find(|(code, _)| self.keys.ends_with(code)).map(|&(_, egg)| egg)?;
+        self.keys.clear();
+        Some(egg)
+    }
+}
+
+/// Set off an easter egg, telling the player when they found a new one
+pub fn unlock(mt: &mut Mutes, egg: Egg) {
+    match egg {
+        Egg::Ufo => mt.ufo = Some(mt.time),
+        Egg::Nyan => mt.nyan = !mt.nyan,
+    }
+    if mt.stats.eggs.insert(egg.name()) {
+        let msg = format!("Secret found: {} ({}/{})", egg.name(), mt.stats.eggs.len(), Egg::ALL.len());
+        mt.ui.toasts.push(ToastKind::Achievement, msg);
+    }
+}
+
+/// Draw the UFO flying across the sky while it is there
+pub fn draw(mt: &mut Mutes) {
+    let Some(start) = mt.ufo else { return; };
+    let t = (mt.time - start) / UFO_TIME;
+    if t > 1.0 {
+        mt.ufo = None;
+        return;
+    }
+    let art = AsciiArt::new(UFO_ART, "");
+    let x = (-art.w as f32 + t * (mt.renderer.w + art.w) as f32) as i32;
+    let bob = (mt.time * 2.0).sin().round() as i32;
+    mt.renderer.put_ascii(&art, x, HUD_HEIGHT + 1 + bob, COLOR_UFO);
+}
+
+/// Color of the cat, going through the rainbow after the nyan code
+pub fn cat_color(mt: &Mutes, cn: &Consts) -> &'static str {
+    if !mt.nyan { return cn.colors.cat; }
+    let gradient = Gradient::new(color::RAINBOW, Direction::Columns);
+    color::code(gradient.at((mt.flicker_time() * NYAN_SPEED).rem_euclid(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    fn feed(s: &mut Secrets, keys: &[Key]) -> Vec<Egg> {
+        keys.iter().filter_map(|&k| s.feed(k)).collect()
+    }
+
+    fn typed(text: &str) -> Vec<Key> {
+        text.chars().map(Key::Char).collect()
+    }
+
+    #[test]
+    fn codes_are_noticed_among_other_keys() {
+        let mut s = Secrets::default();
+        assert_eq!(feed(&mut s, &typed("hi nyan")), vec![Egg::Nyan]);
+        let konami = CODES[0].0;
+        let keys: Vec<Key> = [Key::Left, Key::Char('p')].iter().chain(konami).copied().collect();
+        assert_eq!(feed(&mut s, &keys), vec![Egg::Ufo]);
+    }
+
+    #[test]
+    fn codes_have_to_be_typed_in_one_go() {
+        let mut s = Secrets::default();
+        assert!(feed(&mut s, &typed("nyxan")).is_empty());
+        assert!(feed(&mut s, &typed("nya")).is_empty());
+    }
+
+    #[test]
+    fn a_code_counts_once() {
+        let mut s = Secrets::default();
+        assert_eq!(feed(&mut s, &typed("nyan")), vec![Egg::Nyan]);
+        // The last n of the code doesn't start it again
+        assert!(feed(&mut s, &typed("yan")).is_empty());
+    }
+
+    #[test]
+    fn new_eggs_are_tracked_in_the_stats() {
+        let mut mt = Mutes::detached(assets::load_with(None, &[]), (80, 24));
+        unlock(&mut mt, Egg::Nyan);
+        assert!(mt.nyan);
+        unlock(&mut mt, Egg::Nyan);
+        assert!(!mt.nyan);
+        unlock(&mut mt, Egg::Ufo);
+        assert_eq!(mt.ufo, Some(mt.time));
+        assert_eq!(mt.stats.eggs.len(), Egg::ALL.len());
+    }
+}
@@ -0,0 +1,112 @@
+use rand::Rng;
+
+use crate::render::{Pixel, Renderer};
+use crate::{utils, AsciiArt};
+
+/// Chance of a flake staying on a surface it falls onto, instead of falling past it
+const LAND_CHANCE: f64 = 0.6;
+
+/// Seconds a flake rests on a surface before it melts away, at least and at most
+const REST_TIME: (f32, f32) = (2.0, 6.0);
+
+/// Chance of a flake sliding along the surface instead of resting, and the seconds it takes a cell
+const SLIDE_CHANCE: f64 = 0.3;
+const SLIDE_TIME: f32 = 0.4;
+
+/// Most flakes resting at once, so that a long blizzard doesn't bury the scene
+const MAX_FLAKES: usize = 400;
+
+/// Top row of the things drawn in each column of the screen, which snow can land on
+#[derive(Default)]
+pub struct Surfaces {
+    /// Tops found in the frame being drawn, and in the last frame drawn
+    drawing: Vec<i32>,
+    last: Vec<i32>,
+}
+
+impl Surfaces {
+    /// Mark the top of the characters of an art drawn at screen x and y
+    pub fn mark(&mut self, art: &AsciiArt, x: i32, y: i32) {
+        for (i, line) in art.art.lines().enumerate() {
+            for (j, c) in line.chars().enumerate() {
+                if c != ' ' { self.mark_cell(x + j as i32, y + i as i32); }
+            }
+        }
+    }
+
+    /// Mark the top of a box w cells wide drawn at screen x and y
+    pub fn mark_row(&mut self, x: i32, y: i32, w: i32) {
+        for col in x..x + w {
+            self.mark_cell(col, y);
+        }
+    }
+
+    fn mark_cell(&mut self, x: i32, y: i32) {
+        if x < 0 { return; }
+        let x = x as usize;
+        if self.drawing.len() <= x { self.drawing.resize(x + 1, i32::MAX); }
+        self.drawing[x] = self.drawing[x].min(y);
+    }
+
+    /// Keep the tops marked in the frame drawn for snow to land on in the next one
+    pub fn end_frame(&mut self) {
+        self.last = std::mem::take(&mut self.drawing);
+    }
+
+    /// Top row of what was drawn in a column in the last frame, if anything
+    pub fn top(&self, x: i32) -> Option<i32> {
+        usize::try_from(x).ok().and_then(|x| self.last.get(x)).copied().filter(|&y| y != i32::MAX)
+    }
+}
+
+struct Flake {
+    /// World column and screen row of the cell it rests in, right above a surface
+    x: i32,
+    y: i32,
+    color: &'static str,
+    char: char,
+    /// Game time it melts at
+    melts: f32,
+    /// Cells it slides to the right each move, or to the left when negative, and when it moves next
+    slide: i32,
+    next_slide: f32,
+}
+
+/// Snow resting on top of the trees, the house and the bubbles for a moment before melting or sliding
+/// off their edges
+#[derive(Default)]
+pub struct Settled {
+    flakes: Vec<Flake>,
+}
+
+impl Settled {
+    /// Let a flake falling into the cell above a surface at world column x rest there, by chance,
+    /// returning whether it did
+    pub fn land(&mut self, x: i32, y: i32, color: &'static str, char: char, time: f32) -> bool {
+        let mut rng = utils::rng();
+        if self.flakes.len() >= MAX_FLAKES || !rng.gen_bool(LAND_CHANCE) { return false; }
+        let melts = time + rng.gen_range(REST_TIME.0..REST_TIME.1);
+        let slide = if rng.gen_bool(SLIDE_CHANCE) { if rng.gen_bool(0.5) { 1 } else { -1 } } else { 0 };
+        self.flakes.retain(|f| (f.x, f.y) != (x, y));
+        self.flakes.push(Flake { x, y, color, char, melts, slide, next_slide: time + SLIDE_TIME });
+        true
+    }
+
+    /// Melt the flakes that rested long enough, slide the sliding ones along, and drop the ones
+    /// without a surface below them anymore, then draw the rest. Screen columns are found from the
+    /// world columns with the camera's scroll and zoom.
+    pub fn update(&mut self, surfaces: &Surfaces, renderer: &mut Renderer, time: f32, scroll: i32, zoom: i32) {
+        let column = |x: i32| (x - scroll).div_euclid(zoom);
+        self.flakes.retain_mut(|f| {
+            if time >= f.melts { return false; }
+            if f.slide != 0 && time >= f.next_slide {
+                f.x += f.slide * zoom;
+                f.next_slide = time + SLIDE_TIME;
+            }
+            surfaces.top(column(f.x)) == Some(f.y + 1)
+        });
+        for f in &self.flakes {
+            renderer.put(column(f.x), f.y, Pixel { color: f.color, char: f.char, dim: false });
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::render::Pixel;
+use crate::Mutes;
+
+/// Shadow characters, darker right below what casts it and lighter where it stretches away from the light
+const CORE: char = '▒';
+const FRINGE: char = '░';
+
+/// How far a shadow stretches past the side facing away from the light when the light is on the
+/// horizon, as a share of the width of what casts it
+const STRETCH: f32 = 0.8;
+
+/// Draw the shadow of something w world cells wide at world x onto the ground below it, stretching
+/// away from the sun the lower it is in the sky. The moon casts fainter shadows, only their soft part.
+pub fn draw(mt: &mut Mutes, x: i32, w: i32, color: &'static str) {
+    let (t, day) = mt.world.clock.light();
+
+    // The light rises on the left of the sky and sets on the right, so the shadow stretches right
+    // in the morning and left in the evening
+    let stretch = ((t - 0.5).abs() * 2.0 * STRETCH * w as f32).round() as i32;
+    let (start, end) = if t < 0.5 { (x, x + w + stretch) } else { (x - stretch, x + w) };
+
+    let scroll = mt.get_scroll();
+    for sx in mt.screen_x(start)..mt.screen_x(end) {
+        let cx = scroll + sx * mt.camera.zoom;
+        let char = if day && (x..x + w).contains(&cx) { CORE } else { FRINGE };
+        let y = mt.renderer.h - 1 - mt.world.tiles.tile(cx).height;
+        mt.renderer.put(sx, y, Pixel { color, char, dim: true });
+    }
+}
@@ -0,0 +1,40 @@
+use rand::Rng;
+
+use crate::utils;
+
+/// A temporary shake of the whole screen, for impacts like the cat landing from a jump. It is applied
+/// as an offset when the buffer is projected onto the screen, so nothing else has to know about it.
+#[derive(Clone, Copy, Default)]
+pub struct Shake {
+    /// Largest offset in cells, and the game time the shake started and how long it lasts
+    amplitude: f32,
+    start: f32,
+    duration: f32,
+}
+
+impl Shake {
+    /// Start shaking, unless a stronger shake is still going on
+    pub fn start(&mut self, amplitude: f32, duration: f32, time: f32) {
+        if amplitude >= self.strength(time) {
+            *self = Self { amplitude, start: time, duration };
+        }
+    }
+
+    /// Amplitude at a game time, easing out over the duration of the shake
+    fn strength(&self, time: f32) -> f32 {
+        let elapsed = time - self.start;
+        if self.duration <= 0.0 || !(0.0..self.duration).contains(&elapsed) { return 0.0; }
+        self.amplitude * (1.0 - elapsed / self.duration)
+    }
+
+    /// Random offset of the screen at a game time, shaking less vertically since cells are about twice
+    /// as tall as they are wide
+    pub fn offset(&self, time: f32) -> (i32, i32) {
+        let a = self.strength(time);
+        if a < 0.5 { return (0, 0); }
+        let mut rng = utils::rng();
+        let dx = rng.gen_range(-a..=a).round() as i32;
+        let dy = rng.gen_range(-a / 2.0..=a / 2.0).round() as i32;
+        (dx, dy)
+    }
+}
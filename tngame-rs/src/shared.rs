@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::save;
+
+/// Shared world constants
+const SAVE_INTERVAL: f32 = 30.0; // Seconds between saving the shared world
+const SNOW_BUILDUP: f32 = 1.0 / 600.0; // Snow cover gained per second of full snowfall
+const SNOW_MELT: f32 = 1.0 / 900.0; // Snow cover lost per second without snowfall
+const MAX_SNOWMEN: usize = 20; // The oldest snowmen melt away when more are built
+pub const SNOWMAN_WIDTH: i32 = 5; // Snowmen can't be built closer together than this
+
+/// Changes visitors made to the world, shared between all sessions of a server and kept between
+/// server restarts. Outside of server mode it is the player's own world, kept between visits.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SharedWorld {
+    /// World x of the snowmen built by visitors, oldest first
+    #[serde(default)]
+    pub snowmen: Vec<i32>,
+    /// How much of the ground is covered in snow, from 0 to 1
+    #[serde(default)]
+    pub snow_cover: f32,
+    #[serde(skip)]
+    last_save: f32,
+    /// Whether the world only lasts for the session instead of being saved, like an imported one
+    #[serde(skip)]
+    session: bool,
+}
+
+impl SharedWorld {
+    /// Location of the world file, which can be overridden with TN_WORLD
+    fn path() -> Option<PathBuf> {
+        save::data_path("TN_WORLD", "world.json")
+    }
+
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Snowmen and snow of a world imported from a file, which aren't saved into the player's own world
+    pub fn session(snowmen: Vec<i32>, snow_cover: f32) -> Self {
+        Self { snowmen, snow_cover, last_save: 0.0, session: true }
+    }
+
+    /// Let snow build up on the ground while it snows, and melt away otherwise
+    pub fn update(&mut self, dt: f32, snowfall: f32) {
+        let change = if snowfall > 0.0 { snowfall * SNOW_BUILDUP } else { -SNOW_MELT };
+        self.snow_cover = (self.snow_cover + change * dt).clamp(0.0, 1.0);
+    }
+
+    /// Build a snowman at world x, returning false if there is one in the way
+    pub fn build_snowman(&mut self, x: i32) -> bool {
+        if self.snowmen.iter().any(|s| (s - x).abs() < SNOWMAN_WIDTH) { return false; }
+        self.snowmen.push(x);
+        let extra = self.snowmen.len().saturating_sub(MAX_SNOWMEN);
+        self.snowmen.drain(..extra);
+        true
+    }
+
+    /// Save the world every few seconds
+    pub fn autosave(&mut self, time: f32) {
+        if time - self.last_save < SAVE_INTERVAL { return; }
+        self.last_save = time;
+        if let Err(e) = self.save() {
+            log::warn!("Failed to save the shared world: {}", e);
+        }
+    }
+
+    /// Merge the world with the one saved by other sessions in the meantime, and save it in one rename
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = Self::path().filter(|_| !self.session) else { return Ok(()) };
+        let saved = Self::load();
+        let mut snowmen = saved.snowmen;
+        for x in std::mem::take(&mut self.snowmen) {
+            if !snowmen.contains(&x) { snowmen.push(x); }
+        }
+        let extra = snowmen.len().saturating_sub(MAX_SNOWMEN);
+        snowmen.drain(..extra);
+        self.snowmen = snowmen;
+        self.snow_cover = self.snow_cover.max(saved.snow_cover);
+
+        let dir = path.parent().ok_or_else(|| anyhow!("No folder for the shared world"))?;
+        fs::create_dir_all(dir)?;
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
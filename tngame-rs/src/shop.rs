@@ -0,0 +1,165 @@
+use crate::input::Key;
+use crate::render::Pixel;
+use crate::save::Save;
+use crate::{AsciiArt, Mutes, State};
+
+/// Where on the cat an item is worn, one item in each place at a time
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    // On the line above the ears
+    Head,
+    // Between the paws, under the face
+    Neck,
+}
+
+/// A cosmetic sold in the shop in the house
+pub struct Item {
+    pub name: &'static str,
+    pub price: u32,
+    pub slot: Slot,
+    art: &'static str,
+    color: &'static str,
+}
+
+/// Items in the order of the shop, picked with the number keys from 1
+pub const ITEMS: [Item; 4] = [
+    Item { name: "Red scarf", price: 5, slot: Slot::Neck, art: "~~~", color: "\x1b[38;2;230;80;80m" },
+    Item { name: "Bobble hat", price: 8, slot: Slot::Head, art: "_o_", color: "\x1b[38;2;120;180;255m" },
+    Item { name: "Top hat", price: 12, slot: Slot::Head, art: "▄█▄", color: "\x1b[38;2;170;140;220m" },
+    Item { name: "Crown", price: 20, slot: Slot::Head, art: "vVv", color: "\x1b[38;2;255;215;90m" },
+];
+
+/// The item picked with a key of the shop
+pub fn item(key: char) -> Option<&'static Item> {
+    key.to_digit(10).and_then(|i| ITEMS.get((i as usize).wrapping_sub(1)))
+}
+
+/// Text of the shop, shown in the cat's bubble while it is open
+pub fn menu(save: &Save, coins: u32) -> String {
+    let mut text = format!("Shop - {} coins to spend", coins);
+    for (i, item) in ITEMS.iter().enumerate() {
+        let state = if save.wearing.contains(item.name) { "wearing" }
+            else if save.owned.contains(item.name) { "owned" }
+            else { "" };
+        text.push_str(&format!("\n[{}] {:<10} {:>2}$ {}", i + 1, item.name, item.price, state));
+    }
+    text.push_str("\n[esc] leave");
+    text
+}
+
+/// Buy an item with coins, or put it on or take it off once it is owned, returning what the cat says
+/// about it
+pub fn pick(save: &mut Save, coins: &mut u32, item: &Item) -> String {
+    let name = item.name.to_lowercase();
+    if save.wearing.remove(item.name) {
+        return format!("Took off the {}.", name);
+    }
+    if !save.owned.contains(item.name) {
+        if *coins < item.price {
+            return format!("I need {} more coins\nfor the {}...", item.price - *coins, name);
+        }
+        *coins -= item.price;
+        save.owned.insert(item.name.to_string());
+    }
+
+    // Take off what is worn in the same place first
+    for other in ITEMS.iter().filter(|o| o.slot == item.slot) {
+        save.wearing.remove(other.name);
+    }
+    save.wearing.insert(item.name.to_string());
+    format!("Wearing the {}!\nHow do I look?", name)
+}
+
+/// Draw the items the cat is wearing onto its art, drawn at world x and screen y. Hats sit above the
+/// ears, and scarves only show where the art leaves room for them.
+pub fn draw(mt: &mut Mutes, art: &AsciiArt, x: i32, y: i32) {
+    let lines: Vec<&str> = art.art.lines().collect();
+    let Some(head) = lines.first().and_then(|l| l.chars().position(|c| c == '/')).map(|p| p as i32 + 2) else { return; };
+    for item in ITEMS.iter().filter(|i| mt.save.wearing.contains(i.name)) {
+        let (row, line) = match item.slot {
+            Slot::Head => (y - 1, None),
+            Slot::Neck => (y + art.h - 1, lines.last()),
+        };
+        let start = head - item.art.chars().count() as i32 / 2;
+        for (j, char) in item.art.chars().enumerate() {
+            let col = start + j as i32;
+            let room = line.is_none_or(|l| matches!(l.chars().nth(col as usize), Some(' ' | '_')));
+            if !room { continue; }
+            mt.renderer.put(mt.screen_x(x + col), row, Pixel { color: item.color, char, dim: false });
+        }
+    }
+}
+
+/// Handle a key while shopping, where the number keys buy an item, or put it on or take it off once it
+/// is owned
+pub fn handle_key(mt: &mut Mutes, key: Key) {
+    match key {
+        Key::Char('q') | Key::CtrlC => mt.should_exit = true,
+        Key::Char('e') | Key::Esc => mt.state = State::Exploring,
+        Key::Char(c) => {
+            if let Some(item) = item(c) {
+                let msg = pick(&mut mt.save, &mut mt.world.inventory.coins, item);
+                mt.write_save();
+                mt.say(&msg);
+            }
+        },
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets;
+
+    #[test]
+    fn buying_spends_the_coins_and_puts_the_item_on() {
+        let (mut save, mut coins) = (Save::default(), 10);
+        assert_eq!(pick(&mut save, &mut coins, &ITEMS[1]), "Wearing the bobble hat!\nHow do I look?");
+        assert_eq!(coins, 2);
+        assert!(save.owned.contains("Bobble hat") && save.wearing.contains("Bobble hat"));
+    }
+
+    #[test]
+    fn too_few_coins_buy_nothing() {
+        let (mut save, mut coins) = (Save::default(), 3);
+        assert_eq!(pick(&mut save, &mut coins, &ITEMS[3]), "I need 17 more coins\nfor the crown...");
+        assert_eq!(coins, 3);
+        assert!(save.owned.is_empty() && save.wearing.is_empty());
+    }
+
+    #[test]
+    fn owned_items_come_off_and_on_for_free() {
+        let (mut save, mut coins) = (Save::default(), 5);
+        pick(&mut save, &mut coins, &ITEMS[0]);
+        assert_eq!(pick(&mut save, &mut coins, &ITEMS[0]), "Took off the red scarf.");
+        assert!(save.owned.contains("Red scarf") && save.wearing.is_empty());
+        pick(&mut save, &mut coins, &ITEMS[0]);
+        assert_eq!(coins, 0);
+        assert!(save.wearing.contains("Red scarf"));
+    }
+
+    #[test]
+    fn one_item_is_worn_in_each_place() {
+        let (mut save, mut coins) = (Save::default(), 50);
+        for i in [0, 1, 2] {
+            pick(&mut save, &mut coins, &ITEMS[i]);
+        }
+        assert_eq!(save.wearing.iter().collect::<Vec<_>>(), ["Red scarf", "Top hat"]);
+        assert_eq!(save.owned.len(), 3);
+    }
+
+    #[test]
+    fn purchases_are_kept_in_the_save() {
+        let mut mt = Mutes::detached(assets::load_with(None, &[]), (80, 24));
+        mt.state = State::Shopping;
+        mt.world.inventory.coins = 8;
+        handle_key(&mut mt, Key::Char('2'));
+        assert_eq!(mt.world.inventory.coins, 0);
+        let save: Save = serde_json::from_str(&serde_json::to_string(&mt.save).unwrap()).unwrap();
+        assert!(save.owned.contains("Bobble hat") && save.wearing.contains("Bobble hat"));
+        assert!(menu(&save, 0).contains("[2] Bobble hat  8$ wearing"));
+        handle_key(&mut mt, Key::Esc);
+        assert_eq!(mt.state, State::Exploring);
+    }
+}
@@ -0,0 +1,205 @@
+use rand::Rng;
+
+use crate::input::Key;
+use crate::render::Pixel;
+use crate::palette::Palette;
+use crate::{AsciiArt, Mutes, utils};
+
+/// Sled hill constants (x positions as percentages of the screen width)
+pub const HILL_START_VW: f32 = 2.0; // Where the sled hill starts rising
+pub const SLED_X_VW: f32 = 2.2; // Where the sled waits on top of the hill
+const HILL_HEIGHT: i32 = 6; // Height of the sled hill in pixels
+const HILL_STEEPNESS: i32 = 3; // Pixels walked per pixel climbed
+
+/// Downhill run constants
+const LANES: i32 = 3; // Number of lanes the sled can switch between
+const LANE_GAP: i32 = 4; // Vertical distance between two lanes in pixels
+const START_SPEED: f32 = 20.0; // Initial scroll speed in pixels per second
+const ACCELERATION: f32 = 1.5; // Scroll speed gained every second
+const MAX_SPEED: f32 = 70.0; // Maximum scroll speed in pixels per second
+const OBSTACLE_GAP: (f32, f32) = (12.0, 30.0); // Range of distances between two obstacles
+const PIXELS_PER_METER: f32 = 4.0; // Scrolled pixels per meter of distance score
+const SLED_SCREEN_X: i32 = 8; // Screen x of the sled during the run
+
+const COLOR_SLOPE: &str = "\x1b[38;2;220;235;245m";
+const COLOR_ROCK: &str = "\x1b[38;2;160;160;170m";
+const SLOPE_CHARS: [char; 4] = ['_', '_', '.', '-'];
+
+/// Elevation of the sled hill at world x, relative to the regular terrain
+pub fn hill_height(x: i32, w: i32) -> i32 {
+    let start = (w as f32 * HILL_START_VW) as i32;
+    ((x - start) / HILL_STEEPNESS).clamp(0, HILL_HEIGHT)
+}
+
+/// An obstacle on the slope
+struct Obstacle {
+    x: f32,
+    lane: i32,
+    tree: bool,
+}
+
+/// State of one downhill sled run
+pub struct Sled {
+    pub lane: i32,
+    dist: f32,
+    speed: f32,
+    obstacles: Vec<Obstacle>,
+    next_obstacle: f32,
+
+    asc_sled: AsciiArt,
+    asc_tree: AsciiArt,
+    asc_rock: AsciiArt,
+}
+
+impl Sled {
+    pub fn new() -> Self {
+        Self {
+            lane: LANES / 2,
+            dist: 0.0,
+            speed: START_SPEED,
+            obstacles: Vec::new(),
+            next_obstacle: OBSTACLE_GAP.1,
+            asc_sled: AsciiArt::new(
+                r#"
+ /\_/\
+( o o )
+\=====/"#, "Azalea"),
+            asc_tree: AsciiArt::new(
+                r#"
+ /\
+/  \
+ || "#, "Azalea"),
+            asc_rock: AsciiArt::new(
+                r#"
+ __
+(__)"#, "Azalea"),
+        }
+    }
+
+    /// Distance travelled in meters
+    pub fn distance(&self) -> u32 {
+        (self.dist / PIXELS_PER_METER) as u32
+    }
+
+    /// Move the sled up (positive) or down (negative) the lanes
+    pub fn steer(&mut self, amount: i32) {
+        self.lane = (self.lane + amount).clamp(0, LANES - 1);
+    }
+
+    /// Scroll the slope, spawn obstacles, and return false when the sled crashed into one
+    pub fn update(&mut self, dt: f32, w: i32) -> bool {
+        self.speed = (self.speed + ACCELERATION * dt).min(MAX_SPEED);
+        let step = self.speed * dt;
+        self.dist += step;
+
+        // Move obstacles towards the sled and drop the ones that left the screen
+        for o in &mut self.obstacles {
+            o.x -= step;
+        }
+        self.obstacles.retain(|o| o.x > -10.0);
+
+        // Spawn new obstacles at the right edge of the screen
+        self.next_obstacle -= step;
+        if self.next_obstacle <= 0.0 {
+            let mut rng = utils::rng();
+            self.obstacles.push(Obstacle { x: w as f32, lane: rng.gen_range(0..LANES), tree: rng.gen_bool(0.5) });
+            self.next_obstacle = rng.gen_range(OBSTACLE_GAP.0..OBSTACLE_GAP.1);
+        }
+
+        // Check for collisions with obstacles in the same lane
+        let sled = SLED_SCREEN_X..SLED_SCREEN_X + self.asc_sled.w;
+        !self.obstacles.iter().any(|o| {
+            let art = if o.tree { &self.asc_tree } else { &self.asc_rock };
+            let x = o.x.round() as i32;
+            o.lane == self.lane && x < sled.end && x + art.w > sled.start
+        })
+    }
+
+    /// Screen y of the bottom of a lane
+    fn lane_y(&self, lane: i32, h: i32) -> i32 {
+        h - 2 - lane * LANE_GAP
+    }
+
+    /// Draw the slope, the obstacles, the sled, and the score
+    pub fn draw(&self, mt: &mut Mutes, colors: &Palette) {
+        // Draw the slope texture below each lane, scrolling with the travelled distance
+        let scroll = self.dist.round() as i32;
+        for lane in 0..LANES {
+            let y = self.lane_y(lane, mt.renderer.h) + 1;
+            for x in 0..mt.renderer.w {
+                let hash = utils::hash((x + scroll) as u32 ^ lane as u32);
+                mt.renderer.put(x, y, Pixel { color: COLOR_SLOPE, char: SLOPE_CHARS[(hash % SLOPE_CHARS.len() as u32) as usize], dim: false });
+            }
+        }
+
+        // Draw the obstacles from the top lane down so lower lanes appear in front
+        for lane in (0..LANES).rev() {
+            for o in self.obstacles.iter().filter(|o| o.lane == lane) {
+                let (art, c) = if o.tree { (&self.asc_tree, colors.tree) } else { (&self.asc_rock, COLOR_ROCK) };
+                mt.renderer.put_ascii(art, o.x.round() as i32, self.lane_y(lane, mt.renderer.h) - art.h + 1, c);
+            }
+            if lane == self.lane {
+                mt.renderer.put_ascii(&self.asc_sled, SLED_SCREEN_X, self.lane_y(lane, mt.renderer.h) - self.asc_sled.h + 1, colors.cat);
+            }
+        }
+
+        // Draw the score
+        mt.renderer.put_str(2, 2, &format!("Distance: {}m   [w/s] steer", self.distance()), colors.cat);
+    }
+}
+
+/// Handle a key while sledding, where the keys steer the sled instead of moving the cat
+pub fn handle_key(mt: &mut Mutes, key: Key) {
+    let Some(sled) = &mut mt.world.sled else { return; };
+    match key {
+        Key::Char('q') | Key::CtrlC | Key::Esc => mt.should_exit = true,
+        // Steer up on w or up arrow, and down on s or down arrow
+        Key::Char('w') | Key::Up => sled.steer(1),
+        Key::Char('s') | Key::Down => sled.steer(-1),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hill_rises_from_its_start_and_levels_off() {
+        let start = (100.0 * HILL_START_VW) as i32;
+        assert_eq!(hill_height(start - 10, 100), 0);
+        assert_eq!(hill_height(start, 100), 0);
+        assert_eq!(hill_height(start + HILL_STEEPNESS * 2, 100), 2);
+        assert_eq!(hill_height(start + 1000, 100), HILL_HEIGHT);
+    }
+
+    #[test]
+    fn steering_stays_on_the_lanes() {
+        let mut sled = Sled::new();
+        assert_eq!(sled.lane, 1);
+        sled.steer(5);
+        assert_eq!(sled.lane, LANES - 1);
+        sled.steer(-5);
+        assert_eq!(sled.lane, 0);
+    }
+
+    #[test]
+    fn distance_grows_as_the_sled_speeds_up() {
+        let mut sled = Sled::new();
+        assert!(sled.update(1.0, 80));
+        assert_eq!(sled.distance(), ((START_SPEED + ACCELERATION) / PIXELS_PER_METER) as u32);
+        for _ in 0..1000 {
+            sled.update(0.1, 80);
+        }
+        assert_eq!(sled.speed, MAX_SPEED);
+    }
+
+    #[test]
+    fn crashes_into_obstacles_in_its_lane_only() {
+        let mut sled = Sled::new();
+        sled.obstacles.push(Obstacle { x: SLED_SCREEN_X as f32, lane: sled.lane, tree: true });
+        assert!(!sled.update(0.0, 80));
+        sled.steer(1);
+        assert!(sled.update(0.0, 80));
+    }
+}
@@ -0,0 +1,247 @@
+use rand::Rng;
+
+use crate::input::Key;
+use crate::particles::Particle;
+use crate::render::Pixel;
+use crate::{utils, Consts, Mutes};
+
+/// Hits it takes to win a fight
+pub const WIN_SCORE: u32 = 5;
+
+/// Throwing constants
+const FLIGHT: f32 = 0.9; // Seconds a snowball is in the air until it reaches where it was aimed at
+const GRAVITY: f32 = 40.0; // Pulls the snowballs down in pixels per second squared, so they arc
+const COOLDOWN: f32 = 0.6; // Seconds the cat needs to make the next snowball
+const NPC_THROW: (f32, f32) = (1.0, 2.2); // Seconds between two throws of the NPC
+const NPC_AIM: f32 = 5.0; // Cells the NPC misses where the cat stands by at most
+
+/// Dodging constants of the NPC
+const STANDOFF: f32 = 10.0; // Cells it backs away from the cat by before the fight
+const DODGE_RANGE: f32 = 5.0; // Cells it moves away from where the fight started by at most
+const DODGE_SPEED: f32 = 10.0; // Cells per second
+const DODGE_EVERY: (f32, f32) = (0.5, 1.5); // Seconds between picking a new spot
+
+/// Seconds someone hit flashes for
+const FLASH: f32 = 0.3;
+pub const COLOR_FLASH: &str = "\x1b[38;2;255;255;255m";
+
+const COLOR_BALL: &[&str] = &["\x1b[38;2;235;245;255m"];
+const BALL_GLYPHS: &[char] = &['o'];
+
+/// Where someone in the fight stands, as the world x and screen y of the top left corner and the size
+/// of their art
+#[derive(Clone, Copy)]
+pub struct Body {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Body {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        (self.x as f32..(self.x + self.w) as f32).contains(&x) && (self.y as f32..(self.y + self.h) as f32).contains(&y)
+    }
+}
+
+/// A snowball in the air, thrown by the cat or by the NPC
+struct Ball {
+    p: Particle,
+    by_cat: bool,
+}
+
+/// A snowball fight between the cat and an NPC, who dodges around where it started and throws back
+pub struct Fight {
+    pub npc: usize,
+    pub npc_x: f32, // World x of the NPC
+    home: f32,
+    dodge_to: f32,
+    next_dodge: f32,
+
+    /// Hits scored by the cat and by the NPC
+    pub score: (u32, u32),
+    balls: Vec<Ball>,
+    cooldown: f32,
+    next_throw: f32,
+    /// Seconds the cat and the NPC still flash for after being hit
+    flash: (f32, f32),
+}
+
+impl Fight {
+    /// Start a fight with the NPC at world x, which first backs away from the cat at cat_x
+    pub fn new(npc: usize, npc_x: i32, cat_x: i32) -> Self {
+        let x = npc_x as f32;
+        let home = x + if npc_x >= cat_x { STANDOFF } else { -STANDOFF };
+        Self {
+            npc,
+            npc_x: x,
+            home,
+            dodge_to: home,
+            next_dodge: DODGE_EVERY.1,
+            score: (0, 0),
+            balls: Vec::new(),
+            cooldown: 0.0,
+            next_throw: NPC_THROW.1,
+            flash: (0.0, 0.0),
+        }
+    }
+
+    /// Throw a snowball arcing from one body to land on world x at the middle of another
+    fn throw(&mut self, from: Body, to: Body, x: f32, by_cat: bool) {
+        let (x0, y0) = ((from.x + from.w / 2) as f32, from.y as f32);
+        let y1 = (to.y + to.h / 2) as f32;
+        let vx = (x - x0) / FLIGHT;
+        let vy = (y1 - y0 - GRAVITY * FLIGHT * FLIGHT / 2.0) / FLIGHT;
+        let p = Particle { gravity: GRAVITY, ..Particle::endless(x0, y0, vx, vy, COLOR_BALL, BALL_GLYPHS) };
+        self.balls.push(Ball { p, by_cat });
+    }
+
+    /// Throw a snowball at the NPC, unless the cat is still making one
+    pub fn throw_at_npc(&mut self, cat: Body, npc: Body) {
+        if self.cooldown > 0.0 { return; }
+        self.cooldown = COOLDOWN;
+        self.throw(cat, npc, (npc.x + npc.w / 2) as f32, true);
+    }
+
+    /// Move the snowballs and the NPC, and let it throw back. Returns where snowballs hit someone, to
+    /// burst into bits of snow there.
+    pub fn update(&mut self, dt: f32, cat: Body, npc: Body, h: i32) -> Vec<(f32, f32)> {
+        self.cooldown -= dt;
+        self.flash = ((self.flash.0 - dt).max(0.0), (self.flash.1 - dt).max(0.0));
+
+        // Dodge around from time to time
+        self.next_dodge -= dt;
+        if self.next_dodge <= 0.0 {
+            let mut rng = utils::rng();
+            self.dodge_to = self.home + rng.gen_range(-DODGE_RANGE..=DODGE_RANGE);
+            self.next_dodge = rng.gen_range(DODGE_EVERY.0..DODGE_EVERY.1);
+        }
+        let step = DODGE_SPEED * dt;
+        let dx = self.dodge_to - self.npc_x;
+        self.npc_x += dx.clamp(-step, step);
+
+        // Throw back at the cat, a bit off so it isn't hit every time
+        self.next_throw -= dt;
+        if self.next_throw <= 0.0 {
+            let (miss, next) = {
+                let mut rng = utils::rng();
+                (rng.gen_range(-NPC_AIM..=NPC_AIM), rng.gen_range(NPC_THROW.0..NPC_THROW.1))
+            };
+            self.throw(npc, cat, (cat.x + cat.w / 2) as f32 + miss, false);
+            self.next_throw = next;
+        }
+
+        // Score the snowballs hitting the one they were thrown at, and drop the ones on the ground
+        let mut hits = Vec::new();
+        for b in &mut self.balls {
+            b.p.step(dt);
+            let target = if b.by_cat { npc } else { cat };
+            if !target.contains(b.p.x, b.p.y) { continue; }
+            hits.push((b.p.x, b.p.y));
+            b.p.life = 0.0;
+            if b.by_cat {
+                self.score.0 += 1;
+                self.flash.1 = FLASH;
+            } else {
+                self.score.1 += 1;
+                self.flash.0 = FLASH;
+            }
+        }
+        self.balls.retain(|b| b.p.alive() && b.p.y < h as f32);
+        hits
+    }
+
+    /// Whether the cat won, once someone scored enough hits
+    pub fn winner(&self) -> Option<bool> {
+        if self.score.0 >= WIN_SCORE { Some(true) }
+        else if self.score.1 >= WIN_SCORE { Some(false) }
+        else { None }
+    }
+
+    /// Whether the cat, or the NPC, was just hit
+    pub fn flashing(&self, cat: bool) -> bool {
+        if cat { self.flash.0 > 0.0 } else { self.flash.1 > 0.0 }
+    }
+
+    /// Draw the snowballs in the air and the score
+    pub fn draw(&self, mt: &mut Mutes, name: &str, color: &'static str) {
+        for b in &self.balls {
+            let x = mt.screen_x(b.p.x.round() as i32);
+            mt.renderer.put(x, b.p.y.round() as i32, Pixel { color: b.p.color(), char: b.p.glyph(), dim: false });
+        }
+        let score = format!("Cat {} - {} {}   [e] throw  [a/d] dodge  [esc] give up", self.score.0, self.score.1, name);
+        mt.renderer.put_str((mt.renderer.w - score.chars().count() as i32) / 2, 2, &score, color);
+    }
+}
+
+/// Handle a key in a snowball fight, where e and enter throw a snowball and escape gives up. Other keys
+/// are left for walking around to dodge, which returns false.
+pub fn handle_key(mt: &mut Mutes, cn: &Consts, key: Key) -> bool {
+    match key {
+        Key::Char('e') | Key::Enter => mt.throw_snowball(cn),
+        Key::Esc => mt.end_fight(false),
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAT: Body = Body { x: 0, y: 10, w: 6, h: 3 };
+    const NPC: Body = Body { x: 30, y: 10, w: 6, h: 4 };
+
+    /// Run a fight for some seconds, returning the number of hits
+    fn run(f: &mut Fight, secs: f32) -> usize {
+        (0..(secs / 0.05) as usize).map(|_| f.update(0.05, CAT, NPC, 24).len()).sum()
+    }
+
+    #[test]
+    fn the_npc_backs_away_from_the_cat() {
+        assert_eq!(Fight::new(0, 50, 40).home, 50.0 + STANDOFF);
+        assert_eq!(Fight::new(0, 30, 40).home, 30.0 - STANDOFF);
+    }
+
+    #[test]
+    fn snowballs_arc_onto_the_npc() {
+        let mut f = Fight::new(0, 30, 0);
+        f.throw_at_npc(CAT, NPC);
+        // The NPC doesn't throw back before the snowball lands
+        assert_eq!(run(&mut f, FLIGHT + 0.1), 1);
+        assert_eq!(f.score, (1, 0));
+        assert!(f.flashing(false));
+        assert!(!f.flashing(true));
+        run(&mut f, FLASH);
+        assert!(!f.flashing(false));
+    }
+
+    #[test]
+    fn the_cat_needs_time_to_make_a_snowball() {
+        let mut f = Fight::new(0, 30, 0);
+        f.throw_at_npc(CAT, NPC);
+        f.throw_at_npc(CAT, NPC);
+        assert_eq!(f.balls.len(), 1);
+        f.update(COOLDOWN, CAT, NPC, 24);
+        f.throw_at_npc(CAT, NPC);
+        assert_eq!(f.balls.len(), 2);
+    }
+
+    #[test]
+    fn the_npc_throws_back() {
+        let mut f = Fight::new(0, 30, 0);
+        run(&mut f, NPC_THROW.1 + 0.05);
+        assert_eq!(f.balls.iter().filter(|b| !b.by_cat).count(), 1);
+    }
+
+    #[test]
+    fn the_first_to_five_hits_wins() {
+        let mut f = Fight::new(0, 30, 0);
+        f.score = (WIN_SCORE - 1, WIN_SCORE - 1);
+        assert_eq!(f.winner(), None);
+        f.score.1 += 1;
+        assert_eq!(f.winner(), Some(false));
+        f.score.0 += 1;
+        assert_eq!(f.winner(), Some(true));
+    }
+}
@@ -0,0 +1,24 @@
+use crate::AsciiArt;
+
+/// An animated ascii art made of frames that loop at a fixed frame rate
+pub struct Sprite {
+    frames: Vec<AsciiArt>,
+    fps: f32,
+}
+
+impl Sprite {
+    pub fn new(frames: &[&str], fps: f32, credit: &str) -> Self {
+        Self { frames: frames.iter().map(|f| AsciiArt::new(f, credit)).collect(), fps }
+    }
+
+    /// A sprite with a single frame
+    pub fn still(art: &str, credit: &str) -> Self {
+        Self::new(&[art], 0.0, credit)
+    }
+
+    /// The frame to show at a point in game time
+    pub fn frame(&self, time: f32) -> &AsciiArt {
+        let i = (time * self.fps) as usize % self.frames.len();
+        &self.frames[i]
+    }
+}
@@ -0,0 +1,24 @@
+use std::collections::BTreeSet;
+
+/// Statistics collected while playing
+#[derive(Clone, Default)]
+pub struct Stats {
+    pub sled_runs: u32,
+    pub sled_best: u32,
+    pub sled_total: u32,
+    pub fish_caught: u32,
+    pub snowball_wins: u32,
+    /// Names of the easter eggs found
+    pub eggs: BTreeSet<&'static str>,
+}
+
+impl Stats {
+    /// Record a finished sled run, returning whether it beat the previous best distance
+    pub fn record_sled(&mut self, distance: u32) -> bool {
+        self.sled_runs += 1;
+        self.sled_total += distance;
+        let best = distance > self.sled_best;
+        self.sled_best = self.sled_best.max(distance);
+        best
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::presence::Presence;
+use crate::utils;
+
+/// Stress test constants
+pub const PARTICLES: f32 = 10.0; // Snow density multiplier, far past what the settings allow
+const CROWD: usize = 40; // Cats walking around the visitor's cat
+const SPREAD: f32 = 120.0; // Farthest the crowd walks from the visitor's cat, in cells either way
+const SPEED: (u32, u32) = (4, 16); // Range of the crowd's walking speeds, in cells per second
+const PERCENTILES: [f32; 4] = [50.0, 90.0, 99.0, 99.9];
+
+/// What the crowd keeps saying, long and short, so that their bubbles crowd each other out
+const LINES: [&str; 6] = [
+    "Meow!",
+    "Is it snowing\nharder than usual?",
+    "Excuse me,\ncoming through",
+    "Has anyone seen\nthe fish around here?\nI heard there\nare plenty",
+    ":3",
+    "So many cats\ntoday...",
+];
+
+/// Loads the renderer with dozens of cats that never stop talking and ten times the snow, timing each
+/// frame to report how long they took when the game exits
+pub struct Stress {
+    report: PathBuf,
+    pub crowd: Vec<Presence>,
+    /// Time each frame took to update and draw
+    frames: Vec<Duration>,
+}
+
+impl Stress {
+    /// Spread the crowd around world x, saying their lines from the unix time in milliseconds, to
+    /// report to a file
+    pub fn new(report: PathBuf, x: i32, now: u64) -> Self {
+        let crowd = (0..CROWD as u32).map(|i| {
+            let hash = utils::hash(i ^ 0x57e55);
+            let offset = (hash % (SPREAD as u32 * 2)) as f32 - SPREAD;
+            let speed = (SPEED.0 + hash / 7 % (SPEED.1 - SPEED.0)) as f32;
+            let vx = if hash.is_multiple_of(2) { speed } else { -speed };
+            let line = LINES[i as usize % LINES.len()].to_string();
+            Presence::local(format!("cat{}", i + 1), x as f32 + offset, i as usize, vx, Some((line, now)))
+        }).collect();
+        Self { report, crowd, frames: Vec::new() }
+    }
+
+    /// Walk the crowd, turning around when too far from the visitor's cat at world x, and keep their
+    /// bubbles showing
+    pub fn update(&mut self, x: i32, dt: f32, now: u64) {
+        for c in &mut self.crowd {
+            c.shown_x += c.vx * dt;
+            let away = c.shown_x - x as f32;
+            if (away > SPREAD && c.vx > 0.0) || (away < -SPREAD && c.vx < 0.0) {
+                c.vx = -c.vx;
+            }
+            c.x = c.shown_x.round() as i32;
+            if let Some((_, at)) = &mut c.message {
+                *at = now;
+            }
+        }
+    }
+
+    pub fn record(&mut self, took: Duration) {
+        self.frames.push(took);
+    }
+
+    /// Frame times at the percentiles, with the number of frames and the slowest one
+    fn summary(&self) -> String {
+        let mut sorted = self.frames.clone();
+        sorted.sort();
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+        let mut text = format!("frames  {}\n", sorted.len());
+        let Some(&max) = sorted.last() else { return text; };
+        for p in PERCENTILES {
+            let i = ((p / 100.0 * sorted.len() as f32).ceil() as usize).clamp(1, sorted.len()) - 1;
+            text.push_str(&format!("p{:<6} {:.2}ms\n", p, ms(sorted[i])));
+        }
+        text.push_str(&format!("max     {:.2}ms\n", ms(max)));
+        text
+    }
+
+    /// Write the report, with the size of the screen and the number of snow particles it was drawn with
+    pub fn write(&self, w: i32, h: i32, particles: usize) -> Result<()> {
+        let text = format!("tngame stress test: {}x{}, {} cats, {} snow particles\n\n{}", w, h, self.crowd.len(), particles, self.summary());
+        fs::write(&self.report, text).map_err(|e| anyhow!("Failed to write the stress report {}: {}", self.report.display(), e))
+    }
+}
@@ -0,0 +1,247 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+/// Whether an environment variable is set to a truthy value, or None if it isn't set
+fn flag(var: &str) -> Option<bool> {
+    env::var(var).ok().map(|v| !matches!(v.as_str(), "" | "0" | "false" | "no"))
+}
+
+/// Whether the terminal supports OSC 8 hyperlinks, which can be forced on or off with TN_HYPERLINKS
+pub fn supports_hyperlinks() -> bool {
+    if let Some(on) = flag("TN_HYPERLINKS") { return on; }
+
+    // Terminals that announce themselves and are known to support hyperlinks
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    matches!(program.as_str(), "iTerm.app" | "WezTerm" | "vscode" | "Hyper" | "ghostty")
+        || term.contains("kitty") || term.contains("foot") || term.contains("ghostty")
+        || env::var("WT_SESSION").is_ok()
+        || env::var("KITTY_WINDOW_ID").is_ok()
+        || env::var("VTE_VERSION").ok().and_then(|v| v.parse::<u32>().ok()).is_some_and(|v| v >= 5000)
+}
+
+/// Let the Windows console interpret the escape codes the game is drawn with, which older consoles
+/// only do when asked to. Fails with a message for the player on consoles that can't at all, instead
+/// of filling them with garbled codes. Output that isn't a console, like a pipe, is left alone.
+#[cfg(windows)]
+pub fn enable_vt() -> anyhow::Result<()> {
+    use windows_sys::Win32::System::Console::{GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_OUTPUT_HANDLE};
+
+    // SAFETY: The handle comes from the process and the mode is a plain integer written by the call
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 { return Ok(()); }
+        if SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) == 0 {
+            anyhow::bail!("This console can't show colors and animations. Please run tngame in Windows Terminal, \
+                or in a console of Windows 10 or newer.");
+        }
+    }
+    Ok(())
+}
+
+/// Terminals other than the Windows console interpret escape codes on their own
+#[cfg(not(windows))]
+pub fn enable_vt() -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Size of the terminal in columns and rows
+#[cfg(unix)]
+pub fn size() -> Option<(u16, u16)> {
+    termion::terminal_size().ok()
+}
+
+/// Size of the visible window of the Windows console in columns and rows
+#[cfg(windows)]
+pub fn size() -> Option<(u16, u16)> {
+    use windows_sys::Win32::System::Console::{GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_SCREEN_BUFFER_INFO, STD_OUTPUT_HANDLE};
+
+    // SAFETY: The handle comes from the process and the info is plain data written by the call
+    unsafe {
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+        if GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) == 0 { return None; }
+        let window = info.srWindow;
+        Some(((window.Right - window.Left + 1) as u16, (window.Bottom - window.Top + 1) as u16))
+    }
+}
+
+/// Keys are read as they are pressed, without being echoed or waiting for enter, until this is dropped
+#[cfg(unix)]
+pub struct RawMode {
+    _terminal: termion::raw::RawTerminal<std::io::Stdout>,
+}
+
+#[cfg(unix)]
+pub fn raw_mode() -> Option<RawMode> {
+    use termion::raw::IntoRawMode;
+    std::io::stdout().into_raw_mode().ok().map(|t| RawMode { _terminal: t })
+}
+
+/// Keys are read as they are pressed, without being echoed or waiting for enter, until this is dropped.
+/// The console sends them as the same escape codes as other terminals.
+#[cfg(windows)]
+pub struct RawMode {
+    handle: windows_sys::Win32::Foundation::HANDLE,
+    mode: u32,
+}
+
+#[cfg(windows)]
+pub fn raw_mode() -> Option<RawMode> {
+    use windows_sys::Win32::System::Console::{GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_VIRTUAL_TERMINAL_INPUT, STD_INPUT_HANDLE};
+
+    // SAFETY: The handle comes from the process and the mode is a plain integer written by the call
+    unsafe {
+        let handle = GetStdHandle(STD_INPUT_HANDLE);
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 { return None; }
+        let raw = mode & !(ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT | ENABLE_PROCESSED_INPUT) | ENABLE_VIRTUAL_TERMINAL_INPUT;
+        if SetConsoleMode(handle, raw) == 0 { return None; }
+        Some(RawMode { handle, mode })
+    }
+}
+
+#[cfg(windows)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: The handle stays open for as long as the process runs
+        unsafe { windows_sys::Win32::System::Console::SetConsoleMode(self.handle, self.mode); }
+    }
+}
+
+/// Whether the game runs in a tmux pane or popup
+pub fn in_tmux() -> bool {
+    env::var("TMUX").is_ok_and(|v| !v.is_empty())
+}
+
+/// Size of the current tmux pane or popup asked from tmux, for when the terminal can't be measured
+/// directly, like when stdout is redirected
+pub fn tmux_pane_size() -> Option<(u16, u16)> {
+    if !in_tmux() { return None; }
+    let out = std::process::Command::new("tmux").args(["display-message", "-p", "#{pane_width}x#{pane_height}"]).output().ok()?;
+    let size = String::from_utf8(out.stdout).ok()?;
+    let (w, h) = size.trim().split_once('x')?;
+    Some((w.parse().ok()?, h.parse().ok()?))
+}
+
+/// Escape codes clearing a screen h lines tall before every frame. tmux moves the contents of fully
+/// cleared screens into the pane's history, which would fill it with a copy of every frame, so there
+/// the lines are erased one by one instead.
+pub fn clear_screen(h: i32) -> String {
+    if !in_tmux() { return "\x1b[2J".to_string(); }
+    (1..=h).map(|y| format!("\x1b[{};1H\x1b[2K", y)).collect()
+}
+
+/// Drop the background colors from an escape code setting colors, so the terminal's own background
+/// shows through
+pub fn without_background(code: &str) -> String {
+    let Some(params) = code.strip_prefix("\x1b[").and_then(|c| c.strip_suffix('m')) else {
+        return code.to_string();
+    };
+    let mut kept = Vec::new();
+    let mut p = params.split(';');
+    while let Some(param) = p.next() {
+        match param {
+            // Extended colors take more parameters, which are dropped with the background
+            "38" | "48" => {
+                let args: Vec<&str> = match p.next() {
+                    Some("2") => ["2"].into_iter().chain(p.by_ref().take(3)).collect(),
+                    Some(mode) => [mode].into_iter().chain(p.by_ref().take(1)).collect(),
+                    None => Vec::new(),
+                };
+                if param == "38" {
+                    kept.push(param);
+                    kept.extend(args);
+                }
+            }
+            _ if param.parse::<u32>().is_ok_and(|n| (40..=49).contains(&n) || (100..=107).contains(&n)) => (),
+            _ => kept.push(param),
+        }
+    }
+    if kept.is_empty() { return String::new(); }
+    format!("\x1b[{}m", kept.join(";"))
+}
+
+/// Whether the terminal can show Unicode, judging from the locale
+pub fn supports_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .find_map(|v| env::var(v).ok().filter(|v| !v.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// Colors the terminal can show
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Colors {
+    #[default]
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+}
+
+impl Colors {
+    /// Colors set with TN_COLORS ("truecolor" or "256"), defaulting to true color
+    pub fn from_env() -> Self {
+        match env::var("TN_COLORS").as_deref() {
+            Ok("256") => Colors::Ansi256,
+            _ => Colors::TrueColor,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colors::TrueColor => "truecolor",
+            Colors::Ansi256 => "256",
+        }
+    }
+
+    /// Rewrite a true color escape code like "\x1b[38;2;r;g;bm" into one the terminal can show
+    pub fn convert(self, color: &str) -> String {
+        if self == Colors::TrueColor { return color.to_string(); }
+        let Some(params) = color.strip_prefix("\x1b[").and_then(|c| c.strip_suffix('m')) else {
+            return color.to_string();
+        };
+        let p: Vec<&str> = params.split(';').collect();
+        match p.as_slice() {
+            [layer @ ("38" | "48"), "2", r, g, b] => {
+                let rgb = [r, g, b].map(|c| c.parse::<u8>().unwrap_or(0));
+                format!("\x1b[{};5;{}m", layer, ansi256(rgb))
+            }
+            _ => color.to_string(),
+        }
+    }
+}
+
+/// Closest color of the 6x6x6 cube or the grey ramp of the 256 color palette
+fn ansi256([r, g, b]: [u8; 3]) -> u8 {
+    // Grey ramp from 8 to 238 in steps of 10
+    if r.abs_diff(g) < 8 && g.abs_diff(b) < 8 {
+        let avg = (r as u32 + g as u32 + b as u32) / 3;
+        return match avg {
+            0..=4 => 16,
+            247.. => 231,
+            _ => 232 + ((avg.saturating_sub(8) + 5) / 10).min(23) as u8,
+        };
+    }
+    let cube = |c: u8| if c < 48 { 0 } else if c < 115 { 1 } else { (c - 35) / 40 };
+    16 + 36 * cube(r) + 6 * cube(g) + cube(b)
+}
+
+/// Find a web address in a text like "b'ger from ascii.co.uk/art/tree", returning its byte range and url
+pub fn find_url(text: &str) -> Option<(usize, usize, String)> {
+    let mut start = 0;
+    for word in text.split(' ') {
+        let end = start + word.len();
+        let domain = word.split('/').next().unwrap_or("");
+        if domain.contains('.') && domain.split('.').all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) {
+            let url = if word.starts_with("http") { word.to_string() } else { format!("https://{}", word) };
+            return Some((start, end, url));
+        }
+        start = end + 1;
+    }
+    None
+}
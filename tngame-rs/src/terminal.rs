@@ -0,0 +1,572 @@
+use std::{env, io};
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, stdin, stdout};
+use tokio::sync::{watch, Mutex};
+
+use crate::args::Args;
+use crate::autosave::Autosave;
+use crate::broadcast::{self, Broadcast};
+use crate::handshake::Handshake;
+use crate::export::WorldFile;
+use crate::input::{self, Parser};
+use crate::map::{Editor, Map};
+use crate::narration::Narrator;
+use crate::replay::{Header, Playback, Recorder};
+use crate::terrain::Params;
+use crate::toast::Kind as ToastKind;
+use crate::transport::{self, Compression};
+use crate::{assets, cutscene, events, handle_key, leaderboard, location, narration, presence, restore_menu, term, toast, twitch, update_frame, utils, Consts, Mutes, DEFAULT_TERM_SIZE, FRAME_TIME};
+use crate::utils::Instant;
+
+const CLEAR: &str = "\x1b[2J";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const HOME: &str = "\x1b[1;1H"; // Move the cursor to the top left corner
+const PUSH_TITLE: &str = "\x1b[22;2t"; // Save the window title on the terminal's title stack
+const POP_TITLE: &str = "\x1b[23;2t"; // Restore the saved window title
+
+/// Frames simulated before --frame prints one, so the snow is falling and the cat has settled
+const FRAME_TICKS: u32 = 20;
+
+/// Seconds the login greeter shows the animation for, unless --duration is given
+const GREET_DURATION: f32 = 10.0;
+
+/// Time between frames while the terminal doesn't have focus
+const UNFOCUSED_FRAME_TIME: Duration = Duration::from_secs(1);
+/// Time between frames once nothing was pressed and nothing on screen changed for a while
+const IDLE_AFTER: Duration = Duration::from_secs(5);
+const IDLE_FRAME_TIME: Duration = Duration::from_millis(1000 / 3);
+
+/// Size of the screen to draw on, set with TN_TERM_SIZE or asked from the terminal
+pub fn term_size() -> (i32, i32) {
+    if let Ok(size) = env::var("TN_TERM_SIZE") {
+        // Environment variable in format "widthxheight"
+        let mut split = size.split('x');
+        return (split.next().unwrap().parse().unwrap(), split.next().unwrap().parse().unwrap());
+    }
+
+    // Get the terminal size from the terminal
+    let (w, h) = term::size().or_else(term::tmux_pane_size).unwrap_or(DEFAULT_TERM_SIZE);
+    if env::var("TN_DEBUG").is_ok() {
+        println!("Terminal size: {}x{}", w, h);
+        // Press enter to continue
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+    }
+    (w as i32, h as i32)
+}
+
+/// Run the game, simulating and drawing frames in one task and writing them out in another. They only
+/// share the latest frame, so a slow terminal or connection drops frames instead of holding up the game
+/// and the input waiting for the lock. Only a game that has the terminal to itself shows the frame time
+/// and sets the window title, leaving greeters, streams and embedding apps alone.
+pub async fn start_update_loop(mt: Arc<Mutex<Mutes>>, broadcast: Option<Broadcast>, out: impl AsyncWrite + Unpin, owns_terminal: bool) -> Result<()> {
+    let (frames, latest) = watch::channel(String::new());
+    tokio::try_join!(simulate(mt.clone(), frames, broadcast.as_ref(), owns_terminal), deliver(mt, latest, broadcast.as_ref(), out))?;
+    Ok(())
+}
+
+/// Update and draw frames until the game exits, sending the ones that changed to be written out
+async fn simulate(mt: Arc<Mutex<Mutes>>, frames: watch::Sender<String>, broadcast: Option<&Broadcast>, owns_terminal: bool) -> Result<()> {
+    // The last frame drawn, and since when nothing on screen changed
+    let mut last_frame = String::new();
+    let mut unchanged_since = Instant::now();
+
+    // Start the loop
+    loop {
+        // Get the current time
+        let now = Instant::now();
+        let woken;
+
+        let mut txt: String;
+        {
+            let mut mt = mt.lock().await;
+            if mt.should_exit { break; }
+            let cn = mt.assets.clone();
+
+            // Replay the recorded input handled before this frame
+            let frame = mt.frame;
+            let due = mt.playback.as_mut().map(|p| p.due(frame)).unwrap_or_default();
+            for r in due {
+                handle_key(&mut mt, &cn, r.event, r.releases);
+            }
+            if mt.should_exit { break; }
+            woken = mt.woken;
+
+            update_frame(&mut mt, &cn, now);
+
+            // Tell what changed instead of drawing in narration mode, leaving out frames without news
+            let sentences = narration(&mt, &cn);
+            if let Some(n) = &mut mt.narrator {
+                let lines = n.narrate(sentences);
+                if !lines.is_empty() {
+                    frames.send_replace(format!("{}\r\n", lines.join("\r\n")));
+                }
+                drop(mt);
+                tokio::time::sleep(FRAME_TIME).await;
+                continue;
+            }
+
+            // Draw the buffer, time it, and print it
+            let shake = mt.camera.shake.offset(mt.time);
+            match mt.renderer.draw_buf(shake) {
+                Ok(t) => txt = t,
+                // Keep showing the last frame
+                Err(e) => {
+                    log::warn!("Failed to draw a frame: {}", e);
+                    toast::report("Couldn't draw a frame");
+                    txt = last_frame.clone();
+                }
+            }
+
+            // Update the window title when the cat's surroundings change
+            let title = format!("tngame — {}, {}", location(&mt, &cn), format!("{:?}", mt.world.clock.phase()).to_lowercase());
+            if owns_terminal && title != mt.ui.title {
+                txt.push_str(&format!("\x1b]2;{}\x07", title));
+                mt.ui.title = title;
+            }
+        }
+
+        // Leave out frames that look just like the last one
+        let changed = txt != last_frame;
+        if changed {
+            last_frame = txt.clone();
+            unchanged_since = now;
+        }
+
+        let end = Instant::now();
+
+        let draw_time = (end - now).as_secs_f32();
+        // Print draw time at 1, 1
+        if owns_terminal {
+            txt.push_str(HOME);
+            txt.push_str(&format!("\r{:.2}ms ({:.1} fps)", draw_time * 1000.0, 1.0 / draw_time));
+            if let Some(b) = broadcast.filter(|b| b.viewers() > 0) {
+                txt.push_str(&format!(" - {} watching", b.viewers()));
+            }
+        }
+
+        if changed {
+            frames.send_replace(txt);
+        }
+
+        // Use tokio to sleep for 1/20th of a second. Draw less often while the terminal is in the
+        // background, or when nothing was pressed and nothing moved for a while, waking up as soon as
+        // a key is pressed or the terminal gets focus again.
+        let idle = now - unchanged_since >= IDLE_AFTER && now - woken >= IDLE_AFTER;
+        let wait = {
+            let mt = mt.lock().await;
+            if !mt.focused { UNFOCUSED_FRAME_TIME } else if idle { IDLE_FRAME_TIME } else { mt.quality.frame_time(FRAME_TIME) }
+        };
+        if let Some(s) = &mut mt.lock().await.stress {
+            s.record(end - now);
+        }
+        let mut slept = Duration::ZERO;
+        loop {
+            tokio::time::sleep(FRAME_TIME).await;
+            slept += FRAME_TIME;
+            let mt = mt.lock().await;
+            if slept >= wait || (mt.woken != woken && mt.focused) { break; }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write out the latest frame whenever there is a new one, until the game stops sending them
+async fn deliver(mt: Arc<Mutex<Mutes>>, mut latest: watch::Receiver<String>, broadcast: Option<&Broadcast>, mut out: impl AsyncWrite + Unpin) -> Result<()> {
+    // Frames for networked front-ends may be compressed, as agreed in the handshake
+    let compression = Compression::from_env();
+
+    while latest.changed().await.is_ok() {
+        let txt = latest.borrow_and_update().clone();
+
+        // Frame end with 3 Null bytes, or a length header when compressed. The time it takes to get
+        // through tells how slow the connection is.
+        let start = Instant::now();
+        out.write_all(&compression.frame(&txt)).await?;
+        out.flush().await?;
+        // Stress tests keep drawing everything, however slow it gets
+        let mut mt = mt.lock().await;
+        let level = if mt.stress.is_some() { None } else { mt.quality.observe(start.elapsed()) };
+        drop(mt);
+        if let Some(level) = level {
+            log::info!("Changed to quality level {} for how fast frames are delivered", level);
+        }
+
+        // Mirror the frame to spectators
+        if let Some(b) = broadcast {
+            b.send(&txt);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn pull_input(mt: Arc<Mutex<Mutes>>, mut input: impl AsyncRead + Unpin) -> Result<()> {
+    // Read keyboard input in a loop
+    let mut buf = [0; 64];
+    let mut parser = Parser::new();
+    loop {
+        // Read the bytes available on stdin, which can hold several keys
+        let n = input.read(&mut buf).await?;
+        if n == 0 { break; }
+
+        {
+            let mut mt = mt.lock().await;
+            let cn = mt.assets.clone();
+            for event in parser.feed(&buf[..n]) {
+                let releases = mt.kitty;
+                handle_key(&mut mt, &cn, event, releases);
+            }
+            if mt.should_exit { break; }
+        }
+
+        // Sleep for 1/100th of a second
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    Ok(())
+}
+
+/// Run the game in the terminal with the command line arguments
+pub fn run() -> Result<()> {
+    pretty_env_logger::init();
+    let args = Args::parse()?;
+
+    // Networked front-ends describe the visitor's terminal in a handshake, and visitors of an
+    // incompatible front-end are told why instead of getting a garbled screen
+    if leaderboard::server_mode() {
+        match Handshake::from_env() {
+            Ok(hs) => hs.apply(),
+            Err(e) => {
+                log::warn!("Rejected the front-end's handshake: {}", e);
+                // End with the frame marker so that the relay passes the message on
+                print!("\r\n{}\r\n{}", e, transport::FRAME_END);
+                io::stdout().flush()?;
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // Spectators only show the frames of another session
+    if let Some(path) = &args.watch {
+        return spectate(path);
+    }
+
+    // An imported world brings its map, its terrain and its snowmen. One that doesn't load leaves the
+    // built-in world.
+    let imported = args.import_world.as_deref().and_then(|path| WorldFile::load(path).map_err(|e| {
+        log::warn!("{}", e);
+        toast::report(format!("Couldn't import the world {}", path.display()));
+    }).ok());
+
+    // Replays run with the seed, the screen size and the world of the recording. A world code also
+    // seeds the random numbers, so the world starts out the same for everyone with the code.
+    let playback = args.play_inputs.as_deref().map(Playback::load).transpose()?;
+    let world = match &playback {
+        Some(p) => p.world.as_deref().map(Params::from_code).transpose()?,
+        None => args.world.or_else(|| imported.as_ref().map(WorldFile::terrain)),
+    };
+    let seed = playback.as_ref().map_or_else(|| world.map_or_else(rand::random, |w| w.seed as u64), |p| p.seed);
+    if let Some(p) = &playback {
+        env::set_var("TN_TERM_SIZE", format!("{}x{}", p.width, p.height));
+        if let Some(date) = &p.date {
+            env::set_var("TN_DATE", date);
+        }
+    }
+    if playback.is_some() || args.record_inputs.is_some() || world.is_some() {
+        utils::seed_rng(seed);
+    }
+
+    let cn = assets::load();
+
+    // Maps replace the built-in world, which a new map being edited starts out from
+    let map = match (&args.edit, &args.map) {
+        (Some(path), _) if !path.exists() => None,
+        // A map that doesn't load leaves the built-in world, rather than no world at all
+        (Some(path), _) | (None, Some(path)) => Map::load(path).map_err(|e| {
+            log::warn!("{}", e);
+            toast::report(format!("Couldn't load the map {}", path.display()));
+        }).ok(),
+        (None, None) => imported.as_ref().map(|w| w.map.clone()),
+    };
+    let set_up = |mut mt: Mutes| {
+        mt.renderer.theme = args.theme.or(mt.assets.colors.theme).unwrap_or(mt.renderer.theme);
+        mt.reduce_motion = args.reduce_motion;
+        mt.cozy = args.cozy;
+        mt.narrator = args.narrate.then(Narrator::default);
+        if let Some(world) = world {
+            mt.world.tiles.set_terrain(world);
+        }
+        if let Some(map) = &map {
+            mt.world.load_map(map.clone());
+        }
+        if let Some(imported) = &imported {
+            mt.shared = imported.shared();
+        }
+        mt
+    };
+    let new_mutes = || set_up(Mutes::new(cn.clone(), term_size()));
+
+    // Export the world for others to import instead of playing
+    if let Some(path) = &args.export_world {
+        WorldFile::take(&new_mutes()).write(path)?;
+        println!("Exported the world to {}", path.display());
+        return Ok(());
+    }
+
+    // Print a single frame for scripts and pipelines
+    if args.frame {
+        print!("{}", plain_frame(new_mutes(), &cn));
+        return Ok(());
+    }
+
+    // Older Windows consoles have to be asked to interpret escape codes. Those that can't at all get a
+    // single frame as plain text instead of a screen full of garbled codes.
+    if !leaderboard::server_mode() && args.stream.is_none() {
+        if let Err(e) = term::enable_vt() {
+            log::warn!("{}", e);
+            println!("{}\n", e);
+            print!("{}", plain_frame(new_mutes(), &cn));
+            return Ok(());
+        }
+    }
+
+    // Streams only write frames, for a consumer that isn't a terminal
+    if let Some(path) = &args.stream {
+        return stream(new_mutes(), path, args.duration);
+    }
+
+    // Login greeters only show the animation for a moment
+    if args.greet {
+        return greet(set_up(Mutes::detached(cn.clone(), term_size())), args.duration.unwrap_or(GREET_DURATION));
+    }
+
+    let mut mutes = new_mutes();
+    mutes.editor = args.edit.clone().map(Editor::new);
+    // Recordings and replays run on the game's own clock from the time of day the recording started at
+    let hours = mutes.world.clock.hours();
+    mutes.recorder = args.record_inputs.as_deref().and_then(|p| {
+        let header = Header {
+            seed, width: mutes.renderer.w, height: mutes.renderer.h, world: world.map(|w| w.code()),
+            started: presence::now_ms(), hours: Some(hours), date: Some(events::today().format("%Y-%m-%d").to_string()),
+        };
+        Recorder::create(p, &header).map_err(|e| {
+            log::warn!("Failed to create the recording {}: {}", p.display(), e);
+            toast::report("Couldn't start recording the input");
+        }).ok()
+    });
+    mutes.playback = playback;
+    if mutes.recorder.is_some() || mutes.playback.is_some() {
+        mutes.world.clock.set(mutes.playback.as_ref().and_then(|p| p.hours).unwrap_or(hours));
+    }
+    if let Some(world) = world {
+        mutes.ui.toasts.push(ToastKind::Info, format!("World code {}", world.code()));
+    }
+    mutes.renderer.transparent_bg = args.transparent_bg;
+    if let Some(report) = args.stress.clone() {
+        mutes.start_stress(&cn, report);
+    }
+
+    // Autosave while playing, and offer to restore the autosave of a visit that ended suddenly
+    let playing = mutes.editor.is_none() && mutes.playback.is_none();
+    mutes.autosave = playing && Autosave::available();
+    if let Some(a) = mutes.autosave.then(Autosave::load).flatten() {
+        mutes.recovered = Some(a);
+        mutes.paused = true;
+        mutes.ui.menu = Some(restore_menu());
+    }
+
+    // Play the intro the first time the game starts, and the story moments at the landmarks
+    mutes.cutscenes = playing;
+    if mutes.cutscenes && mutes.recovered.is_none() && !mutes.save.seen.contains(cutscene::INTRO.name) {
+        mutes.play(&cutscene::INTRO);
+    }
+    if playing {
+        mutes.visit();
+    }
+    let mt = Arc::new(Mutex::new(mutes));
+
+    // Set terminal to raw mode
+    let mut out = std::io::stdout();
+    let raw = term::raw_mode();
+    if raw.is_some() {
+        print!("Successfully set terminal to raw mode");
+    }
+
+    // Clear the screen, sent as a frame of its own so that front-ends reading compressed frames get it too
+    let setup = [CLEAR, HIDE_CURSOR, PUSH_TITLE, input::KITTY_ENABLE, input::KITTY_QUERY, input::FOCUS_ENABLE].concat();
+    out.write_all(&Compression::from_env().frame(&setup))?;
+    out.flush()?;
+
+
+    // Start update_loop and pull_input concurrently and wait for them to finish
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        // Mirror the real weather in the background if a location is configured
+        #[cfg(feature = "weather")]
+        if let Some((lat, lon)) = crate::weather::real::location() {
+            tokio::spawn(crate::weather::real::poll(mt.clone(), lat, lon));
+        }
+
+        // Reload the art while the game runs when it changes in the assets folder
+        #[cfg(feature = "hot-reload")]
+        if let Some(dir) = assets::dir() {
+            let mt = mt.clone();
+            std::thread::spawn(move || assets::watch(dir, Vec::new(), mt));
+        }
+
+        // Let a stream's chat vote on what the cat does if a channel is configured
+        if let Some(channel) = twitch::channel() {
+            tokio::spawn(twitch::poll(mt.clone(), channel));
+        }
+
+        // Walk with a game controller in the background
+        #[cfg(feature = "gamepad")]
+        {
+            let mt = mt.clone();
+            std::thread::spawn(move || crate::gamepad::poll(mt));
+        }
+
+        // Mirror the session to spectators watching the socket
+        let broadcast = args.broadcast.as_deref().map(Broadcast::listen).transpose()?;
+
+        let update_loop = start_update_loop(mt.clone(), broadcast, stdout(), true);
+        let pull_input = pull_input(mt.clone(), stdin());
+        tokio::try_join!(update_loop, pull_input)?;
+        Ok::<(), Error>(())
+    })?;
+
+    // Leave the shared world, saving what was changed in it
+    {
+        let mut mt = mt.blocking_lock();
+        if let Some(v) = &mt.visitors {
+            v.leave();
+        }
+        if let Err(e) = mt.shared.save() {
+            log::warn!("Failed to save the shared world: {}", e);
+        }
+
+        // Report how long the frames of a stress test took
+        if let Some(s) = &mt.stress {
+            let particles = mt.active_snow();
+            if let Err(e) = s.write(mt.renderer.w, mt.renderer.h, particles) {
+                log::warn!("{}", e);
+            }
+        }
+
+        // Quitting ends the visit, while the autosave is kept if the game stopped any other way
+        if mt.autosave && mt.should_exit {
+            Autosave::remove();
+        }
+    }
+
+    // Reset the terminal
+    out.write_all(SHOW_CURSOR.as_ref())?;
+    out.write_all(input::KITTY_DISABLE.as_ref())?;
+    out.write_all(input::FOCUS_DISABLE.as_ref())?;
+    out.write_all(POP_TITLE.as_ref())?;
+    out.write_all(CLEAR.as_ref())?;
+    out.write_all("\r\nThanks for visiting <3\n".as_ref())?;
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Simulate a few frames and draw the last one as plain text
+fn plain_frame(mut mt: Mutes, cn: &Consts) -> String {
+    let (mut now, mut frame) = (mt.last_update, String::new());
+    for _ in 0..FRAME_TICKS {
+        now += FRAME_TIME;
+        update_frame(&mut mt, cn, now);
+        frame = mt.renderer.draw_plain();
+    }
+    frame
+}
+
+/// Show the animation as a login greeter for a number of seconds, ending early on the first key. Keys
+/// are only read when stdin is a terminal, nothing is loaded or saved, and the window title is left
+/// alone, so it is safe to run from shell rc files and SSH banners.
+fn greet(mutes: Mutes, duration: f32) -> Result<()> {
+    let mt = Arc::new(Mutex::new(mutes));
+    let mut out = std::io::stdout();
+    let raw = io::stdin().is_terminal().then(term::raw_mode).flatten();
+    out.write_all([CLEAR, HIDE_CURSOR].concat().as_bytes())?;
+    out.flush()?;
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let shown = rt.block_on(async {
+        let key = async {
+            if raw.is_none() { return std::future::pending().await; }
+            let mut buf = [0; 16];
+            stdin().read(&mut buf).await
+        };
+        tokio::select! {
+            r = start_update_loop(mt.clone(), None, stdout(), false) => r,
+            _ = tokio::time::sleep(Duration::from_secs_f32(duration)) => Ok(()),
+            _ = key => Ok(()),
+        }
+    });
+    // Stdin is read on a thread that only ends with the next key, which the runtime doesn't wait for
+    rt.shutdown_background();
+
+    drop(raw);
+    out.write_all([SHOW_CURSOR, CLEAR, HOME].concat().as_bytes())?;
+    out.flush()?;
+    shown
+}
+
+/// Write frames to a file or a pipe ("-" for stdout) without a terminal or any input, for another
+/// process like a web relay to consume. Frames use the same protocol as in server mode, and are written
+/// in real time until the duration is over, the reader goes away, or the game is interrupted.
+fn stream(mutes: Mutes, path: &std::path::Path, duration: Option<f32>) -> Result<()> {
+    let mt = Arc::new(Mutex::new(mutes));
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let mut out: Box<dyn AsyncWrite + Unpin + Send> = if path.as_os_str() == "-" {
+            Box::new(stdout())
+        } else {
+            Box::new(tokio::fs::File::create(path).await
+                .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?)
+        };
+        out.write_all(&Compression::from_env().frame(&[CLEAR, HIDE_CURSOR].concat())).await?;
+
+        let duration = async {
+            match duration {
+                Some(secs) => tokio::time::sleep(Duration::from_secs_f32(secs)).await,
+                None => std::future::pending().await,
+            }
+        };
+        let streamed = tokio::select! {
+            r = start_update_loop(mt.clone(), None, out, false) => r,
+            _ = duration => Ok(()),
+            _ = tokio::signal::ctrl_c() => Ok(()),
+        };
+        // A reader closing the pipe is how streams usually end
+        match streamed {
+            Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => Ok(()),
+            r => r,
+        }
+    })
+}
+
+/// Watch a session broadcasting on the socket, without being able to play
+fn spectate(path: &std::path::Path) -> Result<()> {
+    let mut out = std::io::stdout();
+    let _raw = term::raw_mode();
+    out.write_all(&Compression::from_env().frame(&[CLEAR, HIDE_CURSOR].concat()))?;
+    out.flush()?;
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let watched = rt.block_on(broadcast::watch(path));
+
+    out.write_all(SHOW_CURSOR.as_ref())?;
+    out.write_all(CLEAR.as_ref())?;
+    out.flush()?;
+    watched.map_err(|e| anyhow::anyhow!("Failed to watch {}: {}", path.display(), e))
+}
@@ -0,0 +1,106 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::utils;
+
+/// Terrain constants
+const HILL_WIDTH: i32 = 24; // Distance between two height samples in pixels
+
+/// Ice constants
+const ICE_SPACING: i32 = 40; // Width of the sections that may each contain one ice patch
+const ICE_WIDTH: i32 = 12; // Width of an ice patch in pixels
+
+/// Characters of world codes, leaving out the letters that look like digits, and the length of a
+/// code. A code holds 40 bits: the seed, then the hill height and the ice chance in 4 bits each.
+const CODE_CHARS: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_LEN: usize = 8;
+
+/// What the terrain is generated from, shared between players as a world code. The defaults generate
+/// the built-in world.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Params {
+    /// Seed of the terrain, and of the random number generator
+    pub seed: u32,
+    /// Maximum elevation of the hills in pixels, up to 15
+    pub hill_height: u32,
+    /// One in every ice_chance sections has an ice patch, from 1 to 16
+    pub ice_chance: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { seed: 0, hill_height: 4, ice_chance: 3 }
+    }
+}
+
+impl Params {
+    /// Parse a world code like 3F9KQ27M, which ignores case and dashes, and reads the letters O, I and
+    /// L mistaken for digits as the digits
+    pub fn from_code(code: &str) -> Result<Self> {
+        let chars: Vec<char> = code.chars().filter(|c| *c != '-').map(|c| c.to_ascii_uppercase()).collect();
+        if chars.len() != CODE_LEN { bail!("A world code has {} characters: {}", CODE_LEN, code); }
+        let mut bits = 0u64;
+        for c in chars {
+            let c = match c { 'O' => '0', 'I' | 'L' => '1', c => c };
+            let digit = CODE_CHARS.iter().position(|&d| d as char == c).ok_or_else(|| anyhow!("Invalid world code: {}", code))?;
+            bits = bits << 5 | digit as u64;
+        }
+        Ok(Self { seed: (bits >> 8) as u32, hill_height: (bits >> 4 & 0xf) as u32, ice_chance: (bits & 0xf) as u32 + 1 })
+    }
+
+    /// The world code to share
+    pub fn code(&self) -> String {
+        let bits = (self.seed as u64) << 8 | (self.hill_height as u64 & 0xf) << 4 | (self.ice_chance as u64 - 1) & 0xf;
+        (0..CODE_LEN).rev().map(|i| CODE_CHARS[(bits >> (i * 5) & 0x1f) as usize] as char).collect()
+    }
+
+    /// Smoothly interpolated elevation of the ground at world x, in pixels above the lowest ground line
+    pub fn height(&self, x: i32) -> i32 {
+        // Value noise with one sample point every hill width, so the slopes stay gentle
+        (utils::noise(self.seed, x as f32 / HILL_WIDTH as f32) * self.hill_height as f32).round() as i32
+    }
+
+    /// Whether the ground at world x is covered by an ice patch
+    pub fn is_ice(&self, x: i32) -> bool {
+        // Keep the start of the world free of ice
+        let i = x.div_euclid(ICE_SPACING);
+        if i <= 0 { return false; }
+
+        // Pick the sections with ice and the offset of the patch inside them by hashing the section
+        let hash = utils::hash(i as u32 ^ 0x1ce ^ self.seed);
+        let offset = (hash / self.ice_chance % (ICE_SPACING - ICE_WIDTH) as u32) as i32;
+        hash.is_multiple_of(self.ice_chance) && (offset..offset + ICE_WIDTH).contains(&x.rem_euclid(ICE_SPACING))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for params in [
+            Params::default(),
+            Params { seed: u32::MAX, hill_height: 15, ice_chance: 16 },
+            Params { seed: 123_456_789, hill_height: 0, ice_chance: 1 },
+        ] {
+            let code = params.code();
+            assert_eq!(code.len(), CODE_LEN);
+            assert_eq!(Params::from_code(&code).unwrap(), params);
+        }
+    }
+
+    #[test]
+    fn codes_forgive_case_dashes_and_lookalikes() {
+        let params = Params::from_code("3F9KQ27M").unwrap();
+        assert_eq!(Params::from_code("3f9k-q27m").unwrap(), params);
+        assert_eq!(Params::from_code("O0IL1111").unwrap(), Params::from_code("00111111").unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_codes() {
+        assert!(Params::from_code("3F9KQ27").is_err());
+        assert!(Params::from_code("3F9KQ27MM").is_err());
+        assert!(Params::from_code("3F9KQ27U").is_err());
+        assert!(Params::from_code("").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::env;
+
+use anyhow::{bail, Result};
+
+use crate::color::{self, Rgb};
+
+/// Bold text on a black background, and the black background the screen is cleared with
+const BOLD_ON_BLACK: &str = "1;40";
+pub const BLACK_BACKGROUND: &str = "\x1b[40m";
+
+/// Colors colorful enough to keep their hue in the high contrast theme, where every other color is white
+const MIN_SATURATION: u8 = 60;
+
+/// How the colors of the game are shown
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Theme {
+    #[default]
+    Default,
+    /// Bold and bright colors on black, without dim colors, for low quality displays and poor eyesight
+    HighContrast,
+}
+
+impl Theme {
+    /// Names of the themes, in the order the settings cycle through them
+    pub const NAMES: &'static [&'static str] = &["default", "high-contrast"];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "default" => Ok(Theme::Default),
+            "high-contrast" => Ok(Theme::HighContrast),
+            _ => bail!("Unknown theme: {} (expected default or high-contrast)", name),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    /// Theme set with TN_THEME, falling back to the default theme
+    pub fn from_env() -> Self {
+        env::var("TN_THEME").ok().and_then(|t| Self::parse(&t).ok()).unwrap_or_default()
+    }
+
+    /// Rewrite a true color escape code like "\x1b[38;2;r;g;bm" in the colors of the theme. High
+    /// contrast turns every color into the bright basic color of its hue, or bright white if it has
+    /// little color to it.
+    pub fn apply(self, color: &str) -> String {
+        if self == Theme::Default { return color.to_string(); }
+        let code = color::parse(color).map_or(97, bright);
+        format!("\x1b[{};{}m", BOLD_ON_BLACK, code)
+    }
+}
+
+/// Bright basic color closest to the hue of a color, from 91 for red to 96 for cyan, or 97 for white
+pub fn bright(rgb: Rgb) -> u8 {
+    let max = *rgb.iter().max().unwrap();
+    let min = *rgb.iter().min().unwrap();
+    if max - min < MIN_SATURATION { return 97; }
+
+    // The channels at least halfway between the weakest and the strongest make up the color
+    let mid = min + (max - min) / 2;
+    let [r, g, b] = rgb.map(|c| (c > mid) as u8);
+    90 + (r | g << 1 | b << 2)
+}
@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::terrain::Params;
+use crate::{sled, utils, X_BOUND_VW};
+
+/// Tile map constants
+const CHUNK_WIDTH: i32 = 64; // Tiles per chunk
+const KEEP_CHUNKS: i32 = 2; // Chunks kept loaded beyond each side of the screen
+
+/// What the ground of a tile is made of
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Ground {
+    Grass,
+    Ice,
+    /// A frozen pond the cat skates across
+    Pond,
+}
+
+/// One column of the world
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub ground: Ground,
+    /// Elevation of the ground in pixels above the lowest ground line, including the sled hill
+    pub height: i32,
+    /// Which of the grass characters grows on the tile, wrapping around their number
+    pub decoration: u32,
+    /// Snow cover above which snow that built up covers this tile, from 0 to 1
+    pub cover: f32,
+    /// Whether the cat can't walk onto the tile, like past the ends of the world
+    pub solid: bool,
+}
+
+/// The world as tiles keyed by world x. Tiles are generated a chunk at a time the first time they are
+/// needed, and dropped again once the camera moved far away from them.
+pub struct TileMap {
+    /// Screen width the world is laid out for, which places the sled hill and the end of the world
+    w: i32,
+    /// What the hills and the ice are generated from
+    terrain: Params,
+    chunks: RefCell<HashMap<i32, Box<[Tile]>>>,
+    /// Ground of tiles changed by a map, which generated chunks keep
+    grounds: HashMap<i32, Ground>,
+}
+
+impl TileMap {
+    pub fn new(w: i32) -> Self {
+        Self { w, terrain: Params::default(), chunks: RefCell::new(HashMap::new()), grounds: HashMap::new() }
+    }
+
+    pub fn terrain(&self) -> Params {
+        self.terrain
+    }
+
+    /// Generate the world from other terrain, keeping the ground changed by a map
+    pub fn set_terrain(&mut self, terrain: Params) {
+        self.terrain = terrain;
+        self.chunks.get_mut().clear();
+    }
+
+    /// Screen width the world is laid out for
+    pub fn w(&self) -> i32 {
+        self.w
+    }
+
+    /// Generate the tiles of a chunk from the terrain
+    fn generate(&self, chunk: i32) -> Box<[Tile]> {
+        let end = (self.w as f32 * X_BOUND_VW) as i32;
+        (chunk * CHUNK_WIDTH..(chunk + 1) * CHUNK_WIDTH).map(|x| Tile {
+            ground: self.ground(x),
+            height: self.terrain.height(x) + sled::hill_height(x, self.w),
+            decoration: utils::hash(x as u32 ^ self.terrain.seed),
+            cover: (utils::hash(x as u32 ^ 0x5a0 ^ self.terrain.seed) % 100) as f32 / 100.0,
+            solid: !(0..=end).contains(&x),
+        }).collect()
+    }
+
+    /// Ground at world x, without loading its chunk
+    pub fn ground(&self, x: i32) -> Ground {
+        self.grounds.get(&x).copied().unwrap_or(if self.terrain.is_ice(x) { Ground::Ice } else { Ground::Grass })
+    }
+
+    /// Tile at world x, loading its chunk if needed
+    pub fn tile(&self, x: i32) -> Tile {
+        let chunk = x.div_euclid(CHUNK_WIDTH);
+        let mut chunks = self.chunks.borrow_mut();
+        let tiles = chunks.entry(chunk).or_insert_with(|| self.generate(chunk));
+        tiles[x.rem_euclid(CHUNK_WIDTH) as usize]
+    }
+
+    /// Change the ground of the tile at world x, in its chunk if it is loaded and whenever it is loaded again
+    pub fn set_ground(&mut self, x: i32, ground: Ground) {
+        self.grounds.insert(x, ground);
+        if let Some(tiles) = self.chunks.get_mut().get_mut(&x.div_euclid(CHUNK_WIDTH)) {
+            tiles[x.rem_euclid(CHUNK_WIDTH) as usize].ground = ground;
+        }
+    }
+
+    /// Drop the chunks far away from the screen starting at a scroll and showing w cells of the world
+    pub fn unload(&mut self, scroll: i32, w: i32) {
+        let first = scroll.div_euclid(CHUNK_WIDTH) - KEEP_CHUNKS;
+        let last = (scroll + w).div_euclid(CHUNK_WIDTH) + KEEP_CHUNKS;
+        self.chunks.get_mut().retain(|c, _| (first..=last).contains(c));
+    }
+
+    /// Number of chunks loaded
+    pub fn loaded(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_the_chunk_of_a_tile_once() {
+        let map = TileMap::new(80);
+        assert_eq!(map.loaded(), 0);
+        map.tile(0);
+        map.tile(CHUNK_WIDTH - 1);
+        assert_eq!(map.loaded(), 1);
+        // Tiles left of the world fall in chunks of their own rather than wrapping into chunk 0
+        map.tile(-1);
+        map.tile(CHUNK_WIDTH);
+        assert_eq!(map.loaded(), 3);
+    }
+
+    #[test]
+    fn only_the_world_can_be_walked_on() {
+        let map = TileMap::new(80);
+        let end = (80.0 * X_BOUND_VW) as i32;
+        assert!(map.tile(-1).solid);
+        assert!(map.tile(-CHUNK_WIDTH * 3).solid);
+        assert!(!map.tile(0).solid);
+        assert!(!map.tile(end).solid);
+        assert!(map.tile(end + 1).solid);
+    }
+
+    #[test]
+    fn unloading_keeps_the_chunks_near_the_screen() {
+        let mut map = TileMap::new(80);
+        for chunk in 0..20 {
+            map.tile(chunk * CHUNK_WIDTH);
+        }
+        map.unload(CHUNK_WIDTH * 10, 80);
+        let near = (10 - KEEP_CHUNKS..=(CHUNK_WIDTH * 10 + 80).div_euclid(CHUNK_WIDTH) + KEEP_CHUNKS).count();
+        assert_eq!(map.loaded(), near);
+    }
+
+    #[test]
+    fn reloaded_chunks_come_back_the_same() {
+        let mut map = TileMap::new(80);
+        map.set_ground(5, Ground::Pond);
+        let before = map.tile(5);
+        map.unload(CHUNK_WIDTH * 50, 80);
+        assert_eq!(map.loaded(), 0);
+        let after = map.tile(5);
+        assert_eq!(after.ground, Ground::Pond);
+        assert_eq!((after.height, after.decoration, after.cover), (before.height, before.decoration, before.cover));
+    }
+}
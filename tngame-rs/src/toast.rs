@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::render::Renderer;
+
+/// How long a notification stays up, and how many are stacked at once while the others wait
+const DURATION: Duration = Duration::from_secs(4);
+const MAX_SHOWN: usize = 3;
+
+/// What a notification is about, which sets its color and icon
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Info,
+    Achievement,
+    Error,
+}
+
+impl Kind {
+    fn style(self) -> (&'static str, &'static str) {
+        match self {
+            Kind::Info => ("\x1b[38;2;170;200;255m", "i"),
+            Kind::Achievement => ("\x1b[38;2;255;215;100m", "*"),
+            Kind::Error => ("\x1b[38;2;255;120;120m", "!"),
+        }
+    }
+}
+
+/// Errors the game kept running after, reported from places that can't reach it, like loading the
+/// art, and shown as notifications from the next frame on
+static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Report an error the game keeps running after, to be shown as a notification
+pub fn report(text: impl Into<String>) {
+    REPORTED.lock().unwrap_or_else(|e| e.into_inner()).push(text.into());
+}
+
+struct Toast {
+    kind: Kind,
+    text: String,
+    /// Unix time in milliseconds it came up at, or None while it waits for room in the stack
+    shown: Option<u64>,
+}
+
+/// Notifications stacked in the top right corner, each going away after a few seconds. They are
+/// drawn into the frame, so things like errors don't print over the screen.
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Queue a notification, unless the same one is already up or waiting
+    pub fn push(&mut self, kind: Kind, text: impl Into<String>) {
+        let text = text.into().replace('\n', " ");
+        if self.queue.iter().any(|t| t.kind == kind && t.text == text) { return; }
+        self.queue.push_back(Toast { kind, text, shown: None });
+    }
+
+    /// Drop the notifications that were up long enough, bring up the waiting ones there is room for,
+    /// and draw them below a line of the screen, by the unix time in milliseconds
+    pub fn draw(&mut self, renderer: &mut Renderer, top: i32, now: u64) {
+        let reported = std::mem::take(&mut *REPORTED.lock().unwrap_or_else(|e| e.into_inner()));
+        for text in reported {
+            self.push(Kind::Error, text);
+        }
+        self.queue.retain(|t| t.shown.is_none_or(|at| now.saturating_sub(at) < DURATION.as_millis() as u64));
+        for (i, t) in self.queue.iter_mut().take(MAX_SHOWN).enumerate() {
+            t.shown.get_or_insert(now);
+            let (color, icon) = t.kind.style();
+            let line = format!(" {} {} ", icon, t.text);
+            renderer.put_str(renderer.w - line.chars().count() as i32 - 1, top + i as i32, &line, color);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::env;
+
+/// End of an uncompressed frame
+pub const FRAME_END: &str = "\x00\x00\x00";
+
+/// Marks a run in RLE compressed frames, followed by the run length and the repeated byte. It never
+/// appears in UTF-8 text, so everything else is copied as is
+const RLE_MARK: u8 = 0xff;
+
+/// Shortest run worth encoding
+const RLE_MIN_RUN: usize = 4;
+
+/// How frames are compressed on their way to a networked front-end. Compressed frames start with a
+/// tag byte and a 4 byte big-endian length instead of ending with null bytes
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Rle,
+    Zstd,
+}
+
+impl Compression {
+    /// Parse the name of a method, if this build supports it
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Compression::None),
+            "rle" => Some(Compression::Rle),
+            "zstd" if cfg!(feature = "zstd") => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Pick the first of the methods offered by the front-end that this build supports
+    pub fn negotiate(offered: &[String]) -> Self {
+        offered.iter().find_map(|c| Self::parse(c)).unwrap_or_default()
+    }
+
+    /// Compression set with TN_COMPRESSION, which the handshake passes on
+    pub fn from_env() -> Self {
+        env::var("TN_COMPRESSION").ok().and_then(|c| Self::parse(&c)).unwrap_or_default()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Rle => "rle",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Turn a drawn frame into the bytes written to stdout
+    pub fn frame(self, txt: &str) -> Vec<u8> {
+        let (tag, payload) = match self {
+            Compression::None => return [txt, FRAME_END].concat().into_bytes(),
+            Compression::Rle => (b'R', rle(txt.as_bytes())),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => match zstd::bulk::compress(txt.as_bytes(), 3) {
+                Ok(z) => (b'Z', z),
+                Err(e) => {
+                    log::warn!("Failed to compress a frame, sending it with RLE: {}", e);
+                    (b'R', rle(txt.as_bytes()))
+                }
+            },
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => (b'R', rle(txt.as_bytes())),
+        };
+        let mut out = Vec::with_capacity(payload.len() + 5);
+        out.push(tag);
+        out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        out.extend(payload);
+        out
+    }
+}
+
+/// Run-length encode the bytes, mostly shortening the long runs of spaces between things on screen
+fn rle(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let run = bytes[i..].iter().take(u8::MAX as usize).take_while(|&&c| c == b).count();
+        if run >= RLE_MIN_RUN || b == RLE_MARK {
+            out.extend_from_slice(&[RLE_MARK, run as u8, b]);
+        } else {
+            out.extend(std::iter::repeat_n(b, run));
+        }
+        i += run;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Undo rle, like the front-ends do
+    fn unrle(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == RLE_MARK {
+                out.extend(std::iter::repeat_n(bytes[i + 2], bytes[i + 1] as usize));
+                i += 3;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn uncompressed_frames_end_with_null_bytes() {
+        assert_eq!(Compression::None.frame("hi"), b"hi\x00\x00\x00");
+    }
+
+    #[test]
+    fn rle_frames_are_tagged_with_their_length() {
+        let txt = format!("\x1b[1;1H{}snow ❄{}", " ".repeat(300), "=".repeat(3));
+        let frame = Compression::Rle.frame(&txt);
+        assert_eq!(frame[0], b'R');
+        let len = u32::from_be_bytes(frame[1..5].try_into().unwrap()) as usize;
+        assert_eq!(len, frame.len() - 5);
+        assert!(frame.len() < txt.len());
+        assert_eq!(unrle(&frame[5..]), txt.as_bytes());
+    }
+
+    #[test]
+    fn rle_round_trips_runs_and_the_mark() {
+        let bytes = [vec![b'a'; 3], vec![b' '; 600], vec![RLE_MARK], vec![b'b'; 4]].concat();
+        assert_eq!(unrle(&rle(&bytes)), bytes);
+        assert_eq!(rle(b"abc"), b"abc");
+    }
+
+    #[test]
+    fn negotiates_the_first_supported_method() {
+        let offered = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(Compression::negotiate(&offered(&["brotli", "rle", "none"])), Compression::Rle);
+        assert_eq!(Compression::negotiate(&offered(&["brotli"])), Compression::None);
+        assert_eq!(Compression::negotiate(&[]), Compression::None);
+        let zstd = if cfg!(feature = "zstd") { Compression::Zstd } else { Compression::Rle };
+        assert_eq!(Compression::negotiate(&offered(&["zstd", "rle"])), zstd);
+    }
+}
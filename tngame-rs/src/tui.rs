@@ -0,0 +1,39 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::color;
+use crate::game::Game;
+use crate::theme::{self, Theme};
+
+/// Draws the last frame of the game into a pane of a ratatui app, cut off where the pane is smaller
+/// than the game. Build the game with the size of the pane to fill it.
+impl Widget for &Game {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (pixels, theme) = self.pixels();
+        for (y, row) in pixels.iter().enumerate().take(area.height as usize) {
+            for (x, p) in row.iter().enumerate().take(area.width as usize) {
+                let Some(p) = p else { continue };
+                if let Some(cell) = buf.cell_mut((area.x + x as u16, area.y + y as u16)) {
+                    let style = style(p.color, theme);
+                    let style = if p.dim && theme == Theme::Default { style.add_modifier(Modifier::DIM) } else { style };
+                    cell.set_char(p.char).set_style(style);
+                }
+            }
+        }
+    }
+}
+
+/// Style of a pixel's true color escape code in a theme
+fn style(code: &str, theme: Theme) -> Style {
+    let rgb = color::parse(code);
+    match theme {
+        Theme::Default => rgb.map_or_else(Style::new, |[r, g, b]| Style::new().fg(Color::Rgb(r, g, b))),
+        // Bright basic colors 91 to 97 are 9 to 15 in the 256 color palette
+        Theme::HighContrast => Style::new()
+            .fg(Color::Indexed(rgb.map_or(97, theme::bright) - 82))
+            .bg(Color::Black)
+            .add_modifier(Modifier::BOLD),
+    }
+}
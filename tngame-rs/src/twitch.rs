@@ -0,0 +1,184 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use crate::input::{Key, KeyEvent, Kind};
+use crate::{handle_key, toast, Mutes};
+
+/// Twitch chat constants
+const DEFAULT_SERVER: &str = "irc.chat.twitch.tv:6667";
+const ANONYMOUS_NICK: &str = "justinfan31415"; // Twitch lets nicks like this read chat without logging in
+const VOTE_WINDOW: Duration = Duration::from_secs(1); // Votes are counted and the winner acted out this often
+const WALK_PRESSES: usize = 4; // Key presses a winning walk vote makes, so the cat covers some ground
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Something the audience can vote for
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum Command {
+    Left,
+    Right,
+    Up,
+    Down,
+    Pet,
+    Feed,
+    Talk,
+    Snowman,
+    Reply(char),
+}
+
+impl Command {
+    /// Read a vote from a chat message like "!left" or "right"
+    fn parse(msg: &str) -> Option<Self> {
+        let word = msg.split_whitespace().next()?.trim_start_matches('!').to_lowercase();
+        Some(match word.as_str() {
+            "left" | "l" | "a" => Command::Left,
+            "right" | "r" | "d" => Command::Right,
+            "up" | "w" => Command::Up,
+            "down" | "s" => Command::Down,
+            "pet" | "p" => Command::Pet,
+            "feed" | "f" => Command::Feed,
+            "talk" | "e" | "interact" => Command::Talk,
+            "snowman" | "b" => Command::Snowman,
+            "1" | "2" | "3" => Command::Reply(word.chars().next()?),
+            _ => return None,
+        })
+    }
+
+    /// Keys the cat is controlled with when this command wins the vote
+    fn keys(self) -> Vec<Key> {
+        match self {
+            Command::Left => vec![Key::Left; WALK_PRESSES],
+            Command::Right => vec![Key::Right; WALK_PRESSES],
+            Command::Up => vec![Key::Up],
+            Command::Down => vec![Key::Down],
+            Command::Pet => vec![Key::Char('p')],
+            Command::Feed => vec![Key::Char('f')],
+            Command::Talk => vec![Key::Char('e')],
+            Command::Snowman => vec![Key::Char('b')],
+            Command::Reply(c) => vec![Key::Char(c)],
+        }
+    }
+}
+
+/// The channel whose chat controls the cat, opted into with TN_TWITCH_CHANNEL
+pub fn channel() -> Option<String> {
+    env::var("TN_TWITCH_CHANNEL").ok()
+        .map(|c| c.trim().trim_start_matches('#').to_lowercase())
+        .filter(|c| !c.is_empty())
+}
+
+/// Split an IRC chat line into the sender and the message
+fn privmsg(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let (prefix, rest) = rest.split_once(' ')?;
+    let rest = rest.strip_prefix("PRIVMSG ")?;
+    let (_, msg) = rest.split_once(" :")?;
+    Some((prefix.split('!').next()?, msg))
+}
+
+/// The command with the most votes and how many it got, or none without votes. Ties are broken by the
+/// order of the commands, so the result doesn't depend on hashing.
+fn tally(votes: impl IntoIterator<Item = Command>) -> Option<(Command, usize)> {
+    let mut counts: HashMap<Command, usize> = HashMap::new();
+    for cmd in votes {
+        *counts.entry(cmd).or_default() += 1;
+    }
+    counts.into_iter().max_by_key(|(c, n)| (*n, Reverse(*c)))
+}
+
+/// Read chat until the connection drops, acting out the most voted command every vote window. Every
+/// chatter has one vote per window, and changing their mind replaces it.
+async fn play(mt: &Arc<Mutex<Mutes>>, channel: &str) -> Result<()> {
+    let server = env::var("TN_TWITCH_SERVER").unwrap_or_else(|_| DEFAULT_SERVER.to_string());
+    let stream = TcpStream::connect(&server).await?;
+    let (read, mut write) = stream.into_split();
+    write.write_all(format!("NICK {}\r\nJOIN #{}\r\n", ANONYMOUS_NICK, channel).as_bytes()).await?;
+    log::info!("Reading votes from #{} on {}", channel, server);
+
+    let mut lines = BufReader::new(read).lines();
+    let mut votes: HashMap<String, Command> = HashMap::new();
+    let mut window = tokio::time::interval(VOTE_WINDOW);
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = line?.ok_or_else(|| anyhow!("Chat closed the connection"))?;
+                if let Some(ping) = line.strip_prefix("PING ") {
+                    write.write_all(format!("PONG {}\r\n", ping).as_bytes()).await?;
+                } else if let Some((user, msg)) = privmsg(&line) {
+                    if let Some(cmd) = Command::parse(msg) {
+                        votes.insert(user.to_string(), cmd);
+                    }
+                }
+            }
+            _ = window.tick() => {
+                let Some((cmd, count)) = tally(votes.drain().map(|(_, c)| c)) else { continue; };
+                let mut mt = mt.lock().await;
+                if mt.should_exit { return Ok(()); }
+                let cn = mt.assets.clone();
+                for key in cmd.keys() {
+                    handle_key(&mut mt, &cn, KeyEvent::new(key, Kind::Press), false);
+                }
+                log::info!("Chat voted {:?} ({} votes)", cmd, count);
+            }
+        }
+    }
+}
+
+/// Let the audience of a stream control the cat by voting in chat, reconnecting when the connection drops
+pub async fn poll(mt: Arc<Mutex<Mutes>>, channel: String) {
+    loop {
+        if let Err(e) = play(&mt, &channel).await {
+            log::warn!("Lost the connection to chat: {}", e);
+            toast::report("Lost the connection to chat, reconnecting");
+        }
+        if mt.lock().await.should_exit { return; }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_votes_from_chat_messages() {
+        assert_eq!(Command::parse("!left"), Some(Command::Left));
+        assert_eq!(Command::parse("RIGHT please"), Some(Command::Right));
+        assert_eq!(Command::parse("2"), Some(Command::Reply('2')));
+        assert_eq!(Command::parse("hello chat"), None);
+        assert_eq!(Command::parse(""), None);
+    }
+
+    #[test]
+    fn splits_chat_lines_into_sender_and_message() {
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #cozycat :!pet the cat";
+        assert_eq!(privmsg(line), Some(("viewer", "!pet the cat")));
+        assert_eq!(privmsg(":tmi.twitch.tv 001 justinfan31415 :Welcome, GLHF!"), None);
+        assert_eq!(privmsg("PING :tmi.twitch.tv"), None);
+    }
+
+    #[test]
+    fn the_most_votes_win() {
+        let votes = [Command::Left, Command::Pet, Command::Pet, Command::Right, Command::Pet];
+        assert_eq!(tally(votes), Some((Command::Pet, 3)));
+        assert_eq!(tally([]), None);
+    }
+
+    #[test]
+    fn ties_go_to_the_first_command() {
+        assert_eq!(tally([Command::Feed, Command::Right, Command::Left, Command::Feed, Command::Right]), Some((Command::Right, 2)));
+    }
+
+    #[test]
+    fn walk_votes_cover_some_ground() {
+        assert_eq!(Command::Left.keys(), vec![Key::Left; WALK_PRESSES]);
+        assert_eq!(Command::Reply('3').keys(), vec![Key::Char('3')]);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::emote::Emote;
+use crate::input::Key;
+use crate::photo::Photo;
+use crate::reader::Reader;
+use crate::toast::{Kind as ToastKind, Toasts};
+use crate::widget::Menu;
+use crate::{command, cowsay, guestbook, near_entity, run_command, Consts, Mutes, State, CHAT_MAX_LENGTH, CHAT_WRAP};
+
+/// What is open on screen on top of the scene, and what the visitor typed and said
+#[derive(Default)]
+pub struct Ui {
+    // Menu shown while paused, which has the settings
+    pub menu: Option<Menu>,
+
+    // Photo mode, with the camera let go of the cat
+    pub photo: Option<Photo>,
+
+    // Entity of the map that is the signpost the cat is reading, until it walks away from it
+    pub reading: Option<usize>,
+    // Text of the bulletin board being read on the whole screen
+    pub reader: Option<Reader>,
+
+    // Text typed in text input mode
+    pub input: String,
+
+    // The last chat message and emote, which the other visitors see in server mode
+    pub chat: Option<(String, u64)>,
+    pub emote: Option<(Emote, u64)>,
+
+    // Notifications in the top right corner
+    pub toasts: Toasts,
+
+    // Last window title sent to the terminal
+    pub title: String,
+}
+
+/// Handle a key typed into the guestbook message, what the cat says, a cheat command or the line of an
+/// entity in the map editor. Characters typed with an input method arrive whole, since the parser waits
+/// for all bytes of a character.
+pub fn type_key(mt: &mut Mutes, cn: &Consts, key: Key) {
+    let max = if mt.state == State::Writing { guestbook::MAX_LENGTH } else { CHAT_MAX_LENGTH };
+    match key {
+        Key::CtrlC => mt.should_exit = true,
+        Key::Esc => mt.state = State::Exploring,
+        Key::Backspace => { mt.ui.input.pop(); },
+        Key::Enter if mt.state == State::Writing => {
+            let msg = std::mem::take(&mut mt.ui.input);
+            match mt.guestbook.sign(&msg) {
+                Ok(()) => mt.say("Signed! Thanks for\nleaving a message <3"),
+                Err(e) => {
+                    log::warn!("Failed to sign the guestbook: {}", e);
+                    mt.ui.toasts.push(ToastKind::Error, "Couldn't sign the guestbook");
+                }
+            }
+            mt.state = State::Exploring;
+        },
+        Key::Enter if mt.state == State::Labeling => {
+            let text = std::mem::take(&mut mt.ui.input);
+            mt.state = State::Exploring;
+            if let Some(i) = near_entity(mt, cn) {
+                let line = cowsay::wrap(&text, CHAT_WRAP);
+                mt.world.map.set_line(i, (!line.trim().is_empty()).then_some(line));
+            }
+        },
+        Key::Enter if mt.state == State::Commanding => {
+            let line = std::mem::take(&mut mt.ui.input);
+            mt.state = State::Exploring;
+            match command::parse(&line) {
+                Ok(cmd) => run_command(mt, cn, cmd),
+                Err(e) => mt.say(&e),
+            }
+        },
+        Key::Enter => {
+            let msg = cowsay::wrap(&std::mem::take(&mut mt.ui.input), CHAT_WRAP);
+            mt.state = State::Exploring;
+            if msg.trim().is_empty() { return; }
+
+            // In server mode, the other visitors see the message too unless it floods them
+            let time = mt.time;
+            if mt.visitors.as_mut().is_some_and(|v| !v.allow_message(time)) {
+                mt.say("Slow down a little!\nToo many messages.");
+                return;
+            }
+            mt.say(&msg);
+            mt.ui.chat = Some((msg, mt.now_ms()));
+        },
+        Key::Char(c) if !c.is_control() && mt.ui.input.chars().count() < max => mt.ui.input.push(c),
+        _ => (),
+    }
+}
@@ -1,3 +1,19 @@
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Instant that can also tell the time in browsers, where the standard library's can't
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+/// Wall-clock time, which the standard library can't tell in browsers either
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub use web_time::{SystemTime, UNIX_EPOCH};
 
 // Integer hashing
 pub fn hash(mut x: u32) -> u32 {
@@ -5,4 +21,127 @@ pub fn hash(mut x: u32) -> u32 {
     x = ((x >> 16) ^ x).wrapping_mul(0x45d9f3b);
     x = (x >> 16) ^ x;
     x
-}
\ No newline at end of file
+}
+
+/// Pseudo-random value from 0 to 1 of a point of the noise lattice
+fn lattice(seed: u32, i: i32) -> f32 {
+    (hash(i as u32 ^ seed) % 1024) as f32 / 1023.0
+}
+
+/// Ease between 0 and 1 with a smoothstep curve, so noise has no kinks at the lattice points
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Value noise at x, going smoothly from 0 to 1 and back between pseudo-random values at whole numbers.
+/// Another seed gives other noise.
+pub fn noise(seed: u32, x: f32) -> f32 {
+    let i = x.floor();
+    let (a, b) = (lattice(seed, i as i32), lattice(seed, i as i32 + 1));
+    a + (b - a) * smooth(x - i)
+}
+
+/// Value noise at a point of a plane, going smoothly from 0 to 1 and back between pseudo-random values
+/// at whole coordinates
+pub fn noise_2d(seed: u32, x: f32, y: f32) -> f32 {
+    let (i, j) = (x.floor(), y.floor());
+    let row = |j: i32| {
+        let seed = hash(j as u32 ^ seed);
+        let (a, b) = (lattice(seed, i as i32), lattice(seed, i as i32 + 1));
+        a + (b - a) * smooth(x - i)
+    };
+    let (a, b) = (row(j as i32), row(j as i32 + 1));
+    a + (b - a) * smooth(y - j)
+}
+
+/// Noise made of octaves of value noise, each twice as detailed and half as strong as the one before,
+/// still going from 0 to 1
+pub fn fractal(seed: u32, x: f32, octaves: u32) -> f32 {
+    octaves_sum(octaves, |o, f| noise(seed.wrapping_add(o), x * f))
+}
+
+/// Noise of a plane made of octaves of value noise like fractal
+pub fn fractal_2d(seed: u32, x: f32, y: f32, octaves: u32) -> f32 {
+    octaves_sum(octaves, |o, f| noise_2d(seed.wrapping_add(o), x * f, y * f))
+}
+
+/// Sum of octaves of noise taking the octave and its frequency, weighted by their amplitudes so that
+/// it stays from 0 to 1
+fn octaves_sum(octaves: u32, octave: impl Fn(u32, f32) -> f32) -> f32 {
+    let (mut sum, mut total, mut amplitude, mut frequency) = (0.0, 0.0, 1.0, 1.0);
+    for o in 0..octaves.max(1) {
+        sum += octave(o, frequency) * amplitude;
+        total += amplitude;
+        amplitude /= 2.0;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+static RNG: LazyLock<Mutex<StdRng>> = LazyLock::new(|| Mutex::new(StdRng::from_entropy()));
+
+/// Random number generator shared by the whole game, so that seeding it makes a run reproducible.
+/// Don't call this again while holding the returned guard.
+pub fn rng() -> MutexGuard<'static, StdRng> {
+    RNG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Seed the shared random number generator
+pub fn seed_rng(seed: u64) {
+    *rng() = StdRng::seed_from_u64(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn noise_hits_the_lattice_at_whole_numbers() {
+        assert!(close(noise(0, 0.0), 0.0));
+        assert!(close(noise(0, 1.0), 0.9139785));
+        assert!(close(noise(0, 2.0), 0.64907134));
+        assert!(close(noise(0, 3.0), 0.56793743));
+    }
+
+    #[test]
+    fn noise_matches_reference_values() {
+        assert!(close(noise(0, 0.5), 0.45698926));
+        assert!(close(noise(0, 1.25), 0.8725868));
+        assert!(close(noise(7, 2.5), 0.5645161));
+        assert!(close(noise(0, -0.5), 0.038611926));
+        assert!(close(noise_2d(0, 0.0, 0.0), 0.0));
+        assert!(close(noise_2d(0, 1.5, 2.5), 0.6458944));
+        assert!(close(noise_2d(3, -0.25, 0.75), 0.707961));
+        assert!(close(fractal(0, 0.5, 3), 0.2611367));
+        assert!(close(fractal_2d(1, 0.3, 0.7, 4), 0.40516636));
+    }
+
+    #[test]
+    fn one_octave_is_plain_noise() {
+        for i in -20..20 {
+            let x = i as f32 * 0.37;
+            assert_eq!(fractal(5, x, 1), noise(5, x));
+            assert_eq!(fractal_2d(5, x, -x, 1), noise_2d(5, x, -x));
+        }
+    }
+
+    #[test]
+    fn noise_stays_in_range_and_is_smooth() {
+        for i in -1000..1000 {
+            let x = i as f32 * 0.01;
+            for n in [noise(1, x), noise_2d(1, x, x * 0.5), fractal(1, x, 4), fractal_2d(1, x, 0.3, 4)] {
+                assert!((0.0..=1.0).contains(&n), "{} out of range at {}", n, x);
+            }
+            assert!((noise(1, x + 0.01) - noise(1, x)).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn seeds_give_other_noise() {
+        assert!((0..10).any(|i| noise(1, i as f32 + 0.5) != noise(2, i as f32 + 0.5)));
+    }
+}
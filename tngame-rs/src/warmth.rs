@@ -0,0 +1,52 @@
+/// Warmth constants
+const MAX: f32 = 100.0;
+const NIGHT_DRAIN: f32 = 0.6; // Warmth lost every second at night
+const BLIZZARD_DRAIN: f32 = 2.0; // Warmth lost every second at the height of a blizzard, on top of the night
+const HEAT_GAIN: f32 = 8.0; // Warmth gained every second by a fire or the house
+const COLD: f32 = 30.0; // Below this the cat shivers and walks slower
+const SLOWEST: f32 = 0.5; // Share of its speed the cat still walks at when it is freezing
+const BARS: usize = 5; // Bars shown in the meter
+
+/// Cells from a fire or the house within which the cat warms up
+pub const HEAT_RANGE: i32 = 6;
+
+/// Times a second a shivering cat shakes from one side to the other
+pub const SHIVER_RATE: f32 = 8.0;
+
+/// How warm the cat is in cozy mode, draining in the cold and refilling by a fire or the house. Being
+/// cold only slows the cat down, it never freezes.
+pub struct Warmth {
+    value: f32,
+}
+
+impl Warmth {
+    pub fn new() -> Self {
+        Self { value: MAX }
+    }
+
+    /// Cool down at night and in a blizzard as strong as blizzard from 0 to 1, or warm up by a heat
+    /// source, returning whether the cat just got cold
+    pub fn update(&mut self, dt: f32, night: bool, blizzard: f32, heated: bool) -> bool {
+        let was_cold = self.cold();
+        let drain = if night { NIGHT_DRAIN } else { 0.0 } + blizzard * BLIZZARD_DRAIN;
+        let gain = if heated { HEAT_GAIN } else { 0.0 };
+        self.value = (self.value + (gain - drain) * dt).clamp(0.0, MAX);
+        self.cold() && !was_cold
+    }
+
+    pub fn cold(&self) -> bool {
+        self.value < COLD
+    }
+
+    /// Share of its speed the cat walks at, slowing down the colder it gets below the cold line
+    pub fn pace(&self) -> f32 {
+        if !self.cold() { return 1.0; }
+        SLOWEST + (1.0 - SLOWEST) * self.value / COLD
+    }
+
+    /// The meter shown in the HUD
+    pub fn meter(&self) -> String {
+        let full = ((self.value / MAX) * BARS as f32).ceil() as usize;
+        format!("°{}{}", "▮".repeat(full), "▯".repeat(BARS - full))
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of precipitation falling from the sky
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precipitation {
+    Snow,
+    Rain,
+    Clear,
+}
+
+/// Current weather, where intensity scales the amount of particles from 0 (none) to 1 (the full snow density)
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Weather {
+    pub kind: Precipitation,
+    pub intensity: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self { kind: Precipitation::Snow, intensity: 1.0 }
+    }
+}
+
+/// Rain constants
+pub const RAIN_SPEED: f32 = 3.0; // Rain falls this many times faster than snow
+pub const RAIN_CHAR: char = '|';
+pub const COLOR_RAIN: &str = "\x1b[38;2;130;170;230m";
+
+impl Weather {
+    /// Number of particles that should be active out of the total
+    pub fn active(&self, total: usize) -> usize {
+        if self.kind == Precipitation::Clear { return 0; }
+        (total as f32 * self.intensity.clamp(0.0, 1.0)).round() as usize
+    }
+}
+
+/// Mirror the real-world weather of a location using the Open-Meteo API
+#[cfg(feature = "weather")]
+pub mod real {
+    use std::env;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use anyhow::{anyhow, Result};
+    use tokio::sync::Mutex;
+
+    use super::{Precipitation, Weather};
+    use crate::Mutes;
+
+    /// How often the weather is fetched again
+    const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Precipitation amounts at which the particles reach their full density
+    const FULL_SNOWFALL: f64 = 1.0; // cm per hour
+    const FULL_RAIN: f64 = 4.0; // mm per hour
+    const MIN_INTENSITY: f32 = 0.15; // Minimum intensity so light precipitation is still visible
+
+    /// The location to mirror, opted into with TN_WEATHER_LOCATION in the format "latitude,longitude"
+    pub fn location() -> Option<(f64, f64)> {
+        let loc = env::var("TN_WEATHER_LOCATION").ok()?;
+        let (lat, lon) = loc.split_once(',')?;
+        Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?))
+    }
+
+    /// Fetch the current weather at a location
+    fn fetch(lat: f64, lon: f64) -> Result<Weather> {
+        let url = format!("https://api.open-meteo.com/v1/forecast?latitude={lat}&longitude={lon}&current=weather_code,snowfall,rain");
+        let json: serde_json::Value = ureq::get(&url).timeout(TIMEOUT).call()?.into_json()?;
+        let current = &json["current"];
+        let code = current["weather_code"].as_u64().ok_or_else(|| anyhow!("Missing weather code"))?;
+        let snowfall = current["snowfall"].as_f64().unwrap_or(0.0);
+        let rain = current["rain"].as_f64().unwrap_or(0.0);
+
+        // Map WMO weather codes to precipitation kinds, and the amounts to intensities
+        let scale = |amount: f64, full: f64| ((amount / full) as f32).clamp(MIN_INTENSITY, 1.0);
+        Ok(match code {
+            71..=77 | 85 | 86 => Weather { kind: Precipitation::Snow, intensity: scale(snowfall, FULL_SNOWFALL) },
+            51..=67 | 80..=82 | 95..=99 => Weather { kind: Precipitation::Rain, intensity: scale(rain, FULL_RAIN) },
+            _ => Weather { kind: Precipitation::Clear, intensity: 0.0 },
+        })
+    }
+
+    /// Keep the in-game weather in sync with the real weather, keeping the last known weather when offline
+    pub async fn poll(mt: Arc<Mutex<Mutes>>, lat: f64, lon: f64) {
+        loop {
+            match tokio::task::spawn_blocking(move || fetch(lat, lon)).await {
+                Ok(Ok(weather)) => {
+                    log::info!("Real weather: {:?}", weather);
+                    mt.lock().await.world.weather = weather;
+                }
+                Ok(Err(e)) => log::warn!("Failed to fetch the weather: {}", e),
+                Err(e) => log::warn!("Weather task failed: {}", e),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
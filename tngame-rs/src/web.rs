@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+
+use crate::game::{Game, GameBuilder};
+use crate::input::Parser;
+
+/// The game running in a browser terminal like xterm.js, which passes on what the visitor types and
+/// writes out the frames the game draws
+#[wasm_bindgen]
+pub struct WebGame {
+    game: Game,
+    parser: Parser,
+}
+
+#[wasm_bindgen]
+impl WebGame {
+    /// Game filling a terminal of a number of columns and rows, looking the same every time with a seed
+    #[wasm_bindgen(constructor)]
+    pub fn new(cols: u16, rows: u16, seed: Option<u32>) -> WebGame {
+        let mut builder = GameBuilder::new().size(cols, rows);
+        if let Some(seed) = seed {
+            builder = builder.seed(seed as u64);
+        }
+        Self { game: builder.build(), parser: Parser::new() }
+    }
+
+    /// Move the game on by some milliseconds, returning the frame to write to the terminal
+    pub fn tick(&mut self, ms: f64) -> Result<String, JsError> {
+        self.game.tick(Duration::from_secs_f64(ms.max(0.0) / 1000.0)).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(self.game.render().to_string())
+    }
+
+    /// Handle what the terminal reports the visitor typed, which can hold several keys
+    pub fn input(&mut self, data: &str) {
+        for event in self.parser.feed(data.as_bytes()) {
+            self.game.handle_key_event(event);
+        }
+    }
+
+    /// Whether the visitor quit the game
+    pub fn exited(&self) -> bool {
+        self.game.should_exit()
+    }
+}
@@ -0,0 +1,224 @@
+use crate::input::Key;
+use crate::Mutes;
+
+/// Colors of menus and text inputs, with the focused item standing out
+const COLOR_TEXT: &str = "\x1b[38;2;255;231;151m";
+const COLOR_FOCUS: &str = "\x1b[38;2;255;255;255m";
+const COLOR_FRAME: &str = "\x1b[38;2;120;130;150m";
+
+/// Marker in front of the focused item
+const FOCUS: &str = "> ";
+
+/// Cells of a slider's bar
+const SLIDER_W: usize = 10;
+
+/// Something in a menu the keyboard can change or activate
+pub enum Widget {
+    /// Activated with enter
+    Button(&'static str),
+    /// Switched with enter or the left and right keys
+    Toggle(&'static str, bool),
+    /// Moved by a step with the left and right keys, within min and max
+    Slider { label: &'static str, value: f32, min: f32, max: f32, step: f32 },
+    /// One of a few options, cycled with enter or the left and right keys
+    Choice { label: &'static str, options: &'static [&'static str], selected: usize },
+}
+
+impl Widget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Widget::Button(l) | Widget::Toggle(l, _) => l,
+            Widget::Slider { label, .. } | Widget::Choice { label, .. } => label,
+        }
+    }
+
+    /// Change the value by a number of steps, returning whether it changed
+    fn adjust(&mut self, by: i32) -> bool {
+        match self {
+            Widget::Button(_) => false,
+            Widget::Toggle(_, on) => {
+                *on = !*on;
+                true
+            }
+            Widget::Slider { value, min, max, step, .. } => {
+                let old = *value;
+                *value = (*value + by as f32 * *step).clamp(*min, *max);
+                *value != old
+            }
+            Widget::Choice { options, selected, .. } => {
+                *selected = (*selected as i32 + by).rem_euclid(options.len() as i32) as usize;
+                true
+            }
+        }
+    }
+
+    /// Line showing the widget and its value
+    fn line(&self) -> String {
+        match self {
+            Widget::Button(l) => l.to_string(),
+            Widget::Toggle(l, on) => format!("{:<14} [{}]", l, if *on { "on" } else { "off" }),
+            Widget::Slider { label, value, min, max, .. } => {
+                let filled = ((value - min) / (max - min) * SLIDER_W as f32).round() as usize;
+                format!("{:<14} [{}{}] x{:.2}", label, "#".repeat(filled), "-".repeat(SLIDER_W - filled), value)
+            }
+            Widget::Choice { label, options, selected } => format!("{:<14} < {} >", label, options[*selected]),
+        }
+    }
+}
+
+/// What a key did to a menu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// The value of the item at an index changed
+    Changed(usize),
+    /// The button at an index was activated
+    Activated(usize),
+}
+
+/// A list of widgets navigated with the arrow keys or w and s, and changed with enter, the left and
+/// right arrow keys or a and d
+pub struct Menu {
+    pub title: &'static str,
+    pub items: Vec<Widget>,
+    pub focus: usize,
+}
+
+impl Menu {
+    pub fn new(title: &'static str, items: Vec<Widget>) -> Self {
+        Self { title, items, focus: 0 }
+    }
+
+    /// Handle a key, returning what it did to the menu
+    pub fn key(&mut self, key: Key) -> Option<Action> {
+        let n = self.items.len();
+        let by = match key {
+            Key::Up | Key::Char('w') => {
+                self.focus = (self.focus + n - 1) % n;
+                return None;
+            }
+            Key::Down | Key::Char('s') => {
+                self.focus = (self.focus + 1) % n;
+                return None;
+            }
+            Key::Left | Key::Char('a') => -1,
+            Key::Right | Key::Char('d') => 1,
+            Key::Enter if matches!(self.items[self.focus], Widget::Button(_)) => return Some(Action::Activated(self.focus)),
+            Key::Enter => 1,
+            _ => return None,
+        };
+        self.items[self.focus].adjust(by).then_some(Action::Changed(self.focus))
+    }
+
+    pub fn toggle(&self, i: usize) -> bool {
+        matches!(self.items[i], Widget::Toggle(_, true))
+    }
+
+    pub fn value(&self, i: usize) -> f32 {
+        match self.items[i] {
+            Widget::Slider { value, .. } => value,
+            _ => 0.0,
+        }
+    }
+
+    pub fn selected(&self, i: usize) -> usize {
+        match self.items[i] {
+            Widget::Choice { selected, .. } => selected,
+            _ => 0,
+        }
+    }
+
+    /// Draw the menu in a frame at the center of the screen
+    pub fn draw(&self, mt: &mut Mutes) {
+        let lines: Vec<String> = self.items.iter().map(Widget::line).collect();
+        let w = lines.iter().map(|l| l.chars().count()).chain([self.title.chars().count()]).max().unwrap_or(0) + FOCUS.len() + 2;
+        let h = lines.len() as i32 + 4;
+        let (x, y) = ((mt.renderer.w - w as i32 - 2) / 2, (mt.renderer.h - h) / 2);
+
+        let rule = "-".repeat(w);
+        mt.renderer.put_str(x, y, &format!("+{}+", rule), COLOR_FRAME);
+        mt.renderer.put_str(x, y + 1, &format!("|{:^w$}|", self.title), COLOR_FRAME);
+        mt.renderer.put_str(x, y + 2, &format!("|{:w$}|", ""), COLOR_FRAME);
+        for (i, line) in lines.iter().enumerate() {
+            let (marker, color) = if i == self.focus { (FOCUS, COLOR_FOCUS) } else { ("  ", COLOR_TEXT) };
+            let row = y + 3 + i as i32;
+            mt.renderer.put_str(x, row, "|", COLOR_FRAME);
+            mt.renderer.put_str(x + 1, row, &format!(" {}{:<2$}", marker, line, w - FOCUS.len() - 1), color);
+            mt.renderer.put_str(x + w as i32 + 1, row, "|", COLOR_FRAME);
+        }
+        mt.renderer.put_str(x, y + h - 1, &format!("+{}+", rule), COLOR_FRAME);
+    }
+}
+
+/// Draw a line of text being typed on the bottom line of the screen, after a prompt and followed by
+/// hints of the keys that finish it
+pub fn text_input(mt: &mut Mutes, prompt: &str, text: &str, hints: &str) {
+    let line = format!(" {}{}_   {}", prompt, text, hints);
+    let line = format!("{:<1$}", line, mt.renderer.w as usize);
+    mt.renderer.put_str(0, mt.renderer.h - 1, &line, COLOR_TEXT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> Menu {
+        Menu::new("Settings", vec![
+            Widget::Button("Resume"),
+            Widget::Toggle("Snow", true),
+            Widget::Slider { label: "Speed", value: 1.0, min: 0.5, max: 1.5, step: 0.25 },
+            Widget::Choice { label: "Theme", options: &["Night", "Day", "Dusk"], selected: 0 },
+        ])
+    }
+
+    #[test]
+    fn focus_wraps_around_the_items() {
+        let mut m = menu();
+        assert_eq!(m.key(Key::Up), None);
+        assert_eq!(m.focus, 3);
+        m.key(Key::Char('s'));
+        m.key(Key::Down);
+        assert_eq!(m.focus, 1);
+    }
+
+    #[test]
+    fn buttons_are_activated_with_enter_only() {
+        let mut m = menu();
+        assert_eq!(m.key(Key::Enter), Some(Action::Activated(0)));
+        assert_eq!(m.key(Key::Right), None);
+        assert_eq!(m.key(Key::Char('x')), None);
+    }
+
+    #[test]
+    fn toggles_switch_either_way() {
+        let mut m = menu();
+        m.focus = 1;
+        assert_eq!(m.key(Key::Enter), Some(Action::Changed(1)));
+        assert!(!m.toggle(1));
+        m.key(Key::Left);
+        assert!(m.toggle(1));
+    }
+
+    #[test]
+    fn sliders_step_within_their_range() {
+        let mut m = menu();
+        m.focus = 2;
+        assert_eq!(m.key(Key::Right), Some(Action::Changed(2)));
+        assert_eq!(m.value(2), 1.25);
+        m.key(Key::Char('d'));
+        // Past the end nothing changes
+        assert_eq!(m.key(Key::Right), None);
+        assert_eq!(m.value(2), 1.5);
+        assert_eq!(m.items[2].line(), "Speed          [##########] x1.50");
+    }
+
+    #[test]
+    fn choices_cycle_through_the_options() {
+        let mut m = menu();
+        m.focus = 3;
+        m.key(Key::Left);
+        assert_eq!(m.selected(3), 2);
+        m.key(Key::Enter);
+        assert_eq!(m.selected(3), 0);
+        assert_eq!(m.items[3].line(), "Theme          < Night >");
+    }
+}
@@ -0,0 +1,99 @@
+use crate::blizzard::Blizzard;
+use crate::clock::Clock;
+use crate::inventory::Inventory;
+use crate::map::Map;
+use crate::movement::Movement;
+use crate::particles::ParticleSystem;
+use crate::path::MoveTo;
+use crate::pose::Pose;
+use crate::sled::Sled;
+use crate::snowball::Fight;
+use crate::tilemap::{Ground, TileMap};
+use crate::weather::Weather;
+use crate::{chimney_smoke, create_snow, snow_count, Consts};
+
+/// Everything in the world, and the cat walking through it
+pub struct World {
+    // World x of the cat, and the position it is drawn at, which glides towards it
+    pub x: i32,
+    pub draw_x: f32,
+
+    // Where the cat is walking to on its own, when it was told to go somewhere
+    pub move_to: Option<MoveTo>,
+    pub movement: Movement,
+
+    // Sliding velocity of the cat on ice in pixels per second, and the sub-pixel distance slid
+    pub slide_v: f32,
+    pub slide_acc: f32,
+
+    // Whether the cat is sitting on a bench, and how it rests elsewhere, until it walks off
+    pub sitting: bool,
+    pub pose: Pose,
+
+    // Seconds the cat has stayed in the middle of a frozen pond, where the ice starts to crack
+    pub linger: f32,
+
+    // The ground of the world, and the content placed on it by a map file
+    pub tiles: TileMap,
+    pub map: Map,
+
+    pub clock: Clock,
+    pub weather: Weather,
+    pub blizzard: Blizzard,
+    pub snow: ParticleSystem,
+    // Smoke rising from the chimney in world space
+    pub smoke: ParticleSystem,
+    // Effects like fireworks in screen space, and the fireworks rockets flying up until they burst
+    pub effects: ParticleSystem,
+    pub rockets: ParticleSystem,
+
+    pub sled: Option<Sled>,
+    pub fight: Option<Fight>,
+    pub inventory: Inventory,
+}
+
+impl World {
+    /// The built-in world laid out for a screen w by h cells, with the cat in the middle
+    pub fn new(w: i32, h: i32, cn: &Consts) -> Self {
+        let x = (w - cn.asc_cat.w) / 2;
+        let mut world = Self {
+            x,
+            draw_x: x as f32,
+            move_to: None,
+            movement: Movement::new(),
+            slide_v: 0.0,
+            slide_acc: 0.0,
+            sitting: false,
+            pose: Pose::Stand,
+            linger: 0.0,
+            tiles: TileMap::new(w),
+            map: Map::default(),
+            clock: Clock::new(),
+            weather: Weather::default(),
+            blizzard: Blizzard::new(0.0),
+            snow: ParticleSystem { particles: create_snow(w, h, snow_count(w, h, 1.0), cn), ..Default::default() },
+            smoke: ParticleSystem { emitters: vec![chimney_smoke(cn)], ..Default::default() },
+            effects: ParticleSystem::default(),
+            rockets: ParticleSystem::default(),
+            sled: None,
+            fight: None,
+            inventory: Inventory::new(),
+        };
+        world.load_map(Map::default_world());
+        world
+    }
+
+    /// Place the content of a map in the world
+    pub fn load_map(&mut self, map: Map) {
+        for t in &map.tiles {
+            self.tiles.set_ground(t.x, t.ground);
+        }
+        for p in &map.ponds {
+            let x = p.world_x(self.tiles.w());
+            for x in x..x + p.w {
+                self.tiles.set_ground(x, Ground::Pond);
+            }
+        }
+        self.map = map;
+    }
+}